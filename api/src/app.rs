@@ -0,0 +1,180 @@
+use std::sync::{Arc, Mutex};
+
+use poem::{Endpoint, EndpointExt, Route, delete, get, post, put};
+
+use store::store::Store;
+
+use crate::routes::{
+    account::{change_password, confirm_password_reset, delete_account, request_password_reset},
+    api_key::{create_api_key, list_api_keys, revoke_api_key},
+    audit_log::list_audit_log,
+    export::{export_website_history, export_website_incidents},
+    live::live_events,
+    monitors::{export_monitors, import_monitors},
+    notification::{create_notification_channel, delete_notification_channel, list_notification_channels},
+    oidc::{oidc_callback, oidc_link, oidc_login},
+    organization::{
+        accept_invitation, create_invitation, create_organization, decline_invitation,
+        list_members, list_my_invitations, list_organization_invitations, list_organizations,
+        remove_member, revoke_invitation, update_member,
+    },
+    two_factor::{
+        disable_totp, enroll_totp, regenerate_recovery_codes, sign_in_two_factor,
+        two_factor_status, verify_totp,
+    },
+    user::{refresh_token, sign_in, sign_out, sign_out_all, sign_up},
+    website::{
+        create_website,
+        get_website,
+        list_websites,
+        update_website,
+        delete_website,
+        check_website_now,
+        pause_website,
+        resume_website,
+        get_website_status,
+        get_website_history},
+};
+use crate::auth::Mutating;
+use crate::rate_limit::LoginRateLimit;
+use crate::versioning::{created, deprecated, no_content};
+
+/// Every route of the API with its middleware and shared state. Starts the listener that
+/// feeds `/v1/websites/live`, on its own connection to `database_url`; the background
+/// worker is left to the caller.
+pub fn app(s: Arc<Mutex<Store>>, database_url: String) -> impl Endpoint {
+    let live_tx = crate::live::start_event_listener(database_url);
+    let rate_limit_config = crate::rate_limit::RateLimitConfig::from_env();
+    let login_limit = LoginRateLimit::new(
+        crate::rate_limit::backend_from_env(s.clone()),
+        rate_limit_config.clone(),
+    );
+    let oidc = crate::oidc::OidcConfig::from_env().map(|config| Arc::new(crate::oidc::OidcClient::new(config)));
+
+    routes(login_limit)
+    .around(crate::metrics::track_http)
+    .around(crate::telemetry::request_span)
+    .data(s)
+    .data(live_tx)
+    .data(oidc)
+    .data(rate_limit_config)
+}
+
+/// `Route::new()` with one `.at` per entry, paired with the paths it routes
+macro_rules! route_table {
+    ($($path:literal => $endpoint:expr),* $(,)?) => {
+        (Route::new()$(.at($path, $endpoint))*, vec![$($path),*])
+    };
+}
+
+/// The route tables on their own, without the middleware and state `app` adds
+pub(crate) fn routes(login_limit: LoginRateLimit) -> Route {
+    routes_with_paths(login_limit).0
+}
+
+/// `routes`, and every path it serves apart from the deprecated aliases, with `:param`
+/// segments as routed; lets the OpenAPI test check that nothing routed goes undocumented
+pub(crate) fn routes_with_paths(login_limit: LoginRateLimit) -> (Route, Vec<String>) {
+    let (v1, v1_paths) = route_table! {
+        "/websites" => get(list_websites).post(create_website.around(created)),
+        "/websites/live" => get(live_events),
+        "/websites/:website_id" => get(get_website).put(update_website).delete(delete_website.around(no_content)),
+        "/websites/:website_id/checks" => post(check_website_now),
+        "/websites/:website_id/pause" => post(pause_website),
+        "/websites/:website_id/resume" => post(resume_website),
+        "/websites/:website_id/status" => get(get_website_status),
+        "/websites/:website_id/history" => get(get_website_history),
+        "/websites/:website_id/history/export" => get(export_website_history),
+        "/websites/:website_id/incidents/export" => get(export_website_incidents),
+        "/sign-up" => post(sign_up).with(login_limit.clone()),
+        "/sign-in" => post(sign_in).with(login_limit.clone()),
+        "/sign-in/2fa" => post(sign_in_two_factor).with(login_limit.clone()),
+        "/password-reset" => post(request_password_reset).with(login_limit.clone()),
+        "/password-reset/confirm" => post(confirm_password_reset).with(login_limit.clone()),
+        "/account" => delete(delete_account.around(no_content)),
+        "/account/password" => put(change_password),
+        "/2fa" => get(two_factor_status),
+        "/2fa/enroll" => post(enroll_totp),
+        "/2fa/verify" => post(verify_totp),
+        "/2fa/disable" => post(disable_totp),
+        "/2fa/recovery-codes" => post(regenerate_recovery_codes),
+        "/auth/oidc/login" => get(oidc_login),
+        "/auth/oidc/link" => post(oidc_link),
+        "/auth/oidc/callback" => get(oidc_callback),
+        "/tokens/refresh" => post(refresh_token),
+        "/sign-out" => post(sign_out),
+        "/sign-out-all" => post(sign_out_all),
+        "/api-keys" => get(list_api_keys).post(create_api_key.around(created)),
+        "/api-keys/:key_id" => delete(revoke_api_key.around(no_content)),
+        "/organizations" => get(list_organizations).post(create_organization.around(created)),
+        "/organizations/:org_id/members" => get(list_members),
+        "/organizations/:org_id/members/:user_id" => put(update_member).delete(remove_member.around(no_content)),
+        "/organizations/:org_id/invitations" => get(list_organization_invitations).post(create_invitation.around(created)),
+        "/organizations/:org_id/invitations/:invitation_id" => delete(revoke_invitation.around(no_content)),
+        "/organizations/:org_id/monitors" => get(export_monitors).post(import_monitors),
+        "/organizations/:org_id/notification-channels" => get(list_notification_channels).post(create_notification_channel.around(created)),
+        "/organizations/:org_id/notification-channels/:channel_id" => delete(delete_notification_channel.around(no_content)),
+        "/invitations" => get(list_my_invitations),
+        "/invitations/:invitation_id/accept" => post(accept_invitation),
+        "/invitations/:invitation_id/decline" => post(decline_invitation),
+        "/audit-log" => get(list_audit_log),
+    };
+
+    // The original, unversioned routes; kept as deprecated aliases of `/v1`
+    let legacy = Route::new()
+    .at("/websites", get(list_websites))
+    .at("/websites/live", get(live_events))
+    .at("/website/:website_id", get(get_website).put(update_website).delete(delete_website))
+    .at("/website", post(create_website))
+    .at("/website/:website_id/check", get(check_website_now.data(Mutating)))
+    .at("/website/:website_id/pause", post(pause_website))
+    .at("/website/:website_id/resume", post(resume_website))
+    .at("/website/:website_id/status", get(get_website_status))
+    .at("/website/:website_id/history", get(get_website_history))
+    .at("/website/:website_id/history/export", get(export_website_history))
+    .at("/website/:website_id/incidents/export", get(export_website_incidents))
+    .at("/sign-up", post(sign_up).with(login_limit.clone()))
+    .at("/sign-in", post(sign_in).with(login_limit.clone()))
+    .at("/sign-in/2fa", post(sign_in_two_factor).with(login_limit.clone()))
+    .at("/password-reset", post(request_password_reset).with(login_limit.clone()))
+    .at("/password-reset/confirm", post(confirm_password_reset).with(login_limit))
+    .at("/account", delete(delete_account))
+    .at("/account/password", put(change_password))
+    .at("/2fa", get(two_factor_status))
+    .at("/2fa/enroll", post(enroll_totp))
+    .at("/2fa/verify", post(verify_totp))
+    .at("/2fa/disable", post(disable_totp))
+    .at("/2fa/recovery-codes", post(regenerate_recovery_codes))
+    .at("/auth/oidc/login", get(oidc_login))
+    .at("/auth/oidc/link", post(oidc_link))
+    .at("/auth/oidc/callback", get(oidc_callback))
+    .at("/token/refresh", post(refresh_token))
+    .at("/sign-out", post(sign_out))
+    .at("/sign-out-all", post(sign_out_all))
+    .at("/api-keys", get(list_api_keys).post(create_api_key))
+    .at("/api-keys/:key_id", delete(revoke_api_key))
+    .at("/organizations", get(list_organizations).post(create_organization))
+    .at("/organizations/:org_id/members", get(list_members))
+    .at("/organizations/:org_id/members/:user_id", put(update_member).delete(remove_member))
+    .at("/organizations/:org_id/invitations", get(list_organization_invitations).post(create_invitation))
+    .at("/organizations/:org_id/invitations/:invitation_id", delete(revoke_invitation))
+    .at("/invitations", get(list_my_invitations))
+    .at("/invitations/:invitation_id/accept", post(accept_invitation))
+    .at("/invitations/:invitation_id/decline", post(decline_invitation))
+    .at("/audit-log", get(list_audit_log));
+
+    let (top_level, top_level_paths) = route_table! {
+        "/.well-known/jwks.json" => get(crate::jwt::jwks),
+        "/metrics" => get(crate::metrics::metrics),
+        "/openapi.json" => get(crate::openapi::openapi_json),
+        "/docs" => get(crate::openapi::docs),
+    };
+
+    let paths = v1_paths.into_iter().map(|path| format!("/v1{}", path))
+    .chain(top_level_paths.into_iter().map(String::from))
+    .collect();
+    let routes = top_level
+    .nest("/v1", v1)
+    .nest("/", legacy.around(deprecated));
+    (routes, paths)
+}
@@ -0,0 +1,66 @@
+use poem::Request;
+use serde_json::{Map, Value};
+use store::models::audit_log::NewAuditEntry;
+use store::store::Store;
+
+use crate::rate_limit::RateLimitConfig;
+
+/// One mutating call, as handlers describe it. `organization_id` scopes who can read the
+/// entry; when left empty the actor's personal organization is used so account-level
+/// events (password changes, 2FA, sign-ins) show up in their own log.
+#[derive(Default)]
+pub struct AuditEvent {
+    pub action: &'static str,
+    pub target_type: &'static str,
+    pub target_id: Option<String>,
+    pub organization_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Append an entry for a change that has already happened. A failure to write is logged
+/// rather than surfaced: the change itself went through and the caller should hear so.
+pub fn record(store: &mut Store, req: &Request, actor_id: Option<&str>, event: AuditEvent) {
+    let organization_id = event.organization_id.or_else(|| {
+        let actor = actor_id?;
+        store.personal_organization(actor.to_string()).ok().map(|org| org.id)
+    });
+    // Same rule for the client address as the login limiter, from the config `app` shares
+    let trust_forwarded_for = req.data::<RateLimitConfig>().is_some_and(|config| config.trust_forwarded_for);
+    let ip = crate::rate_limit::client_ip(req, trust_forwarded_for).map(|ip| ip.to_string());
+
+    let entry = NewAuditEntry {
+        organization_id,
+        actor_id: actor_id.map(str::to_string),
+        action: event.action.to_string(),
+        target_type: event.target_type.to_string(),
+        target_id: event.target_id,
+        before: event.before,
+        after: event.after,
+        ip,
+    };
+    if let Err(e) = store.append_audit_log(entry) {
+        tracing::error!(action = event.action, error = ?e, "failed to write audit log entry");
+    }
+}
+
+/// Reduce two JSON objects to the fields that differ, for an entry's before/after pair
+pub fn diff(before: Value, after: Value) -> (Option<Value>, Option<Value>) {
+    let (Value::Object(before), Value::Object(mut after)) = (before, after) else {
+        return (None, None);
+    };
+    let mut old = Map::new();
+    let mut new = Map::new();
+    for (key, value) in before {
+        let changed = after.remove(&key).unwrap_or(Value::Null);
+        if changed != value {
+            old.insert(key.clone(), value);
+            new.insert(key, changed);
+        }
+    }
+    for (key, value) in after {
+        old.insert(key.clone(), Value::Null);
+        new.insert(key, value);
+    }
+    (Some(Value::Object(old)), Some(Value::Object(new)))
+}
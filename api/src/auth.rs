@@ -1,14 +1,33 @@
+use std::sync::{Arc, Mutex};
+
 use poem::{
     web::headers::{authorization::Bearer, Authorization, HeaderMapExt},
     FromRequest, Request, RequestBody, Result,
 };
 use poem::http::StatusCode;
+use store::store::Store;
 use crate::jwt::verify_jwt;
 
+/// Bearer tokens starting with this are API keys rather than JWTs
+pub const API_KEY_PREFIX: &str = "bu_";
+
+/// Marks a route that changes state despite a safe method, like the legacy
+/// `GET /website/:id/check`, so read-only API keys are refused there too
+#[derive(Debug, Clone, Copy)]
+pub struct Mutating;
+
+/// The authenticated caller, via either a JWT or an API key
 #[derive(Debug, Clone)]
 pub struct AuthUser(pub String);
 
-impl<'a> FromRequest<'a> for AuthUser {
+/// The authenticated caller together with the session their access token belongs to
+#[derive(Debug, Clone)]
+pub struct AuthSession {
+    pub user_id: String,
+    pub session_id: String,
+}
+
+impl<'a> FromRequest<'a> for AuthSession {
     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
         let auth_header = req
             .headers()
@@ -21,14 +40,85 @@ impl<'a> FromRequest<'a> for AuthUser {
             })?;
 
         let token = auth_header.token();
+        if token.starts_with(API_KEY_PREFIX) {
+            return Err(poem::Error::from_string(
+                "API keys cannot be used for this endpoint",
+                StatusCode::FORBIDDEN,
+            ));
+        }
         let claims = verify_jwt(token).map_err(|e| {
-            eprintln!("JWT verification error: {:?}", e);
+            tracing::warn!(error = ?e, "JWT verification failed");
             poem::Error::from_string(
                 "Invalid or expired token",
                 StatusCode::UNAUTHORIZED,
             )
         })?;
 
-        Ok(AuthUser(claims.sub))
+        // Signed tokens stay valid until they expire, so check the session wasn't signed out
+        let active = store(req)?.lock().unwrap().is_session_active(claims.sid.clone()).map_err(|e| {
+            tracing::error!(error = ?e, "failed to check session");
+            poem::Error::from_string(
+                "Failed to verify session",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })?;
+        if !active {
+            return Err(poem::Error::from_string(
+                "Session has been revoked",
+                StatusCode::UNAUTHORIZED,
+            ));
+        }
+
+        tracing::Span::current().record("user_id", claims.sub.as_str());
+        Ok(AuthSession {
+            user_id: claims.sub,
+            session_id: claims.sid,
+        })
+    }
+}
+
+impl<'a> FromRequest<'a> for AuthUser {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let api_key = req
+            .headers()
+            .typed_get::<Authorization<Bearer>>()
+            .map(|auth| auth.token().to_string())
+            .filter(|token| token.starts_with(API_KEY_PREFIX));
+
+        let Some(api_key) = api_key else {
+            let session = AuthSession::from_request(req, body).await?;
+            return Ok(AuthUser(session.user_id));
+        };
+
+        let key = store(req)?.lock().unwrap().authenticate_api_key(&api_key).map_err(|e| {
+            tracing::error!(error = ?e, "failed to check API key");
+            poem::Error::from_string(
+                "Failed to verify API key",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })?
+        .ok_or_else(|| {
+            poem::Error::from_string(
+                "Invalid, expired or revoked API key",
+                StatusCode::UNAUTHORIZED,
+            )
+        })?;
+
+        let mutating = !req.method().is_safe() || req.data::<Mutating>().is_some();
+        if key.scope == "read" && mutating {
+            return Err(poem::Error::from_string(
+                "API key is read-only",
+                StatusCode::FORBIDDEN,
+            ));
+        }
+
+        tracing::Span::current().record("user_id", key.user_id.as_str());
+        Ok(AuthUser(key.user_id))
     }
 }
+
+fn store(req: &Request) -> Result<&Arc<Mutex<Store>>> {
+    req.data::<Arc<Mutex<Store>>>().ok_or_else(|| {
+        poem::Error::from_string("Store not configured", StatusCode::INTERNAL_SERVER_ERROR)
+    })
+}
@@ -0,0 +1,89 @@
+use store::models::website::Website;
+use store::store::Store;
+
+use crate::error::{AppError, Entity};
+
+/// Organization roles, ordered from least to most privileged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Viewer,
+    Editor,
+    Admin,
+    Owner,
+}
+
+impl Role {
+    pub fn parse(value: &str) -> Option<Role> {
+        match value {
+            "viewer" => Some(Role::Viewer),
+            "editor" => Some(Role::Editor),
+            "admin" => Some(Role::Admin),
+            "owner" => Some(Role::Owner),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+            Role::Owner => "owner",
+        }
+    }
+}
+
+/// What a caller is trying to do; each maps to the least role allowed to do it
+#[derive(Debug, Clone, Copy)]
+pub enum Permission {
+    ViewWebsite,
+    EditWebsite,
+    DeleteWebsite,
+    ViewOrganization,
+    ManageMembers,
+    ManageNotifications,
+}
+
+impl Permission {
+    fn required_role(&self) -> Role {
+        match self {
+            Permission::ViewWebsite | Permission::ViewOrganization => Role::Viewer,
+            Permission::EditWebsite => Role::Editor,
+            Permission::DeleteWebsite | Permission::ManageMembers | Permission::ManageNotifications => Role::Admin,
+        }
+    }
+}
+
+fn check(role: Option<&str>, permission: Permission) -> Result<Role, AppError> {
+    match role.and_then(Role::parse) {
+        Some(role) if role >= permission.required_role() => Ok(role),
+        _ => Err(AppError::forbidden("You don't have permission to access this resource")),
+    }
+}
+
+/// Load a website and check the caller's role in the organization that owns it
+pub fn authorize_website(
+    store: &mut Store,
+    user_id: &str,
+    website_id: &str,
+    permission: Permission,
+) -> Result<Website, AppError> {
+    let (website, role) = store.get_website_access(website_id.to_string(), user_id.to_string())
+    .map_err(|e| AppError::store_for(Entity::Website(website_id), e, "Website not found", "Failed to fetch website"))?;
+
+    check(role.as_deref(), permission)?;
+    Ok(website)
+}
+
+/// Check the caller's role in an organization, returning it when sufficient
+pub fn authorize_organization(
+    store: &mut Store,
+    user_id: &str,
+    organization_id: &str,
+    permission: Permission,
+) -> Result<Role, AppError> {
+    let role = store.organization_role(organization_id.to_string(), user_id.to_string())
+    .map_err(|e| AppError::store_for(Entity::Organization(organization_id), e, "Organization not found", "Failed to fetch organization"))?;
+
+    check(role.as_deref(), permission)
+}
@@ -0,0 +1,37 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, NaiveDateTime};
+use store::models::check_history::HistoryCursor;
+
+/// Encode a keyset position into an opaque, URL-safe token
+pub fn encode_cursor(cursor: &HistoryCursor) -> String {
+    let (direction, checked_at, id) = match cursor {
+        HistoryCursor::Older { checked_at, id } => ("o", checked_at, id),
+        HistoryCursor::Newer { checked_at, id } => ("n", checked_at, id),
+    };
+    let raw = format!("{}|{}|{}", direction, checked_at.and_utc().timestamp_micros(), id);
+    URL_SAFE_NO_PAD.encode(raw)
+}
+
+/// Decode a token produced by `encode_cursor`, returning None if it was tampered with
+pub fn decode_cursor(token: &str) -> Option<HistoryCursor> {
+    let bytes = URL_SAFE_NO_PAD.decode(token).ok()?;
+    let raw = String::from_utf8(bytes).ok()?;
+    let mut parts = raw.splitn(3, '|');
+    let direction = parts.next()?;
+    let micros = parts.next()?.parse::<i64>().ok()?;
+    let id = parts.next()?.to_string();
+    let checked_at = DateTime::from_timestamp_micros(micros)?.naive_utc();
+
+    match direction {
+        "o" => Some(HistoryCursor::Older { checked_at, id }),
+        "n" => Some(HistoryCursor::Newer { checked_at, id }),
+        _ => None,
+    }
+}
+
+/// Parse a query timestamp, accepting the API's own output format or RFC 3339
+pub fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.naive_utc()))
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use poem::error::ResponseError;
+use poem::http::{StatusCode, header};
+use poem::Response;
+
+/// Content type of every error body, per RFC 7807
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// The row a store call was about, for `AppError::store_for` to log
+#[derive(Clone, Copy, Debug)]
+pub enum Entity<'a> {
+    Website(&'a str),
+    Organization(&'a str),
+    NotificationChannel(&'a str),
+    User(&'a str),
+}
+
+/// An error a handler can return with `?`; rendered as a problem document by
+/// `problem_response`. `code` is a stable, machine-readable identifier clients can
+/// match on, while `message` is for humans and may change.
+#[derive(Debug)]
+pub struct AppError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl AppError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> AppError {
+        AppError { status, code, message: message.into(), retry_after: None }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> AppError {
+        AppError::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> AppError {
+        AppError::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> AppError {
+        AppError::new(StatusCode::FORBIDDEN, "forbidden", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> AppError {
+        AppError::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> AppError {
+        AppError::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    pub fn too_many_requests(message: impl Into<String>, retry_after: Duration) -> AppError {
+        AppError {
+            retry_after: Some(retry_after),
+            ..AppError::new(StatusCode::TOO_MANY_REQUESTS, "too_many_requests", message)
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> AppError {
+        AppError::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message)
+    }
+
+    /// Map a store error once: a missing row is `not_found`, a duplicate is a conflict,
+    /// anything else is logged and reported as `failed` without leaking database details.
+    /// Prefer `store_for` where the call was about a particular row.
+    pub fn store(error: DieselError, not_found: &'static str, failed: &'static str) -> AppError {
+        match AppError::store_client_error(&error, not_found) {
+            Some(err) => err,
+            None => {
+                tracing::error!(error = ?error, "{}", failed);
+                AppError::internal(failed)
+            }
+        }
+    }
+
+    /// `store`, logging a failure with the id of the row it concerned. The signed-in
+    /// user's id is already on the request span.
+    pub fn store_for(entity: Entity<'_>, error: DieselError, not_found: &'static str, failed: &'static str) -> AppError {
+        if let Some(err) = AppError::store_client_error(&error, not_found) {
+            return err;
+        }
+        match entity {
+            Entity::Website(id) => tracing::error!(website_id = %id, error = ?error, "{}", failed),
+            Entity::Organization(id) => tracing::error!(organization_id = %id, error = ?error, "{}", failed),
+            Entity::NotificationChannel(id) => tracing::error!(channel_id = %id, error = ?error, "{}", failed),
+            Entity::User(id) => tracing::error!(user_id = %id, error = ?error, "{}", failed),
+        }
+        AppError::internal(failed)
+    }
+
+    fn store_client_error(error: &DieselError, not_found: &'static str) -> Option<AppError> {
+        match error {
+            DieselError::NotFound => Some(AppError::not_found(not_found)),
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                Some(AppError::conflict("A resource with these details already exists"))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<DieselError> for AppError {
+    fn from(error: DieselError) -> AppError {
+        AppError::store(error, "Resource not found", "Internal server error")
+    }
+}
+
+impl ResponseError for AppError {
+    fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    fn as_response(&self) -> Response {
+        problem(self.status, self.code, &self.message, None, self.retry_after)
+    }
+}
+
+/// Fallback code for errors raised as plain `poem::Error`s, e.g. by extractors
+fn default_code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        StatusCode::TOO_MANY_REQUESTS => "too_many_requests",
+        s if s.is_server_error() => "internal_error",
+        _ => "error",
+    }
+}
+
+fn problem(
+    status: StatusCode,
+    code: &str,
+    message: &str,
+    request_id: Option<&str>,
+    retry_after: Option<Duration>,
+) -> Response {
+    let body = serde_json::json!({
+        "type": "about:blank",
+        "title": status.canonical_reason().unwrap_or("Error"),
+        "status": status.as_u16(),
+        "code": code,
+        "message": message,
+        "request_id": request_id,
+    });
+    let mut resp = Response::builder()
+        .status(status)
+        .content_type(PROBLEM_JSON)
+        .body(body.to_string());
+    if let Some(retry_after) = retry_after {
+        // Round up so clients never retry a moment too early
+        let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        resp.headers_mut().insert(header::RETRY_AFTER, seconds.max(1).into());
+    }
+    resp
+}
+
+/// Render any error reaching the top of the stack as a problem document tagged with the
+/// request id, whether it started life as an `AppError` or a plain `poem::Error`.
+pub fn problem_response(err: poem::Error, request_id: &str) -> Response {
+    if let Some(app) = err.downcast_ref::<AppError>() {
+        return problem(app.status, app.code, &app.message, Some(request_id), app.retry_after);
+    }
+    let status = err.status();
+    // Errors carrying a non-error response (e.g. a redirect) keep it as is
+    if !status.is_client_error() && !status.is_server_error() {
+        return err.into_response();
+    }
+    problem(status, default_code(status), &err.to_string(), Some(request_id), None)
+}
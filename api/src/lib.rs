@@ -0,0 +1,32 @@
+//! The Better Uptime API. `app` builds the whole HTTP application, which the `api`
+//! binary serves; the request and response bodies are public so clients share them
+//! with the server and can't drift from it.
+
+pub mod request_inputs;
+pub mod request_outputs;
+pub mod routes;
+pub mod jwt;
+pub mod password;
+pub mod auth;
+pub mod audit;
+pub mod authz;
+pub mod monitor;
+pub mod notify;
+pub mod worker;
+pub mod cursor;
+pub mod error;
+pub mod openapi;
+pub mod live;
+pub mod metrics;
+pub mod telemetry;
+pub mod session;
+pub mod tags;
+pub mod two_factor;
+pub mod oidc;
+pub mod rate_limit;
+pub mod versioning;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+mod app;
+
+pub use app::app;
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use store::models::website::Website;
+use store::store::Store;
+use tokio::sync::broadcast;
+
+use crate::monitor::CheckResult;
+use crate::request_outputs::LiveEvent;
+
+/// Postgres rejects NOTIFY payloads over 8000 bytes, so long errors are cut short
+const MAX_ERROR_MESSAGE_LEN: usize = 1000;
+
+/// Publish the result of a check, plus a status change if the website flipped up/down.
+/// Goes through Postgres NOTIFY so API processes other than the worker's see it too.
+pub fn publish_check(store: &mut Store, website: &Website, result: &CheckResult) {
+    let now = chrono::Utc::now().naive_utc().format("%Y-%m-%dT%H:%M:%S").to_string();
+
+    let mut events = vec![LiveEvent::Check {
+        website_id: website.id.clone(),
+        organization_id: website.organization_id.clone(),
+        url: website.url.clone(),
+        checked_at: now.clone(),
+        is_up: result.is_up,
+        response_time_ms: result.response_time_ms,
+        status_code: result.status_code,
+        error_message: result.error_message.as_ref()
+            .map(|m| m.chars().take(MAX_ERROR_MESSAGE_LEN).collect()),
+    }];
+    if website.is_up != Some(result.is_up) {
+        events.push(LiveEvent::StatusChange {
+            website_id: website.id.clone(),
+            organization_id: website.organization_id.clone(),
+            url: website.url.clone(),
+            changed_at: now,
+            previous_is_up: website.is_up,
+            is_up: result.is_up,
+        });
+    }
+
+    for event in events {
+        let payload = match serde_json::to_string(&event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!(error = ?e, "failed to serialize live event");
+                continue;
+            }
+        };
+        if let Err(e) = store.publish_event(&payload) {
+            tracing::error!(website_id = %website.id, error = ?e, "failed to publish live event");
+        }
+    }
+}
+
+/// Listen for published events on a dedicated connection and fan them out in-process.
+/// The listener stops once the returned sender, and every clone of it, is dropped.
+pub fn start_event_listener(database_url: String) -> broadcast::Sender<LiveEvent> {
+    let (tx, _) = broadcast::channel(1024);
+    let weak = tx.downgrade();
+
+    std::thread::spawn(move || while weak.strong_count() > 0 {
+        let mut store = match Store::connect(&database_url) {
+            Ok(store) => store,
+            Err(e) => {
+                tracing::error!(error = ?e, "failed to connect event listener");
+                std::thread::sleep(Duration::from_secs(5));
+                continue;
+            }
+        };
+        if let Err(e) = store.listen_events() {
+            tracing::error!(error = ?e, "failed to subscribe to events");
+            std::thread::sleep(Duration::from_secs(5));
+            continue;
+        }
+        tracing::info!("listening for website events");
+
+        loop {
+            let payloads = match store.poll_events() {
+                Ok(payloads) => payloads,
+                Err(e) => {
+                    tracing::warn!(error = ?e, "lost event listener connection");
+                    break;
+                }
+            };
+            let Some(sender) = weak.upgrade() else {
+                return;
+            };
+            for payload in payloads {
+                match serde_json::from_str::<LiveEvent>(&payload) {
+                    // Sending only fails when nobody is subscribed, which is fine
+                    Ok(event) => { let _ = sender.send(event); }
+                    Err(e) => tracing::warn!(error = ?e, "ignoring malformed live event"),
+                }
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+    });
+
+    tx
+}
@@ -1,53 +1,113 @@
 use std::sync::{Arc, Mutex};
 
-use poem::{
-    EndpointExt, Route, Server, get, listener::TcpListener, post,
-};
+use clap::{Parser, Subcommand};
+use diesel::Connection;
+use poem::{Server, listener::TcpListener};
 
+use store::config::Config;
+use store::migrations::MigrationError;
 use store::store::Store;
 
-use crate::routes::{
-    user::{sign_in, sign_up},
-    website::{
-        create_website,
-        get_website,
-        list_websites,
-        update_website,
-        delete_website,
-        check_website_now,
-        get_website_status,
-        get_website_history},
-};
-pub mod request_inputs;
-pub mod request_outputs;
-pub mod routes;
-pub mod jwt;
-pub mod password;  
-pub mod auth;
-pub mod monitor;
-pub mod worker;
+#[derive(Parser)]
+#[command(name = "api", about = "The Better Uptime API server")]
+struct Cli {
+    /// Apply pending database migrations before serving, instead of refusing to start
+    #[arg(long, env = "RUN_MIGRATIONS")]
+    run_migrations: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Inspect and apply the database migrations built into this binary
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// List the migrations and whether each has been applied
+    Status,
+    /// Apply every pending migration
+    Up,
+    /// Revert the most recently applied migrations
+    Down {
+        /// How many to revert
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+}
+
+fn migrate(store: &mut Store, command: MigrateCommand) -> Result<(), MigrationError> {
+    match command {
+        MigrateCommand::Status => {
+            let status = store.schema_status()?;
+            for (name, applied) in &status.migrations {
+                println!("[{}] {}", if *applied { "x" } else { " " }, name);
+            }
+            for version in &status.unknown {
+                println!("[!] {} (not known to this build)", version);
+            }
+            let pending = status.pending().len();
+            println!("{} applied, {} pending", status.migrations.len() - pending, pending);
+        }
+        MigrateCommand::Up => {
+            let applied = store.prepare_schema(true)?;
+            for version in &applied {
+                println!("Applied {}", version);
+            }
+            println!("{} migration(s) applied", applied.len());
+        }
+        MigrateCommand::Down { steps } => {
+            for _ in 0..steps {
+                println!("Reverted {}", store.revert_last_migration()?);
+            }
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), std::io::Error> {
 
     dotenvy::dotenv().ok();
-  
-    let s = Arc::new(Mutex::new(Store::new().unwrap()));
-
-    crate::worker::start_background_worker(s.clone(), 60);
-    println!("[Server] Starting API server on http://0.0.0.0:3000");
-
-    let app = Route::new()
-    .at("/websites", get(list_websites))
-    .at("/website/:website_id", get(get_website).put(update_website).delete(delete_website))
-    .at("/website", post(create_website))
-    .at("/website/:website_id/check", get(check_website_now))
-    .at("/website/:website_id/status", get(get_website_status))      
-    .at("/website/:website_id/history", get(get_website_history)) 
-    .at("/sign-up", post(sign_up))
-    .at("/sign-in", post(sign_in))
-    .data(s);
+    let cli = Cli::parse();
+    let _telemetry = api::telemetry::init();
+    let database_url = Config::default().db_url;
+
+    if let Some(Command::Migrate(command)) = cli.command {
+        let result = Store::connect(&database_url).map_err(MigrationError::from)
+            .and_then(|mut store| migrate(&mut store, command));
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Err(e) = api::jwt::init() {
+        tracing::error!(error = %e, "refusing to start without a usable JWT signing key");
+        std::process::exit(1);
+    }
+
+    let mut store = Store::connect(&database_url).unwrap();
+    match store.prepare_schema(cli.run_migrations) {
+        Ok(applied) => for version in applied {
+            tracing::info!(version = %version, "applied migration");
+        },
+        Err(e) => {
+            tracing::error!(error = %e, "refusing to start on this database schema");
+            std::process::exit(1);
+        }
+    }
+    store.conn.set_instrumentation(api::metrics::DbQueryMetrics::default());
+    let s = Arc::new(Mutex::new(store));
+
+    api::worker::start_background_worker(s.clone(), 15);
+    tracing::info!(addr = "0.0.0.0:3000", "starting API server");
     Server::new(TcpListener::bind("0.0.0.0:3000"))
         .name("hello-world")
-        .run(app)
+        .run(api::app(s, database_url))
         .await
-}
\ No newline at end of file
+}
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use poem::{
+    Endpoint, IntoResponse, PathPattern, Request, Response, Result, handler,
+    http::StatusCode,
+    web::headers::{Authorization, HeaderMapExt, authorization::Bearer},
+};
+use prometheus::{
+    Encoder, Histogram, HistogramVec, IntCounterVec, IntGaugeVec, TextEncoder,
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+};
+use store::models::website::Website;
+
+use crate::monitor::CheckResult;
+
+const WEBSITE_LABELS: &[&str] = &["website_id", "url", "region"];
+
+/// Region this process checks from, reported on every per-website series
+static REGION: LazyLock<String> =
+    LazyLock::new(|| std::env::var("CHECK_REGION").unwrap_or_else(|_| "default".to_string()));
+
+static WEBSITE_UP: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!("website_up", "Whether the last check succeeded (1) or failed (0)", WEBSITE_LABELS)
+        .unwrap()
+});
+
+static WEBSITE_STATUS_CODE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!("website_status_code", "HTTP status code of the last check, 0 if no response", WEBSITE_LABELS)
+        .unwrap()
+});
+
+static WEBSITE_RESPONSE_TIME: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "website_response_time_seconds",
+        "Response time of website checks",
+        WEBSITE_LABELS,
+        vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+    )
+    .unwrap()
+});
+
+static CHECKS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("uptime_checks_total", "Website checks performed", &["result"]).unwrap()
+});
+
+static CHECK_ERRORS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("uptime_check_errors_total", "Failed website checks by kind", &["kind"]).unwrap()
+});
+
+static WORKER_CYCLE_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
+    register_histogram!(
+        "uptime_worker_cycle_duration_seconds",
+        "Time taken by one background worker cycle",
+        vec![1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0]
+    )
+    .unwrap()
+});
+
+static DB_QUERY_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "uptime_db_query_duration_seconds",
+        "Database query latency by statement kind",
+        &["operation", "outcome"],
+        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]
+    )
+    .unwrap()
+});
+
+static HTTP_REQUESTS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("http_requests_total", "HTTP requests served", &["method", "path", "status"]).unwrap()
+});
+
+static HTTP_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "http_request_duration_seconds",
+        "HTTP request latency",
+        &["method", "path"],
+        vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0]
+    )
+    .unwrap()
+});
+
+/// Label sets currently exported per website, so removed or re-pointed websites can be dropped
+static KNOWN_WEBSITES: LazyLock<Mutex<HashSet<(String, String)>>> = LazyLock::new(Default::default);
+
+/// Record the outcome of a single check against the per-website and internal series
+pub fn record_check(website: &Website, result: &CheckResult) {
+    let labels = [website.id.as_str(), website.url.as_str(), REGION.as_str()];
+
+    WEBSITE_UP.with_label_values(&labels).set(result.is_up as i64);
+    WEBSITE_STATUS_CODE.with_label_values(&labels).set(result.status_code.unwrap_or(0) as i64);
+    if let Some(ms) = result.response_time_ms {
+        WEBSITE_RESPONSE_TIME.with_label_values(&labels).observe(ms as f64 / 1000.0);
+    }
+
+    CHECKS_TOTAL.with_label_values(&[if result.is_up { "up" } else { "down" }]).inc();
+    if let Some(kind) = result.error_kind {
+        CHECK_ERRORS_TOTAL.with_label_values(&[kind]).inc();
+    }
+
+    KNOWN_WEBSITES.lock().unwrap().insert((website.id.clone(), website.url.clone()));
+}
+
+/// Drop per-website series for websites that were deleted or whose URL changed
+pub fn retain_websites(websites: &[Website]) {
+    let current: HashSet<(String, String)> = websites.iter()
+        .map(|w| (w.id.clone(), w.url.clone()))
+        .collect();
+
+    let mut known = KNOWN_WEBSITES.lock().unwrap();
+    for (id, url) in known.difference(&current) {
+        let labels = [id.as_str(), url.as_str(), REGION.as_str()];
+        let _ = WEBSITE_UP.remove_label_values(&labels);
+        let _ = WEBSITE_STATUS_CODE.remove_label_values(&labels);
+        let _ = WEBSITE_RESPONSE_TIME.remove_label_values(&labels);
+    }
+    known.retain(|key| current.contains(key));
+}
+
+pub fn observe_worker_cycle(elapsed: Duration) {
+    WORKER_CYCLE_DURATION.observe(elapsed.as_secs_f64());
+}
+
+/// Diesel instrumentation that times every query on the connection it is attached to
+#[derive(Default)]
+pub struct DbQueryMetrics {
+    started: Option<Instant>,
+}
+
+impl Instrumentation for DbQueryMetrics {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => self.started = Some(Instant::now()),
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                let Some(started) = self.started.take() else { return };
+                let sql = query.to_string();
+                let operation = match sql.split_whitespace().next().map(str::to_ascii_uppercase) {
+                    Some(op) if ["SELECT", "INSERT", "UPDATE", "DELETE"].contains(&op.as_str()) => op,
+                    _ => "OTHER".to_string(),
+                };
+                let outcome = if error.is_some() { "error" } else { "ok" };
+                DB_QUERY_DURATION
+                    .with_label_values(&[operation.as_str(), outcome])
+                    .observe(started.elapsed().as_secs_f64());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Middleware recording request counts and latency, labelled by the matched route pattern
+pub async fn track_http<E: Endpoint>(ep: E, req: Request) -> Result<Response> {
+    let method = req.method().to_string();
+    let start = Instant::now();
+
+    let result = ep.call(req).await.map(IntoResponse::into_response);
+
+    let (status, pattern) = match &result {
+        Ok(resp) => (resp.status(), resp.data::<PathPattern>().map(|p| p.0.to_string())),
+        Err(err) => (err.status(), err.data::<PathPattern>().map(|p| p.0.to_string())),
+    };
+    // Unmatched paths collapse into one series instead of one per URL probed
+    let path = pattern.unwrap_or_else(|| "unmatched".to_string());
+
+    HTTP_REQUESTS_TOTAL
+        .with_label_values(&[method.as_str(), path.as_str(), status.as_str()])
+        .inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[method.as_str(), path.as_str()])
+        .observe(start.elapsed().as_secs_f64());
+
+    result
+}
+
+/// Prometheus scrape endpoint. Website URLs are exported as labels, so when
+/// `METRICS_TOKEN` is set scrapers must send it as a bearer token.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "meta",
+    summary = "Prometheus metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", content_type = "text/plain"),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security((), ("metrics_token" = []))
+)]
+#[handler]
+pub fn metrics(req: &Request) -> Result<Response> {
+    if let Ok(expected) = std::env::var("METRICS_TOKEN") {
+        let provided = req.headers()
+            .typed_get::<Authorization<Bearer>>()
+            .map(|auth| auth.token().to_string());
+        if provided.as_deref() != Some(expected.as_str()) {
+            return Err(poem::Error::from_string("Invalid metrics token", StatusCode::UNAUTHORIZED));
+        }
+    }
+
+    let mut buf = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buf)
+        .map_err(|e| {
+            tracing::error!(error = ?e, "failed to encode metrics");
+            poem::Error::from_string("Failed to encode metrics", StatusCode::INTERNAL_SERVER_ERROR)
+        })?;
+
+    Ok(Response::builder()
+        .content_type(TextEncoder::new().format_type())
+        .body(buf))
+}
@@ -1,14 +1,24 @@
 use std::time::Duration;
 use reqwest::Client;
+use store::models::website::Website;
+
+use crate::error::AppError;
+use crate::request_inputs::CheckAssertions;
+
+pub const MIN_CHECK_INTERVAL_SECONDS: i32 = 30;
+pub const MAX_CHECK_INTERVAL_SECONDS: i32 = 86_400;
 
 pub struct CheckResult {
     pub is_up: bool,
     pub response_time_ms: Option<i32>,
     pub status_code: Option<i32>,
     pub error_message: Option<String>,
+    /// Short, low-cardinality classification of a failed check
+    pub error_kind: Option<&'static str>,
 }
 
-pub async fn check_website(url: &str) -> CheckResult {
+#[tracing::instrument(name = "http_probe", skip_all, fields(url = %url))]
+pub async fn check_website(url: &str, assertions: &CheckAssertions) -> CheckResult {
     let client = Client::builder()
         .timeout(Duration::from_secs(10))
         .build()
@@ -20,15 +30,30 @@ pub async fn check_website(url: &str) -> CheckResult {
         Ok(resp) => {
             let elapsed_ms = start.elapsed().as_millis() as i32;
             let status = resp.status();
+            let status_ok = if assertions.status.is_empty() {
+                status.is_success()
+            } else {
+                assertions.status.contains(&status.as_u16())
+            };
+            let failure = if !status_ok {
+                Some((format!("HTTP {}", status.as_u16()), "http_status"))
+            } else if let Some(max) = assertions.max_response_time_ms.filter(|max| elapsed_ms > *max) {
+                Some((format!("Response took {}ms, over the {}ms limit", elapsed_ms, max), "slow"))
+            } else if let Some(expected) = &assertions.body_contains {
+                match resp.text().await {
+                    Ok(body) if body.contains(expected.as_str()) => None,
+                    Ok(_) => Some((format!("Response body doesn't contain {:?}", expected), "assertion")),
+                    Err(e) => Some((e.to_string(), error_kind(&e))),
+                }
+            } else {
+                None
+            };
             CheckResult {
-                is_up: status.is_success(),
+                is_up: failure.is_none(),
                 response_time_ms: Some(elapsed_ms),
                 status_code: Some(status.as_u16() as i32),
-                error_message: if status.is_success() {
-                    None
-                } else {
-                    Some(format!("HTTP {}", status.as_u16()))
-                },
+                error_kind: failure.as_ref().map(|(_, kind)| *kind),
+                error_message: failure.map(|(message, _)| message),
             }
         }
         Err(e) => {
@@ -38,7 +63,52 @@ pub async fn check_website(url: &str) -> CheckResult {
                 response_time_ms: Some(elapsed_ms),
                 status_code: None,
                 error_message: Some(e.to_string()),
+                error_kind: Some(error_kind(&e)),
             }
         }
     }
 }
+
+/// A website's stored assertions; anything unreadable is treated as no assertions
+pub fn assertions_of(website: &Website) -> CheckAssertions {
+    serde_json::from_value(website.assertions.clone()).unwrap_or_default()
+}
+
+pub fn validate_interval(seconds: i32) -> Result<(), AppError> {
+    if !(MIN_CHECK_INTERVAL_SECONDS..=MAX_CHECK_INTERVAL_SECONDS).contains(&seconds) {
+        return Err(AppError::bad_request(format!(
+            "Check interval must be between {} and {} seconds",
+            MIN_CHECK_INTERVAL_SECONDS, MAX_CHECK_INTERVAL_SECONDS,
+        )));
+    }
+    Ok(())
+}
+
+pub fn validate_assertions(assertions: &CheckAssertions) -> Result<(), AppError> {
+    if let Some(code) = assertions.status.iter().find(|code| !(100..=599).contains(*code)) {
+        return Err(AppError::bad_request(format!("{} is not an HTTP status code", code)));
+    }
+    if assertions.body_contains.as_deref().is_some_and(str::is_empty) {
+        return Err(AppError::bad_request("body_contains cannot be empty"));
+    }
+    if assertions.max_response_time_ms.is_some_and(|max| max <= 0) {
+        return Err(AppError::bad_request("max_response_time_ms must be positive"));
+    }
+    Ok(())
+}
+
+fn error_kind(e: &reqwest::Error) -> &'static str {
+    if e.is_timeout() {
+        "timeout"
+    } else if e.is_connect() {
+        "connect"
+    } else if e.is_redirect() {
+        "redirect"
+    } else if e.is_body() || e.is_decode() {
+        "body"
+    } else if e.is_builder() {
+        "invalid_url"
+    } else {
+        "request"
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use store::models::incident::{Incident, IncidentChange};
+use store::models::website::Website;
+use store::store::Store;
+
+/// Tell every notification channel whose tags match the website that an incident opened
+/// or resolved. Deliveries run in the background; failures are logged, not retried.
+pub fn incident_changed(store: &mut Store, website: &Website, change: &IncidentChange) {
+    let channels = match store.notification_channels_for(website.organization_id.clone(), website.tags.clone()) {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!(website_id = %website.id, error = ?e, "failed to find notification channels");
+            return;
+        }
+    };
+    if channels.is_empty() {
+        return;
+    }
+
+    let (event, incident) = match change {
+        IncidentChange::Opened(incident) => ("incident.opened", incident),
+        IncidentChange::Resolved(incident) => ("incident.resolved", incident),
+    };
+    let payload = serde_json::json!({
+        "event": event,
+        "website": {
+            "id": website.id,
+            "name": website.name,
+            "url": website.url,
+            "organization_id": website.organization_id,
+            "tags": website.tags,
+        },
+        "incident": incident_json(incident),
+    });
+
+    for channel in channels {
+        tokio::spawn(deliver(channel.id, channel.url, payload.clone()));
+    }
+}
+
+fn incident_json(incident: &Incident) -> serde_json::Value {
+    serde_json::json!({
+        "id": incident.id,
+        "started_at": incident.started_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        "resolved_at": incident.resolved_at.map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        "cause": incident.cause,
+    })
+}
+
+async fn deliver(channel_id: String, url: String, payload: serde_json::Value) {
+    let result = reqwest::Client::new()
+        .post(&url)
+        .timeout(Duration::from_secs(10))
+        .json(&payload)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+    if let Err(e) = result {
+        tracing::error!(channel_id = %channel_id, error = ?e, "failed to deliver notification");
+    }
+}
@@ -0,0 +1,438 @@
+use std::time::{Duration, Instant};
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+use rand_core::{OsRng, RngCore};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+
+/// How long discovery metadata is reused before being fetched again
+const DISCOVERY_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Signature algorithms accepted on ID tokens; all verified with keys from the JWKS
+const ALLOWED_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256, Algorithm::RS384, Algorithm::RS512,
+    Algorithm::PS256, Algorithm::PS384, Algorithm::PS512,
+    Algorithm::ES256, Algorithm::ES384, Algorithm::EdDSA,
+];
+
+/// Settings for the single OpenID Connect provider we accept logins from
+#[derive(Clone, Debug)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    /// Unset for public clients, which rely on PKCE alone
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    pub scopes: String,
+    /// Link a first-time SSO login to the existing user with the same username.
+    /// Only safe when the provider is the authority for usernames.
+    pub link_by_username: bool,
+}
+
+impl OidcConfig {
+    /// Read `OIDC_*` variables; SSO stays off unless issuer, client id and redirect URI are set
+    pub fn from_env() -> Option<OidcConfig> {
+        let issuer = std::env::var("OIDC_ISSUER").ok()?;
+        let client_id = std::env::var("OIDC_CLIENT_ID").ok()?;
+        let redirect_uri = std::env::var("OIDC_REDIRECT_URI").ok()?;
+        Some(OidcConfig {
+            issuer: issuer.trim_end_matches('/').to_string(),
+            client_id,
+            client_secret: std::env::var("OIDC_CLIENT_SECRET").ok(),
+            redirect_uri,
+            scopes: std::env::var("OIDC_SCOPES").unwrap_or_else(|_| "openid profile email".to_string()),
+            link_by_username: std::env::var("OIDC_LINK_BY_USERNAME").is_ok_and(|v| v == "true"),
+        })
+    }
+}
+
+/// The parts of the discovery document we use
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub preferred_username: Option<String>,
+    pub email: Option<String>,
+}
+
+impl IdTokenClaims {
+    /// A username for a just-in-time provisioned user: `preferred_username`, else the
+    /// local part of `email`, reduced to characters that are safe in a username
+    pub fn username_hint(&self) -> String {
+        let raw = self.preferred_username.clone()
+            .or_else(|| self.email.as_ref().map(|e| e.split('@').next().unwrap_or_default().to_string()))
+            .unwrap_or_default();
+        let cleaned: String = raw.chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            .collect::<String>()
+            .to_lowercase();
+        if cleaned.len() < 3 { "sso-user".to_string() } else { cleaned }
+    }
+}
+
+/// Everything the callback needs to finish a login, kept server-side between the redirects
+pub struct AuthorizationRequest {
+    pub url: String,
+    pub state: String,
+    pub code_verifier: String,
+    pub nonce: String,
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// PKCE S256 challenge for a verifier
+pub fn code_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn provider_error(message: &'static str) -> poem::Error {
+    poem::Error::from_string(message, poem::http::StatusCode::BAD_GATEWAY)
+}
+
+fn invalid_token(message: &'static str) -> poem::Error {
+    poem::Error::from_string(message, poem::http::StatusCode::UNAUTHORIZED)
+}
+
+pub struct OidcClient {
+    pub config: OidcConfig,
+    http: reqwest::Client,
+    metadata: RwLock<Option<(Instant, ProviderMetadata)>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> OidcClient {
+        OidcClient {
+            config,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("failed to build HTTP client"),
+            metadata: RwLock::new(None),
+        }
+    }
+
+    /// Discovery metadata, fetched from the issuer and cached for an hour
+    pub async fn metadata(&self) -> Result<ProviderMetadata, poem::Error> {
+        if let Some((fetched_at, metadata)) = self.metadata.read().await.as_ref()
+            && fetched_at.elapsed() < DISCOVERY_TTL
+        {
+            return Ok(metadata.clone());
+        }
+
+        let url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+        let metadata: ProviderMetadata = self.get_json(&url).await.map_err(|e| {
+            tracing::error!(url = %url, error = ?e, "OIDC discovery failed");
+            provider_error("Failed to reach the identity provider")
+        })?;
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+            tracing::error!(expected = %self.config.issuer, actual = %metadata.issuer, "OIDC issuer mismatch");
+            return Err(provider_error("Identity provider reported a different issuer"));
+        }
+
+        *self.metadata.write().await = Some((Instant::now(), metadata.clone()));
+        Ok(metadata)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, reqwest::Error> {
+        self.http.get(url).send().await?.error_for_status()?.json().await
+    }
+
+    /// Build the redirect to the provider's authorization endpoint with fresh state,
+    /// nonce and PKCE verifier
+    pub async fn authorization_request(&self) -> Result<AuthorizationRequest, poem::Error> {
+        let metadata = self.metadata().await?;
+        let state = random_token();
+        let nonce = random_token();
+        let code_verifier = random_token();
+
+        let url = reqwest::Url::parse_with_params(&metadata.authorization_endpoint, &[
+            ("response_type", "code"),
+            ("client_id", self.config.client_id.as_str()),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("scope", self.config.scopes.as_str()),
+            ("state", state.as_str()),
+            ("nonce", nonce.as_str()),
+            ("code_challenge", code_challenge(&code_verifier).as_str()),
+            ("code_challenge_method", "S256"),
+        ]).map_err(|e| {
+            tracing::error!(error = ?e, "invalid OIDC authorization endpoint");
+            provider_error("Identity provider returned an invalid authorization endpoint")
+        })?;
+
+        Ok(AuthorizationRequest { url: url.to_string(), state, code_verifier, nonce })
+    }
+
+    /// Redeem an authorization code and return the validated ID token claims
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, poem::Error> {
+        let metadata = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response = self.http.post(&metadata.token_endpoint).form(&form).send().await.map_err(|e| {
+            tracing::error!(error = ?e, "OIDC token request failed");
+            provider_error("Failed to reach the identity provider")
+        })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            tracing::warn!(status = %status, body = %body, "OIDC token exchange rejected");
+            return Err(invalid_token("The identity provider rejected the authorization code"));
+        }
+        let tokens: TokenResponse = response.json().await.map_err(|e| {
+            tracing::error!(error = ?e, "malformed OIDC token response");
+            provider_error("Identity provider returned a malformed token response")
+        })?;
+
+        let jwks: JwkSet = self.get_json(&metadata.jwks_uri).await.map_err(|e| {
+            tracing::error!(url = %metadata.jwks_uri, error = ?e, "failed to fetch OIDC JWKS");
+            provider_error("Failed to fetch the identity provider's signing keys")
+        })?;
+
+        validate_id_token(&tokens.id_token, &jwks, &metadata.issuer, &self.config.client_id, nonce)
+    }
+}
+
+/// Check an ID token's signature against the provider's keys, then its issuer,
+/// audience, expiry and nonce
+pub fn validate_id_token(
+    id_token: &str,
+    jwks: &JwkSet,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+) -> Result<IdTokenClaims, poem::Error> {
+    let header = decode_header(id_token).map_err(|_| invalid_token("Malformed ID token"))?;
+    if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+        return Err(invalid_token("ID token uses an unsupported signature algorithm"));
+    }
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }.ok_or_else(|| invalid_token("ID token is signed with an unknown key"))?;
+    let key = DecodingKey::from_jwk(jwk).map_err(|_| invalid_token("Unusable signing key"))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+        .map_err(|e| {
+            tracing::warn!(error = ?e, "ID token failed validation");
+            invalid_token("Invalid ID token")
+        })?
+        .claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(invalid_token("ID token nonce does not match"));
+    }
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use poem::{
+        EndpointExt, Route, Server, get, handler, post,
+        listener::{Acceptor, TcpAcceptor},
+        web::{Data, Form, Json, Query, Redirect},
+    };
+    use serde::Deserialize;
+
+    use super::*;
+
+    const CLIENT_ID: &str = "better-uptime";
+    const TEST_KEY: &str = include_str!("../testdata/oidc_test_key.pem");
+    const TEST_KEY_N: &str = include_str!("../testdata/oidc_test_key.n");
+
+    /// Minimal in-process OpenID provider: issues codes from `/authorize` and checks
+    /// PKCE on `/token` before handing back a signed ID token
+    #[derive(Default)]
+    struct MockProvider {
+        issuer: String,
+        codes: Mutex<HashMap<String, (String, String)>>,
+        audience: Mutex<Option<String>>,
+    }
+
+    #[derive(Deserialize)]
+    struct AuthorizeQuery {
+        redirect_uri: String,
+        state: String,
+        nonce: String,
+        code_challenge: String,
+        code_challenge_method: String,
+    }
+
+    #[derive(Deserialize)]
+    struct TokenForm {
+        code: String,
+        code_verifier: String,
+    }
+
+    #[handler]
+    fn discovery(Data(p): Data<&Arc<MockProvider>>) -> Json<serde_json::Value> {
+        Json(serde_json::json!({
+            "issuer": p.issuer,
+            "authorization_endpoint": format!("{}/authorize", p.issuer),
+            "token_endpoint": format!("{}/token", p.issuer),
+            "jwks_uri": format!("{}/jwks", p.issuer),
+        }))
+    }
+
+    #[handler]
+    fn jwks() -> Json<serde_json::Value> {
+        Json(serde_json::json!({
+            "keys": [{ "kty": "RSA", "kid": "test", "alg": "RS256", "use": "sig", "n": TEST_KEY_N, "e": "AQAB" }]
+        }))
+    }
+
+    #[handler]
+    fn authorize(Query(q): Query<AuthorizeQuery>, Data(p): Data<&Arc<MockProvider>>) -> Redirect {
+        assert_eq!(q.code_challenge_method, "S256");
+        let code = random_token();
+        p.codes.lock().unwrap().insert(code.clone(), (q.code_challenge, q.nonce));
+        Redirect::see_other(format!("{}?code={}&state={}", q.redirect_uri, code, q.state))
+    }
+
+    #[handler]
+    fn token(Form(f): Form<TokenForm>, Data(p): Data<&Arc<MockProvider>>) -> poem::Result<Json<serde_json::Value>> {
+        let (challenge, nonce) = p.codes.lock().unwrap().remove(&f.code)
+            .ok_or_else(|| poem::Error::from_string("invalid_grant", poem::http::StatusCode::BAD_REQUEST))?;
+        if code_challenge(&f.code_verifier) != challenge {
+            return Err(poem::Error::from_string("invalid_grant", poem::http::StatusCode::BAD_REQUEST));
+        }
+        let audience = p.audience.lock().unwrap().clone().unwrap_or_else(|| CLIENT_ID.to_string());
+        let claims = serde_json::json!({
+            "iss": p.issuer,
+            "sub": "user-123",
+            "aud": audience,
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "nonce": nonce,
+            "preferred_username": "Jane.Doe",
+        });
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some("test".to_string());
+        let id_token = encode(&header, &claims, &EncodingKey::from_rsa_pem(TEST_KEY.as_bytes()).unwrap()).unwrap();
+        Ok(Json(serde_json::json!({ "access_token": "at", "token_type": "Bearer", "id_token": id_token })))
+    }
+
+    async fn start_provider() -> (Arc<MockProvider>, OidcClient) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let acceptor = TcpAcceptor::from_std(listener).unwrap();
+        let addr = acceptor.local_addr()[0].as_socket_addr().cloned().unwrap();
+        let provider = Arc::new(MockProvider {
+            issuer: format!("http://{}", addr),
+            ..Default::default()
+        });
+
+        let app = Route::new()
+            .at("/.well-known/openid-configuration", get(discovery))
+            .at("/jwks", get(jwks))
+            .at("/authorize", get(authorize))
+            .at("/token", post(token))
+            .data(provider.clone());
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(app));
+
+        let client = OidcClient::new(OidcConfig {
+            issuer: provider.issuer.clone(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: None,
+            redirect_uri: "http://localhost:3000/auth/oidc/callback".to_string(),
+            scopes: "openid profile".to_string(),
+            link_by_username: false,
+        });
+        (provider, client)
+    }
+
+    /// Follow the authorization URL like a browser would and pull the code out of the callback
+    async fn authorize_code(request: &AuthorizationRequest) -> String {
+        let http = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+        let response = http.get(&request.url).send().await.unwrap();
+        let location = response.headers()["location"].to_str().unwrap();
+        let callback = reqwest::Url::parse(location).unwrap();
+        let params: HashMap<_, _> = callback.query_pairs().into_owned().collect();
+        assert_eq!(params["state"], request.state);
+        params["code"].clone()
+    }
+
+    #[tokio::test]
+    async fn completes_authorization_code_flow_with_pkce() {
+        let (_, client) = start_provider().await;
+        let request = client.authorization_request().await.unwrap();
+        let code = authorize_code(&request).await;
+
+        let claims = client.exchange_code(&code, &request.code_verifier, &request.nonce).await.unwrap();
+        assert_eq!(claims.sub, "user-123");
+        assert_eq!(claims.username_hint(), "jane.doe");
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_code_verifier() {
+        let (_, client) = start_provider().await;
+        let request = client.authorization_request().await.unwrap();
+        let code = authorize_code(&request).await;
+
+        let err = client.exchange_code(&code, "not-the-verifier", &request.nonce).await.unwrap_err();
+        assert_eq!(err.status(), poem::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn rejects_nonce_mismatch() {
+        let (_, client) = start_provider().await;
+        let request = client.authorization_request().await.unwrap();
+        let code = authorize_code(&request).await;
+
+        let err = client.exchange_code(&code, &request.code_verifier, "other-nonce").await.unwrap_err();
+        assert_eq!(err.status(), poem::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn rejects_token_for_another_client() {
+        let (provider, client) = start_provider().await;
+        *provider.audience.lock().unwrap() = Some("someone-else".to_string());
+        let request = client.authorization_request().await.unwrap();
+        let code = authorize_code(&request).await;
+
+        let err = client.exchange_code(&code, &request.code_verifier, &request.nonce).await.unwrap_err();
+        assert_eq!(err.status(), poem::http::StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,265 @@
+use poem::{
+    handler,
+    web::{Html, Json},
+};
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::routes;
+
+pub use crate::request_outputs::{Problem, SuccessMessage};
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        let mut scheme = Http::new(HttpAuthScheme::Bearer);
+        scheme.description = Some(
+            "An access token from `/sign-in`, or an API key (`bu_...`) where the operation allows it".to_string(),
+        );
+        components.add_security_scheme("bearer", SecurityScheme::Http(scheme));
+        let mut metrics = Http::new(HttpAuthScheme::Bearer);
+        metrics.description = Some("The `METRICS_TOKEN` configured on the server, if any".to_string());
+        components.add_security_scheme("metrics_token", SecurityScheme::Http(metrics));
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Better Uptime API",
+        description = "Website monitoring: checks, incidents, alerts and the accounts that own them.\n\n\
+            Every operation lives under `/v1`. The original unversioned routes (`/website/{id}`, \
+            `GET /website/{id}/check`, ...) still answer as before but are deprecated: their \
+            responses carry a `Deprecation` header and they will be removed in a future release."
+    ),
+    paths(
+        routes::website::list_websites,
+        routes::live::live_events,
+        routes::website::get_website,
+        routes::website::update_website,
+        routes::website::delete_website,
+        routes::website::create_website,
+        routes::website::check_website_now,
+        routes::website::pause_website,
+        routes::website::resume_website,
+        routes::website::get_website_status,
+        routes::website::get_website_history,
+        routes::export::export_website_history,
+        routes::export::export_website_incidents,
+        routes::user::sign_up,
+        routes::user::sign_in,
+        routes::two_factor::sign_in_two_factor,
+        routes::account::request_password_reset,
+        routes::account::confirm_password_reset,
+        routes::account::delete_account,
+        routes::account::change_password,
+        routes::two_factor::two_factor_status,
+        routes::two_factor::enroll_totp,
+        routes::two_factor::verify_totp,
+        routes::two_factor::disable_totp,
+        routes::two_factor::regenerate_recovery_codes,
+        routes::oidc::oidc_login,
+        routes::oidc::oidc_link,
+        routes::oidc::oidc_callback,
+        routes::user::refresh_token,
+        routes::user::sign_out,
+        routes::user::sign_out_all,
+        routes::api_key::list_api_keys,
+        routes::api_key::create_api_key,
+        routes::api_key::revoke_api_key,
+        routes::organization::list_organizations,
+        routes::organization::create_organization,
+        routes::organization::list_members,
+        routes::organization::update_member,
+        routes::organization::remove_member,
+        routes::organization::list_organization_invitations,
+        routes::organization::create_invitation,
+        routes::organization::revoke_invitation,
+        routes::organization::list_my_invitations,
+        routes::organization::accept_invitation,
+        routes::organization::decline_invitation,
+        routes::monitors::export_monitors,
+        routes::monitors::import_monitors,
+        routes::notification::list_notification_channels,
+        routes::notification::create_notification_channel,
+        routes::notification::delete_notification_channel,
+        crate::jwt::jwks,
+        routes::audit_log::list_audit_log,
+        crate::metrics::metrics,
+    ),
+    components(schemas(Problem, SuccessMessage), responses(Problem)),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "websites", description = "Monitored websites, their checks and history"),
+        (name = "auth", description = "Signing in and out, sessions and tokens"),
+        (name = "account", description = "The signed-in user's own account"),
+        (name = "two-factor", description = "TOTP second factor and recovery codes"),
+        (name = "sso", description = "OpenID Connect single sign-on"),
+        (name = "api-keys", description = "Long-lived keys for scripts and integrations"),
+        (name = "organizations", description = "Organizations, members and invitations"),
+        (name = "audit", description = "Audit trail of changes"),
+        (name = "meta", description = "Service metadata"),
+    )
+)]
+pub struct ApiDoc;
+
+/// The OpenAPI document for every `/v1` and top-level route in `app.rs`
+#[handler]
+pub fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Interactive docs; Swagger UI is loaded from a CDN and pointed at `/openapi.json`
+#[handler]
+pub fn docs() -> Html<&'static str> {
+    Html(r##"<!doctype html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Better Uptime API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>"##)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+
+    use poem::error::{MethodNotAllowedError, NotFoundError};
+    use poem::{Endpoint, Request, Route};
+
+    use crate::rate_limit::{LoginRateLimit, MemoryBackend, RateLimitConfig};
+
+    use super::*;
+
+    const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+    fn documented_routes(spec: &utoipa::openapi::OpenApi) -> BTreeSet<(String, String)> {
+        let json = serde_json::to_value(spec).unwrap();
+        let mut routes = BTreeSet::new();
+        for (path, item) in json["paths"].as_object().unwrap() {
+            for method in item.as_object().unwrap().keys() {
+                routes.insert((method.to_uppercase(), path.clone()));
+            }
+        }
+        routes
+    }
+
+    /// A path the router can match for a templated one, e.g. `/v1/websites/x` for
+    /// `/v1/websites/{website_id}`
+    fn concrete(path: &str) -> String {
+        path.split('/')
+            .map(|seg| if seg.starts_with('{') { "x" } else { seg })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Whether the router has an endpoint for the request, whatever that endpoint makes of
+    /// it; documented routes must not fall through to the deprecated aliases either
+    async fn routed(app: &Route, method: &str, path: &str) -> bool {
+        let req = Request::builder().method(method.parse().unwrap()).uri(path.parse().unwrap()).finish();
+        match app.call(req).await {
+            Ok(resp) => !resp.headers().contains_key("deprecation"),
+            Err(err) => !err.is::<NotFoundError>() && !err.is::<MethodNotAllowedError>(),
+        }
+    }
+
+    /// Routed paths the spec leaves out on purpose: the spec itself and its viewer
+    const UNDESCRIBED: [&str; 2] = ["/openapi.json", "/docs"];
+
+    /// `/v1/websites/:website_id` as the spec writes it, `/v1/websites/{website_id}`
+    fn templated(path: &str) -> String {
+        path.split('/')
+            .map(|seg| match seg.strip_prefix(':') {
+                Some(param) => format!("{{{}}}", param),
+                None => seg.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    #[tokio::test]
+    async fn spec_matches_the_router() {
+        // Requests reach handlers like the JWKS one, which need keys
+        crate::jwt::init_for_tests();
+        let login_limit = LoginRateLimit::new(Arc::new(MemoryBackend::default()), RateLimitConfig::from_env());
+        let (app, routed_paths) = crate::app::routes_with_paths(login_limit);
+        let documented = documented_routes(&ApiDoc::openapi());
+        let documented_paths: BTreeSet<String> = documented.iter().map(|(_, path)| path.clone()).collect();
+        let routed_paths: BTreeSet<String> = routed_paths.iter()
+            .filter(|path| !UNDESCRIBED.contains(&path.as_str()))
+            .map(|path| templated(path))
+            .collect();
+
+        let unrouted: Vec<_> = documented_paths.difference(&routed_paths).collect();
+        assert!(unrouted.is_empty(), "OpenAPI spec describes paths that aren't routed: {:?}", unrouted);
+        let undescribed: Vec<_> = routed_paths.difference(&documented_paths).collect();
+        assert!(undescribed.is_empty(), "routed paths missing from the OpenAPI spec: {:?}", undescribed);
+
+        let mut missing = Vec::new();
+        let mut undocumented = Vec::new();
+        for path in &documented_paths {
+            for method in METHODS {
+                let route = (method.to_string(), path.clone());
+                match (documented.contains(&route), routed(&app, method, &concrete(path)).await) {
+                    (true, false) => missing.push(route),
+                    (false, true) => undocumented.push(route),
+                    _ => {}
+                }
+            }
+        }
+        assert!(missing.is_empty(), "OpenAPI spec describes routes that don't exist: {:?}", missing);
+        assert!(undocumented.is_empty(), "methods missing from the OpenAPI spec: {:?}", undocumented);
+    }
+
+    fn collect_refs(value: &serde_json::Value, refs: &mut BTreeSet<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(target)) = map.get("$ref") {
+                    refs.insert(target.clone());
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_reference_resolves() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut refs = BTreeSet::new();
+        collect_refs(&json, &mut refs);
+        for target in refs {
+            let pointer = target.strip_prefix('#').expect("only local references");
+            assert!(json.pointer(pointer).is_some(), "dangling reference {}", target);
+        }
+    }
+
+    #[test]
+    fn every_operation_documents_errors() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for (path, item) in json["paths"].as_object().unwrap() {
+            for (method, operation) in item.as_object().unwrap() {
+                let responses = operation["responses"].as_object().unwrap();
+                assert!(
+                    responses.keys().any(|status| status.starts_with('4') || status.starts_with('5')),
+                    "{} {} documents no error responses",
+                    method.to_uppercase(),
+                    path,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use poem::{
+    Body, Endpoint, IntoResponse, Middleware, Request, Response,
+    http::StatusCode,
+};
+use store::store::Store;
+
+use crate::error::AppError;
+
+pub type BackendError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where limiter counters live. In memory is enough for one API process; Postgres
+/// shares them between processes.
+pub trait LimiterBackend: Send + Sync {
+    /// Count a request against `key` in a fixed window; returns the hits so far in
+    /// the window and the time until it resets
+    fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), BackendError>;
+    /// Time left on a lockout of `key`, if there is one
+    fn locked_for(&self, key: &str) -> Result<Option<Duration>, BackendError>;
+    /// Record a failed attempt, returning the consecutive failures; a streak is
+    /// forgotten after `decay` without failures
+    fn record_failure(&self, key: &str, decay: Duration) -> Result<u32, BackendError>;
+    fn lock(&self, key: &str, duration: Duration) -> Result<(), BackendError>;
+    /// Clear the failure streak and any lockout after a successful attempt
+    fn reset_failures(&self, key: &str) -> Result<(), BackendError>;
+}
+
+#[derive(Default)]
+struct Entry {
+    window_start: Option<Instant>,
+    hits: u32,
+    failures: u32,
+    last_failure: Option<Instant>,
+    locked_until: Option<Instant>,
+}
+
+/// Entries kept before idle ones are swept out
+const MEMORY_SWEEP_THRESHOLD: usize = 10_000;
+
+#[derive(Default)]
+pub struct MemoryBackend {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemoryBackend {
+    fn sweep(entries: &mut HashMap<String, Entry>, now: Instant) {
+        let idle = Duration::from_secs(24 * 60 * 60);
+        entries.retain(|_, e| {
+            let recent = |t: Option<Instant>| t.is_some_and(|t| now.duration_since(t) < idle);
+            recent(e.window_start) || recent(e.last_failure) || e.locked_until.is_some_and(|t| t > now)
+        });
+    }
+}
+
+impl LimiterBackend for MemoryBackend {
+    fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), BackendError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MEMORY_SWEEP_THRESHOLD {
+            Self::sweep(&mut entries, now);
+        }
+        let entry = entries.entry(key.to_string()).or_default();
+        match entry.window_start {
+            Some(start) if now.duration_since(start) < window => entry.hits += 1,
+            _ => {
+                entry.window_start = Some(now);
+                entry.hits = 1;
+            }
+        }
+        let elapsed = now.duration_since(entry.window_start.unwrap_or(now));
+        Ok((entry.hits, window.saturating_sub(elapsed)))
+    }
+
+    fn locked_for(&self, key: &str) -> Result<Option<Duration>, BackendError> {
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap();
+        Ok(entries.get(key)
+            .and_then(|e| e.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now))
+    }
+
+    fn record_failure(&self, key: &str, decay: Duration) -> Result<u32, BackendError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry(key.to_string()).or_default();
+        match entry.last_failure {
+            Some(last) if now.duration_since(last) < decay => entry.failures += 1,
+            _ => entry.failures = 1,
+        }
+        entry.last_failure = Some(now);
+        Ok(entry.failures)
+    }
+
+    fn lock(&self, key: &str, duration: Duration) -> Result<(), BackendError> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.entry(key.to_string()).or_default().locked_until = Some(Instant::now() + duration);
+        Ok(())
+    }
+
+    fn reset_failures(&self, key: &str) -> Result<(), BackendError> {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(key) {
+            entry.failures = 0;
+            entry.last_failure = None;
+            entry.locked_until = None;
+        }
+        Ok(())
+    }
+}
+
+/// Hits between sweeps of stale rows from the `rate_limit` table
+const POSTGRES_PRUNE_EVERY: u64 = 1_000;
+
+pub struct PostgresBackend {
+    store: Arc<Mutex<Store>>,
+    hits: AtomicU64,
+}
+
+impl PostgresBackend {
+    pub fn new(store: Arc<Mutex<Store>>) -> PostgresBackend {
+        PostgresBackend { store, hits: AtomicU64::new(0) }
+    }
+}
+
+fn until_from_now(until: chrono::NaiveDateTime) -> Duration {
+    (until - chrono::Utc::now().naive_utc()).to_std().unwrap_or_default()
+}
+
+impl LimiterBackend for PostgresBackend {
+    fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), BackendError> {
+        let mut locked = self.store.lock().unwrap();
+        if self.hits.fetch_add(1, Ordering::Relaxed).is_multiple_of(POSTGRES_PRUNE_EVERY) {
+            let before = chrono::Utc::now().naive_utc() - chrono::Duration::days(1);
+            if let Err(e) = locked.prune_rate_limits(before) {
+                tracing::warn!(error = ?e, "failed to prune rate limit state");
+            }
+        }
+        let (hits, window_start) = locked.rate_limit_hit(key, window.as_secs() as i64)?;
+        let resets_at = window_start + chrono::Duration::from_std(window)?;
+        Ok((hits.max(0) as u32, until_from_now(resets_at)))
+    }
+
+    fn locked_for(&self, key: &str) -> Result<Option<Duration>, BackendError> {
+        let until = self.store.lock().unwrap().rate_limit_locked_until(key)?;
+        Ok(until.map(until_from_now))
+    }
+
+    fn record_failure(&self, key: &str, decay: Duration) -> Result<u32, BackendError> {
+        let failures = self.store.lock().unwrap().rate_limit_failure(key, decay.as_secs() as i64)?;
+        Ok(failures.max(0) as u32)
+    }
+
+    fn lock(&self, key: &str, duration: Duration) -> Result<(), BackendError> {
+        let until = chrono::Utc::now().naive_utc() + chrono::Duration::from_std(duration)?;
+        self.store.lock().unwrap().rate_limit_lock(key, until)?;
+        Ok(())
+    }
+
+    fn reset_failures(&self, key: &str) -> Result<(), BackendError> {
+        self.store.lock().unwrap().rate_limit_reset_failures(key)?;
+        Ok(())
+    }
+}
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+/// Limits for the login endpoints
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    pub window: Duration,
+    pub per_ip: u32,
+    pub per_username: u32,
+    /// Consecutive failures before a username is locked out
+    pub username_lockout_after: u32,
+    /// Consecutive failures before an IP is locked out; higher, since many users may share one
+    pub ip_lockout_after: u32,
+    /// First lockout; each further failure doubles it
+    pub lockout_base: Duration,
+    pub lockout_max: Duration,
+    /// Quiet period after which a failure streak is forgotten
+    pub failure_decay: Duration,
+    /// Take the client address from `X-Forwarded-For`; only behind a trusted proxy
+    pub trust_forwarded_for: bool,
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> RateLimitConfig {
+        RateLimitConfig {
+            window: Duration::from_secs(env_u64("LOGIN_RATE_LIMIT_WINDOW_SECONDS", 60)),
+            per_ip: env_u64("LOGIN_RATE_LIMIT_PER_IP", 20) as u32,
+            per_username: env_u64("LOGIN_RATE_LIMIT_PER_USERNAME", 10) as u32,
+            username_lockout_after: env_u64("LOGIN_LOCKOUT_USERNAME_FAILURES", 5) as u32,
+            ip_lockout_after: env_u64("LOGIN_LOCKOUT_IP_FAILURES", 20) as u32,
+            lockout_base: Duration::from_secs(env_u64("LOGIN_LOCKOUT_BASE_SECONDS", 30)),
+            lockout_max: Duration::from_secs(env_u64("LOGIN_LOCKOUT_MAX_SECONDS", 60 * 60)),
+            failure_decay: Duration::from_secs(env_u64("LOGIN_FAILURE_DECAY_SECONDS", 60 * 60)),
+            trust_forwarded_for: std::env::var("TRUST_FORWARDED_FOR").is_ok_and(|v| v == "true"),
+        }
+    }
+
+    /// Lockout earned by `failures` consecutive failures against a threshold
+    fn lockout(&self, failures: u32, threshold: u32) -> Option<Duration> {
+        if threshold == 0 || failures < threshold {
+            return None;
+        }
+        let doublings = (failures - threshold).min(16);
+        Some(self.lockout_base.saturating_mul(1 << doublings).min(self.lockout_max))
+    }
+}
+
+/// Pick the limiter backend from `RATE_LIMIT_BACKEND` (`memory`, the default, or `postgres`)
+pub fn backend_from_env(store: Arc<Mutex<Store>>) -> Arc<dyn LimiterBackend> {
+    match std::env::var("RATE_LIMIT_BACKEND").as_deref() {
+        Ok("postgres") => Arc::new(PostgresBackend::new(store)),
+        Ok("memory") | Err(_) => Arc::new(MemoryBackend::default()),
+        Ok(other) => {
+            tracing::warn!(backend = %other, "unknown RATE_LIMIT_BACKEND, using memory");
+            Arc::new(MemoryBackend::default())
+        }
+    }
+}
+
+/// Marks a login response that issued tokens. Only these clear a username's failure
+/// streak; a 2FA challenge after a correct password is neither a success nor a failure.
+#[derive(Clone, Copy, Debug)]
+pub struct TokensIssued;
+
+/// Largest login body read to find the username; anything bigger is refused
+const MAX_LOGIN_BODY_BYTES: usize = 4 * 1024;
+
+/// Throttles a login-style endpoint by client IP and by the `username` in its JSON
+/// body. Responses of 401 count as failures toward a progressive lockout; one marked
+/// `TokensIssued` clears the streak.
+#[derive(Clone)]
+pub struct LoginRateLimit {
+    backend: Arc<dyn LimiterBackend>,
+    config: Arc<RateLimitConfig>,
+}
+
+impl LoginRateLimit {
+    pub fn new(backend: Arc<dyn LimiterBackend>, config: RateLimitConfig) -> LoginRateLimit {
+        LoginRateLimit { backend, config: Arc::new(config) }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for LoginRateLimit {
+    type Output = LoginRateLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        LoginRateLimitEndpoint { inner: ep, limit: self.clone() }
+    }
+}
+
+pub struct LoginRateLimitEndpoint<E> {
+    inner: E,
+    limit: LoginRateLimit,
+}
+
+fn too_many_requests(retry_after: Duration) -> AppError {
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    AppError::too_many_requests(
+        format!("Too many attempts, try again in {} seconds", seconds.max(1)),
+        retry_after,
+    )
+}
+
+pub(crate) fn client_ip(req: &Request, trust_forwarded_for: bool) -> Option<IpAddr> {
+    if trust_forwarded_for {
+        let forwarded = req.headers()
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .and_then(|v| v.trim().parse().ok());
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+    req.remote_addr().as_socket_addr().map(|addr| addr.ip())
+}
+
+impl<E: Endpoint> LoginRateLimitEndpoint<E> {
+    /// Fail open on backend errors: a broken limiter shouldn't lock everyone out
+    fn guard<T: Default>(result: Result<T, BackendError>) -> T {
+        result.unwrap_or_else(|e| {
+            tracing::error!(error = %e, "rate limiter backend failed");
+            T::default()
+        })
+    }
+
+    /// Retry-After for the first key that is locked or over its limit
+    fn check(&self, keys: &[(String, u32)]) -> Option<Duration> {
+        let backend = &self.limit.backend;
+        for (key, _) in keys {
+            if let Some(remaining) = Self::guard(backend.locked_for(key)) {
+                return Some(remaining);
+            }
+        }
+        for (key, limit) in keys {
+            let (hits, reset) = Self::guard(backend.hit(key, self.limit.config.window));
+            if hits > *limit {
+                return Some(reset);
+            }
+        }
+        None
+    }
+
+    fn record_outcome(&self, keys: &[(String, u32)], status: StatusCode, tokens_issued: bool) {
+        let backend = &self.limit.backend;
+        let config = &self.limit.config;
+        for (key, _) in keys {
+            if status == StatusCode::UNAUTHORIZED {
+                let failures = Self::guard(backend.record_failure(key, config.failure_decay));
+                let threshold = if key.starts_with("user:") {
+                    config.username_lockout_after
+                } else {
+                    config.ip_lockout_after
+                };
+                if let Some(duration) = config.lockout(failures, threshold) {
+                    tracing::warn!(key = %key, failures, lockout_seconds = duration.as_secs(), "login locked out");
+                    Self::guard(backend.lock(key, duration));
+                }
+            } else if tokens_issued && key.starts_with("user:") {
+                // Only the username streak resets; one success from a shared IP
+                // shouldn't wipe out failures against other accounts
+                Self::guard(backend.reset_failures(key));
+            }
+        }
+    }
+}
+
+impl<E: Endpoint> Endpoint for LoginRateLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> poem::Result<Self::Output> {
+        let config = &self.limit.config;
+        let mut keys = Vec::new();
+        if let Some(ip) = client_ip(&req, config.trust_forwarded_for) {
+            keys.push((format!("ip:{}", ip), config.per_ip));
+        }
+
+        // The username is in the JSON body, so read it and hand the bytes back
+        let body = req.take_body().into_bytes_limit(MAX_LOGIN_BODY_BYTES).await?;
+        if let Some(username) = serde_json::from_slice::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v.get("username").and_then(|u| u.as_str()).map(|u| u.trim().to_lowercase()))
+            .filter(|u| !u.is_empty())
+        {
+            keys.push((format!("user:{}", username), config.per_username));
+        }
+        req.set_body(Body::from(body));
+
+        if let Some(retry_after) = self.check(&keys) {
+            tracing::warn!(path = %req.uri().path(), "login rate limited");
+            return Err(too_many_requests(retry_after).into());
+        }
+
+        // Errors are passed up untouched so they still get rendered as problem documents
+        let result = self.inner.call(req).await.map(IntoResponse::into_response);
+        let (status, tokens_issued) = match &result {
+            Ok(response) => (response.status(), response.extensions().get::<TokensIssued>().is_some()),
+            Err(err) => (err.status(), false),
+        };
+        self.record_outcome(&keys, status, tokens_issued);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poem::web::Json;
+    use poem::{EndpointExt, handler};
+
+    use super::*;
+
+    #[derive(serde::Deserialize)]
+    struct Login {
+        password: String,
+    }
+
+    /// Signs in on "right", answers "2fa" with a challenge and rejects anything else
+    #[handler]
+    fn login(Json(login): Json<Login>) -> poem::Result<Response> {
+        match login.password.as_str() {
+            "right" => Ok(Response::builder().extension(TokensIssued).finish()),
+            "2fa" => Ok(Response::builder().finish()),
+            _ => Err(AppError::unauthorized("Invalid username or password").into()),
+        }
+    }
+
+    fn config() -> RateLimitConfig {
+        RateLimitConfig {
+            window: Duration::from_secs(60),
+            per_ip: 100,
+            per_username: 100,
+            username_lockout_after: 3,
+            ip_lockout_after: 100,
+            lockout_base: Duration::from_secs(30),
+            lockout_max: Duration::from_secs(60 * 60),
+            failure_decay: Duration::from_secs(60 * 60),
+            trust_forwarded_for: false,
+        }
+    }
+
+    fn limited(config: RateLimitConfig) -> impl Endpoint {
+        login.with(LoginRateLimit::new(Arc::new(MemoryBackend::default()), config))
+    }
+
+    /// Status of a sign-in as alice, with the Retry-After of a 429
+    async fn attempt(ep: &impl Endpoint, password: &str) -> (StatusCode, Option<Duration>) {
+        let body = serde_json::json!({ "username": "Alice", "password": password }).to_string();
+        let req = Request::builder().content_type("application/json").body(body);
+        match ep.call(req).await {
+            Ok(resp) => (resp.into_response().status(), None),
+            Err(err) => {
+                let retry_after = err.downcast_ref::<AppError>().and_then(|e| e.retry_after);
+                (err.status(), retry_after)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn locks_out_a_username_after_repeated_failures() {
+        let ep = limited(config());
+        for _ in 0..3 {
+            assert_eq!(attempt(&ep, "wrong").await.0, StatusCode::UNAUTHORIZED);
+        }
+        let (status, retry_after) = attempt(&ep, "right").await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "locked out even with the right password");
+        let retry_after = retry_after.expect("a lockout says when to retry");
+        assert!(retry_after <= Duration::from_secs(30) && retry_after > Duration::from_secs(25));
+    }
+
+    #[test]
+    fn lockouts_double_up_to_the_maximum() {
+        let config = RateLimitConfig { lockout_max: Duration::from_secs(100), ..config() };
+        assert_eq!(config.lockout(2, 3), None);
+        assert_eq!(config.lockout(3, 3), Some(Duration::from_secs(30)));
+        assert_eq!(config.lockout(4, 3), Some(Duration::from_secs(60)));
+        assert_eq!(config.lockout(5, 3), Some(Duration::from_secs(100)));
+        assert_eq!(config.lockout(50, 0), None, "a zero threshold turns lockouts off");
+    }
+
+    #[tokio::test]
+    async fn limits_attempts_per_window() {
+        let ep = limited(RateLimitConfig { per_username: 2, window: Duration::from_millis(200), ..config() });
+        assert_eq!(attempt(&ep, "right").await.0, StatusCode::OK);
+        assert_eq!(attempt(&ep, "right").await.0, StatusCode::OK);
+        let (status, retry_after) = attempt(&ep, "right").await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(retry_after.unwrap() <= Duration::from_millis(200));
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(attempt(&ep, "right").await.0, StatusCode::OK, "a new window starts afresh");
+    }
+
+    #[tokio::test]
+    async fn signing_in_resets_the_failure_streak() {
+        let ep = limited(config());
+        attempt(&ep, "wrong").await;
+        attempt(&ep, "wrong").await;
+        assert_eq!(attempt(&ep, "right").await.0, StatusCode::OK);
+        attempt(&ep, "wrong").await;
+        assert_eq!(attempt(&ep, "wrong").await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(attempt(&ep, "right").await.0, StatusCode::OK, "only two failures since the reset");
+    }
+
+    #[tokio::test]
+    async fn a_two_factor_challenge_does_not_reset_the_streak() {
+        let ep = limited(config());
+        attempt(&ep, "wrong").await;
+        attempt(&ep, "wrong").await;
+        assert_eq!(attempt(&ep, "2fa").await.0, StatusCode::OK);
+        assert_eq!(attempt(&ep, "wrong").await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(attempt(&ep, "2fa").await.0, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn refuses_oversized_bodies() {
+        let ep = limited(config());
+        let body = format!(r#"{{"username":"alice","password":"{}"}}"#, "x".repeat(MAX_LOGIN_BODY_BYTES));
+        let err = ep.call(Request::builder().content_type("application/json").body(body)).await.err().unwrap();
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
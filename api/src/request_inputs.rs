@@ -1,19 +1,316 @@
+use std::collections::BTreeMap;
+
 use serde::{Serialize,Deserialize};
+use utoipa::{IntoParams, ToSchema};
 
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,ToSchema)]
 pub struct CreateWebsiteInput{
    pub url: String,
+   /// Defaults to the caller's personal organization
+   pub organization_id: Option<String>,
+   /// Display name, to tell monitors on similar URLs apart
+   pub name: Option<String>,
+   pub description: Option<String>,
+   /// Free-form labels such as `{"team": "payments"}`, used for filtering and notification routing
+   #[serde(default)]
+   pub tags: BTreeMap<String, String>,
+   /// Seconds between checks, 30 to 86400; defaults to 60
+   pub check_interval_seconds: Option<i32>,
+   #[serde(default)]
+   pub assertions: CheckAssertions,
+}
+
+/// Conditions a response must meet for a check to count as up. With none set, any 2xx
+/// response is up.
+#[derive(Serialize,Deserialize,ToSchema,Clone,Default,PartialEq,Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckAssertions {
+   /// Status codes that count as up, instead of any 2xx
+   #[serde(default, skip_serializing_if = "Vec::is_empty")]
+   pub status: Vec<u16>,
+   /// Text the response body must contain
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub body_contains: Option<String>,
+   /// Slower responses count as down
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub max_response_time_ms: Option<i32>,
+}
+
+/// An organization's monitors as code. Applying a document creates, updates and deletes
+/// websites until they match it; see `POST /v1/organizations/{org_id}/monitors`.
+#[derive(Serialize,Deserialize,ToSchema,Clone,Default,Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorDocument {
+   #[serde(default)]
+   pub monitors: Vec<MonitorSpec>,
+}
+
+#[derive(Serialize,Deserialize,ToSchema,Clone,PartialEq,Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorSpec {
+   /// Stable identifier of this monitor within the organization; renaming it
+   /// replaces the website, losing its history
+   pub key: String,
+   pub url: String,
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub name: Option<String>,
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub description: Option<String>,
+   /// Seconds between checks, 30 to 86400; defaults to 60
+   #[serde(default = "default_check_interval")]
+   pub interval_seconds: i32,
+   #[serde(default, skip_serializing_if = "is_default")]
+   pub assertions: CheckAssertions,
+   #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+   pub tags: BTreeMap<String, String>,
+}
 
+pub fn default_check_interval() -> i32 {
+   60
 }
 
-#[derive(Serialize,Deserialize)]
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+   *value == T::default()
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
 pub struct CreateUserInput {
    pub username: String,
    pub password: String
 }
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct RefreshTokenInput {
+   pub refresh_token: String
+}
+
+/// Fields other than `url` are left unchanged when omitted; an empty
+/// `name` or `description` clears it
+#[derive(Serialize,Deserialize,ToSchema)]
 pub struct UpdateWebsiteInput {
-   pub url:String
+   pub url:String,
+   pub name: Option<String>,
+   pub description: Option<String>,
+   /// Replaces all of the website's tags
+   pub tags: Option<BTreeMap<String, String>>,
+   pub check_interval_seconds: Option<i32>,
+   /// Replaces all of the website's assertions
+   pub assertions: Option<CheckAssertions>,
+}
+
+#[derive(Serialize,Deserialize,ToSchema,Default)]
+pub struct PauseWebsiteInput {
+   /// Resume checks automatically at this time (`YYYY-MM-DDTHH:MM:SS` UTC or RFC 3339);
+   /// paused until resumed by hand when omitted
+   pub resume_at: Option<String>
+}
+
+#[derive(Serialize,Deserialize,ToSchema,Clone,Copy,Default,PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyScope {
+   /// Only requests that change nothing: safe (GET/HEAD) ones, less the legacy
+   /// `GET /website/{id}/check`
+   #[default]
+   Read,
+   /// Everything the owning user can do, except managing API keys, 2FA and sessions
+   Full
+}
+
+impl ApiKeyScope {
+   pub fn as_str(&self) -> &'static str {
+      match self {
+         ApiKeyScope::Read => "read",
+         ApiKeyScope::Full => "full",
+      }
+   }
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct CreateApiKeyInput {
+   pub name: String,
+   #[serde(default)]
+   pub scope: ApiKeyScope,
+   pub expires_at: Option<String>
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct CreateOrganizationInput {
+   pub name: String
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct UpdateMemberInput {
+   /// One of owner, admin, editor, viewer
+   pub role: String
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct CreateNotificationChannelInput {
+   pub name: String,
+   /// Webhook that `incident.opened` and `incident.resolved` events are POSTed to
+   pub url: String,
+   /// Only websites carrying all of these tags are routed here; empty matches every website
+   #[serde(default)]
+   pub match_tags: BTreeMap<String, String>,
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct CreateInvitationInput {
+   pub username: String,
+   /// One of owner, admin, editor, viewer
+   pub role: String
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct TwoFactorCodeInput {
+   /// A 6-digit authenticator code, or a recovery code where accepted
+   pub code: String
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct TwoFactorSignInInput {
+   pub challenge_token: String,
+   /// A 6-digit authenticator code or a recovery code
+   pub code: String
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct ChangePasswordInput {
+   pub current_password: String,
+   pub new_password: String
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct PasswordResetRequestInput {
+   pub username: String
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct PasswordResetConfirmInput {
+   pub token: String,
+   pub new_password: String
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct DeleteAccountInput {
+   /// Required unless the account signs in through SSO only
+   pub password: Option<String>
+}
+
+#[derive(Serialize,Deserialize,IntoParams,Default)]
+#[into_params(parameter_in = Query)]
+pub struct ListWebsitesQuery {
+   pub organization_id: Option<String>,
+   /// Only websites in this state
+   #[param(inline)]
+   pub status: Option<StatusParam>,
+   /// Case-insensitive search in the URL, name and description
+   pub q: Option<String>,
+   /// Comma-separated `key=value` tags that must all match, e.g. `team=payments,env=prod`
+   pub tag: Option<String>,
+   #[serde(default)]
+   #[param(inline)]
+   pub sort: SortParam,
+   #[serde(default)]
+   #[param(inline)]
+   pub order: OrderParam,
+   /// Page size, 1 to 500; defaults to 50
+   pub limit: Option<i64>,
+   pub offset: Option<i64>,
+}
+
+#[derive(Serialize,Deserialize,ToSchema,Clone,Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusParam {
+   Up,
+   Down,
+   Pending,
+   Paused,
+}
+
+#[derive(Serialize,Deserialize,ToSchema,Clone,Copy,Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortParam {
+   #[default]
+   TimeAdded,
+   Url,
+   Status,
+   ResponseTime,
+   LastChecked,
+   Uptime,
+}
+
+#[derive(Serialize,Deserialize,ToSchema,Clone,Copy,Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderParam {
+   Asc,
+   #[default]
+   Desc,
+}
+
+#[derive(Serialize,Deserialize,IntoParams,Default)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryQuery {
+   pub limit: Option<i64>,
+   pub cursor: Option<String>,
+   pub from: Option<String>,
+   pub to: Option<String>,
+   pub is_up: Option<bool>,
+}
+
+#[derive(Serialize,Deserialize,ToSchema,Clone,Copy,Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+   #[default]
+   Csv,
+   Ndjson,
+}
+
+#[derive(Serialize,Deserialize,IntoParams,Default)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+   #[serde(default)]
+   #[param(inline)]
+   pub format: ExportFormat,
+   pub from: Option<String>,
+   pub to: Option<String>,
+}
+
+#[derive(Serialize,Deserialize,ToSchema,Clone,Copy,Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+   #[default]
+   Yaml,
+   Json,
+}
+
+#[derive(Serialize,Deserialize,IntoParams,Default)]
+#[into_params(parameter_in = Query)]
+pub struct ExportMonitorsQuery {
+   #[serde(default)]
+   #[param(inline)]
+   pub format: DocumentFormat,
+}
+
+#[derive(Serialize,Deserialize,IntoParams,Default)]
+#[into_params(parameter_in = Query)]
+pub struct ImportMonitorsQuery {
+   /// Report what the import would change without changing anything
+   #[serde(default)]
+   pub dry_run: bool,
+}
+
+#[derive(Serialize,Deserialize,IntoParams,Default)]
+#[into_params(parameter_in = Query)]
+pub struct AuditLogQuery {
+   pub organization_id: String,
+   pub actor_id: Option<String>,
+   pub action: Option<String>,
+   pub target_type: Option<String>,
+   pub target_id: Option<String>,
+   pub from: Option<String>,
+   pub to: Option<String>,
+   pub limit: Option<i64>,
+   pub cursor: Option<String>,
 }
@@ -1,39 +1,93 @@
+use std::collections::BTreeMap;
+
 use serde::{Serialize,Deserialize};
+use utoipa::{ToResponse, ToSchema};
 
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,ToSchema)]
 pub struct CreateWebsiteOutput {
     pub id: String
 }
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,ToSchema)]
 pub struct CreateUserOutput {
     pub id: String
 }
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,ToSchema)]
 pub struct SignInOutput {
-    pub jwt: String
+    pub jwt: String,
+    pub refresh_token: String,
+    /// Seconds until `jwt` expires
+    pub expires_in: u64
+}
+
+/// Returned by sign-in instead of tokens when the user has 2FA turned on
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct TwoFactorChallengeOutput {
+    pub two_factor_required: bool,
+    /// Exchange for tokens at `/sign-in/2fa` along with a code
+    pub challenge_token: String,
+    pub expires_in: u64
+}
+
+#[derive(Serialize,Deserialize,ToSchema)]
+#[serde(untagged)]
+pub enum SignInResponse {
+    Tokens(SignInOutput),
+    Challenge(TwoFactorChallengeOutput)
 }
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,ToSchema)]
 pub struct GetWebsiteOutput {
-    pub url: String
+    #[serde(flatten)]
+    pub website: WebsiteItem
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct ListWebsiteOutput {
-    pub items: Vec<WebsiteItem>
+    pub items: Vec<WebsiteItem>,
+    /// Websites matching the filters, across all pages
+    pub total: i64,
+    /// Pass as `offset` to fetch the next page; absent on the last page
+    pub next_offset: Option<i64>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct WebsiteItem {
     pub id: String,
     pub url: String,
-    pub time_added: String
+    pub organization_id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub tags: BTreeMap<String, String>,
+    /// Set on websites managed by a monitors-as-code document
+    pub external_key: Option<String>,
+    pub check_interval_seconds: i32,
+    pub assertions: crate::request_inputs::CheckAssertions,
+    pub time_added: String,
+    /// `up`, `down`, `paused`, or `pending` until the first check
+    pub status: String,
+    pub paused_at: Option<String>,
+    /// When a paused website resumes by itself
+    pub resume_at: Option<String>,
+    pub last_checked: Option<String>,
+    pub last_down_time: Option<String>,
+    pub response_time_ms: Option<i32>,
+    /// Percentage of the last 24 hours' checks that were up; null without checks
+    pub uptime_24h: Option<f64>,
+    pub open_incident: Option<IncidentItem>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CheckNowOutput {
+    pub is_up: bool,
+    pub response_time_ms: Option<i32>,
+    pub status_code: Option<i32>,
+    pub error_message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct WebsiteStatusOutput {
     pub is_up: Option<bool>,
     pub last_checked: Option<String>,
@@ -41,7 +95,7 @@ pub struct WebsiteStatusOutput {
     pub response_time_ms: Option<i32>
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, ToSchema)]
     pub struct CheckHistoryItem {
         pub checked_at: String,
         pub is_up: bool,
@@ -50,8 +104,255 @@ pub struct WebsiteStatusOutput {
         pub error_message: Option<String>
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, ToSchema)]
     pub struct WebsiteHistoryOutput {
-        pub items: Vec<CheckHistoryItem>
+        pub items: Vec<CheckHistoryItem>,
+        pub next_cursor: Option<String>,
+        pub prev_cursor: Option<String>
     }
 
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct IncidentItem {
+    pub id: String,
+    pub started_at: String,
+    pub resolved_at: Option<String>,
+    pub duration_seconds: Option<i64>,
+    pub cause: Option<String>
+}
+
+/// Pushed to `/websites/live` subscribers as the worker produces results
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    Check {
+        website_id: String,
+        organization_id: String,
+        url: String,
+        checked_at: String,
+        is_up: bool,
+        response_time_ms: Option<i32>,
+        status_code: Option<i32>,
+        error_message: Option<String>
+    },
+    StatusChange {
+        website_id: String,
+        organization_id: String,
+        url: String,
+        changed_at: String,
+        previous_is_up: Option<bool>,
+        is_up: bool
+    }
+}
+
+impl LiveEvent {
+    pub fn organization_id(&self) -> &str {
+        match self {
+            LiveEvent::Check { organization_id, .. }
+            | LiveEvent::StatusChange { organization_id, .. } => organization_id,
+        }
+    }
+
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            LiveEvent::Check { .. } => "check",
+            LiveEvent::StatusChange { .. } => "status_change",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyItem {
+    pub id: String,
+    pub name: String,
+    pub prefix: String,
+    pub scope: String,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateApiKeyOutput {
+    /// The full key; it is only ever returned here
+    pub key: String,
+    #[serde(flatten)]
+    pub item: ApiKeyItem
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ListApiKeysOutput {
+    pub items: Vec<ApiKeyItem>
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OrganizationItem {
+    pub id: String,
+    pub name: String,
+    pub personal: bool,
+    /// The caller's role in the organization
+    pub role: String,
+    pub created_at: String
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ListOrganizationsOutput {
+    pub items: Vec<OrganizationItem>
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MemberItem {
+    pub user_id: String,
+    pub username: String,
+    pub role: String,
+    pub joined_at: String
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ListMembersOutput {
+    pub items: Vec<MemberItem>
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct NotificationChannelItem {
+    pub id: String,
+    pub organization_id: String,
+    pub name: String,
+    pub url: String,
+    pub match_tags: BTreeMap<String, String>,
+    pub created_at: String
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ListNotificationChannelsOutput {
+    pub items: Vec<NotificationChannelItem>
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct InvitationItem {
+    pub id: String,
+    pub organization_id: String,
+    pub organization_name: Option<String>,
+    pub invitee_user_id: String,
+    pub invitee_username: Option<String>,
+    pub role: String,
+    pub status: String,
+    pub created_at: String,
+    pub expires_at: String
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ListInvitationsOutput {
+    pub items: Vec<InvitationItem>
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TwoFactorStatusOutput {
+    pub enabled: bool,
+    pub recovery_codes_remaining: i64
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TotpEnrollmentOutput {
+    /// Base32 secret for manual entry
+    pub secret: String,
+    /// `otpauth://` URI, usually rendered as a QR code
+    pub otpauth_uri: String
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RecoveryCodesOutput {
+    /// Each code works once; they are only ever returned here
+    pub recovery_codes: Vec<String>
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OidcAuthorizationOutput {
+    /// Open in a browser to continue at the identity provider
+    pub authorization_url: String
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AuditLogItem {
+    pub id: String,
+    pub organization_id: Option<String>,
+    /// Missing for anonymous attempts, e.g. a failed sign-in
+    pub actor_id: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub ip: Option<String>,
+    pub created_at: String
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AuditLogOutput {
+    pub items: Vec<AuditLogItem>,
+    pub next_cursor: Option<String>
+}
+
+/// A field a monitors-as-code import changes on an existing website
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct MonitorFieldChange {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct MonitorUpdate {
+    pub key: String,
+    pub website_id: String,
+    pub changes: Vec<MonitorFieldChange>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct MonitorDeletion {
+    pub key: String,
+    pub website_id: String,
+    pub url: String,
+}
+
+/// What importing a monitors document does, or did
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct MonitorPlanOutput {
+    /// True when nothing was changed because the import was a dry run
+    pub dry_run: bool,
+    /// Keys of monitors that are created
+    pub create: Vec<String>,
+    pub update: Vec<MonitorUpdate>,
+    /// Websites with a key that the document no longer mentions
+    pub delete: Vec<MonitorDeletion>,
+    /// Keys of monitors that already match the document
+    pub unchanged: Vec<String>,
+}
+
+/// Body of every error response; see `error::problem_response`
+#[derive(Serialize,Deserialize,ToSchema,ToResponse,Clone,Debug)]
+#[response(
+    description = "Error details as an RFC 7807 problem document",
+    content_type = "application/problem+json"
+)]
+pub struct Problem {
+    /// Always `about:blank`; `code` carries the machine-readable reason
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Reason phrase of the HTTP status
+    pub title: String,
+    pub status: u16,
+    /// Stable identifier clients can match on, e.g. `not_found`
+    pub code: String,
+    /// Human-readable explanation; may change between releases
+    pub message: String,
+    /// Echoes the `x-request-id` response header, for support requests
+    pub request_id: Option<String>,
+}
+
+/// Body of operations that only confirm they went through
+#[derive(Serialize,Deserialize,ToSchema)]
+pub struct SuccessMessage {
+    pub success: bool,
+    pub message: String,
+}
@@ -0,0 +1,334 @@
+use std::sync::{Arc, Mutex};
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use diesel::result::Error as DieselError;
+use poem::{
+    handler,
+    Request,
+    web::{Data, Json},
+};
+use rand_core::{OsRng, RngCore};
+use store::store::Store;
+
+use crate::audit::{self, AuditEvent};
+use crate::auth::AuthSession;
+use crate::password;
+use crate::request_inputs::{
+    ChangePasswordInput, DeleteAccountInput, PasswordResetConfirmInput, PasswordResetRequestInput,
+};
+use crate::routes::user::validate_password;
+
+fn internal_error(message: &'static str) -> poem::Error {
+    poem::Error::from_string(message, poem::http::StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn hash_new_password(new_password: &str) -> Result<String, poem::Error> {
+    validate_password(new_password)?;
+    password::hash_password(new_password).map_err(|e| {
+        tracing::error!(error = ?e, "password hashing failed");
+        internal_error("Failed to process password")
+    })
+}
+
+/// Lifetime of a password reset token in seconds
+fn reset_token_ttl() -> i64 {
+    std::env::var("PASSWORD_RESET_TTL_SECONDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60 * 60) // 1 hour
+}
+
+/// Users have no email address on file, so reset tokens are handed to an
+/// operator-run webhook that knows how to reach them
+fn reset_webhook_url() -> Option<String> {
+    std::env::var("PASSWORD_RESET_WEBHOOK_URL").ok()
+}
+
+async fn deliver_reset_token(url: String, username: String, token: String, expires_at: String) {
+    let payload = serde_json::json!({
+        "username": username,
+        "token": token,
+        "expires_at": expires_at,
+    });
+    let result = reqwest::Client::new()
+        .post(&url)
+        .timeout(std::time::Duration::from_secs(10))
+        .json(&payload)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+    if let Err(e) = result {
+        tracing::error!(error = ?e, "failed to deliver password reset token");
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/account/password",
+    tag = "account",
+    summary = "Change the caller's password",
+    request_body = crate::request_inputs::ChangePasswordInput,
+    responses(
+        (status = 200, description = "Password changed", body = crate::openapi::SuccessMessage),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn change_password(
+    Json(data): Json<ChangePasswordInput>,
+    auth: AuthSession,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<serde_json::Value>, poem::Error> {
+    let new_hash = hash_new_password(&data.new_password)?;
+
+    let mut locked = s.lock().unwrap();
+    let has_password = locked.has_password(auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to fetch user");
+        internal_error("Failed to change password")
+    })?;
+    if !has_password {
+        return Err(poem::Error::from_string(
+            "This account signs in with SSO and has no password; use a password reset to set one",
+            poem::http::StatusCode::CONFLICT,
+        ));
+    }
+    let valid = locked.verify_user_password(auth.user_id.clone(), &data.current_password).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to verify password");
+        internal_error("Failed to change password")
+    })?;
+    if !valid {
+        return Err(poem::Error::from_string(
+            "Current password is incorrect",
+            poem::http::StatusCode::UNAUTHORIZED,
+        ));
+    }
+
+    locked.set_password(auth.user_id.clone(), new_hash).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to update password");
+        internal_error("Failed to change password")
+    })?;
+    // Keep the caller signed in, but drop every other session
+    let revoked = locked.revoke_other_sessions(auth.user_id.clone(), auth.session_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to revoke sessions");
+        internal_error("Failed to change password")
+    })?;
+    tracing::info!(user_id = %auth.user_id, revoked, "password changed");
+    audit::record(&mut locked, req, Some(&auth.user_id), AuditEvent {
+        action: "user.password_change",
+        target_type: "user",
+        target_id: Some(auth.user_id.clone()),
+        after: Some(serde_json::json!({ "revoked_sessions": revoked })),
+        ..Default::default()
+    });
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Password changed",
+    })))
+}
+
+/// Start a password reset. Always answers the same way so it can't be used to probe
+/// which usernames exist.
+#[utoipa::path(
+    post,
+    path = "/v1/password-reset",
+    tag = "account",
+    summary = "Send a password reset token to the configured reset webhook",
+    description = "The token goes to the server's `PASSWORD_RESET_WEBHOOK_URL` along with the \
+        username, for an operator or integration to pass on; users have no email address here. \
+        Answers 404 when no webhook is configured.",
+    request_body = crate::request_inputs::PasswordResetRequestInput,
+    responses(
+        (status = 202, description = "Accepted whether or not the account exists", body = crate::openapi::SuccessMessage),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 429, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    )
+)]
+#[handler]
+pub fn request_password_reset(
+    Json(data): Json<PasswordResetRequestInput>,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<(poem::http::StatusCode, Json<serde_json::Value>), poem::Error> {
+    let Some(webhook_url) = reset_webhook_url() else {
+        return Err(poem::Error::from_string(
+            "Password reset is not configured",
+            poem::http::StatusCode::NOT_FOUND,
+        ));
+    };
+
+    let mut locked = s.lock().unwrap();
+    match locked.find_user_id_by_username(data.username.clone()) {
+        Ok(user_id) => {
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            let token = URL_SAFE_NO_PAD.encode(bytes);
+            let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::seconds(reset_token_ttl());
+
+            locked.create_password_reset_token(user_id.clone(), &token, expires_at).map_err(|e| {
+                tracing::error!(user_id = %user_id, error = ?e, "failed to create password reset token");
+                internal_error("Failed to start password reset")
+            })?;
+            tracing::info!(user_id = %user_id, "password reset requested");
+            let organization_id = locked.personal_organization(user_id.clone()).ok().map(|org| org.id);
+            audit::record(&mut locked, req, None, AuditEvent {
+                action: "user.password_reset_request",
+                target_type: "user",
+                target_id: Some(user_id.clone()),
+                organization_id,
+                ..Default::default()
+            });
+            tokio::spawn(deliver_reset_token(
+                webhook_url,
+                data.username,
+                token,
+                expires_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            ));
+        }
+        Err(DieselError::NotFound) => {}
+        Err(e) => {
+            tracing::error!(error = ?e, "failed to look up user for password reset");
+            return Err(internal_error("Failed to start password reset"));
+        }
+    }
+
+    Ok((poem::http::StatusCode::ACCEPTED, Json(serde_json::json!({
+        "success": true,
+        "message": "If the account exists, a reset token has been sent",
+    }))))
+}
+
+/// Finish a reset: set the new password and sign the user out everywhere
+#[utoipa::path(
+    post,
+    path = "/v1/password-reset/confirm",
+    tag = "account",
+    summary = "Set a new password with a reset token",
+    request_body = crate::request_inputs::PasswordResetConfirmInput,
+    responses(
+        (status = 200, description = "Password changed; existing sessions are revoked", body = crate::openapi::SuccessMessage),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 429, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    )
+)]
+#[handler]
+pub fn confirm_password_reset(
+    Json(data): Json<PasswordResetConfirmInput>,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<serde_json::Value>, poem::Error> {
+    let new_hash = hash_new_password(&data.new_password)?;
+
+    let mut locked = s.lock().unwrap();
+    let user_id = locked.reset_password(&data.token, new_hash).map_err(|e| {
+        tracing::error!(error = ?e, "failed to reset password");
+        internal_error("Failed to reset password")
+    })?
+    .ok_or_else(|| poem::Error::from_string(
+        "Invalid or expired reset token",
+        poem::http::StatusCode::BAD_REQUEST,
+    ))?;
+    tracing::info!(user_id = %user_id, "password reset completed");
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "user.password_reset",
+        target_type: "user",
+        target_id: Some(user_id.clone()),
+        ..Default::default()
+    });
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Password reset, please sign in again",
+    })))
+}
+
+/// Delete the caller's account along with their personal organization and its websites
+#[utoipa::path(
+    delete,
+    path = "/v1/account",
+    tag = "account",
+    summary = "Delete the caller's account",
+    request_body = crate::request_inputs::DeleteAccountInput,
+    responses(
+        (status = 204, description = "Account deleted"),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn delete_account(
+    Json(data): Json<DeleteAccountInput>,
+    auth: AuthSession,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<serde_json::Value>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let has_password = locked.has_password(auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to fetch user");
+        internal_error("Failed to delete account")
+    })?;
+    if has_password {
+        let valid = match data.password.as_deref() {
+            Some(pw) => locked.verify_user_password(auth.user_id.clone(), pw).map_err(|e| {
+                tracing::error!(user_id = %auth.user_id, error = ?e, "failed to verify password");
+                internal_error("Failed to delete account")
+            })?,
+            None => false,
+        };
+        if !valid {
+            return Err(poem::Error::from_string(
+                "Password is incorrect",
+                poem::http::StatusCode::UNAUTHORIZED,
+            ));
+        }
+    }
+
+    let blocking = locked.sole_owner_organizations(auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to check organizations");
+        internal_error("Failed to delete account")
+    })?;
+    if !blocking.is_empty() {
+        let names: Vec<String> = blocking.into_iter().map(|org| org.name).collect();
+        return Err(poem::Error::from_string(
+            format!(
+                "Transfer ownership of these organizations before deleting your account: {}",
+                names.join(", ")
+            ),
+            poem::http::StatusCode::CONFLICT,
+        ));
+    }
+
+    // The personal organization and any the user was alone in go with the account, so
+    // only the shared ones that live on can keep a record of who left and when
+    let username = locked.get_username(auth.user_id.clone()).ok();
+    let remaining = locked.delete_user(auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to delete account");
+        internal_error("Failed to delete account")
+    })?;
+    tracing::info!(user_id = %auth.user_id, username = ?username, "account deleted");
+    for organization_id in remaining {
+        audit::record(&mut locked, req, Some(&auth.user_id), AuditEvent {
+            action: "user.delete",
+            target_type: "user",
+            target_id: Some(auth.user_id.clone()),
+            organization_id: Some(organization_id),
+            before: Some(serde_json::json!({ "username": username })),
+            ..Default::default()
+        });
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Account deleted",
+    })))
+}
@@ -0,0 +1,199 @@
+use std::sync::{Arc, Mutex};
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use diesel::result::Error as DieselError;
+use poem::{
+    handler,
+    Request,
+    web::{Data, Json, Path},
+};
+use rand_core::{OsRng, RngCore};
+use store::models::api_key::ApiKey;
+use store::store::Store;
+
+use crate::audit::{self, AuditEvent};
+use crate::auth::{API_KEY_PREFIX, AuthSession};
+use crate::cursor::parse_timestamp;
+use crate::request_inputs::CreateApiKeyInput;
+use crate::request_outputs::{ApiKeyItem, CreateApiKeyOutput, ListApiKeysOutput};
+
+fn generate_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", API_KEY_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn to_item(key: ApiKey) -> ApiKeyItem {
+    ApiKeyItem {
+        id: key.id,
+        name: key.name,
+        prefix: key.prefix,
+        scope: key.scope,
+        created_at: key.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        expires_at: key.expires_at.map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        last_used_at: key.last_used_at.map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string()),
+    }
+}
+
+// Key management takes `AuthSession`, so it needs a signed-in user rather than another API key
+
+#[utoipa::path(
+    post,
+    path = "/v1/api-keys",
+    tag = "api-keys",
+    summary = "Create an API key",
+    request_body = crate::request_inputs::CreateApiKeyInput,
+    responses(
+        (status = 201, description = "The key; its secret is shown once", body = crate::request_outputs::CreateApiKeyOutput, headers(("Location" = String, description = "URL of the new API key"))),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn create_api_key(
+    Json(data): Json<CreateApiKeyInput>,
+    auth: AuthSession,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<CreateApiKeyOutput>, poem::Error> {
+    if data.name.trim().is_empty() {
+        return Err(poem::Error::from_string(
+            "Name cannot be empty",
+            poem::http::StatusCode::BAD_REQUEST,
+        ));
+    }
+    let expires_at = match data.expires_at.as_deref() {
+        Some(value) => {
+            let expires_at = parse_timestamp(value).ok_or_else(|| {
+                poem::Error::from_string(
+                    "Invalid 'expires_at' timestamp, expected YYYY-MM-DDTHH:MM:SS or RFC 3339",
+                    poem::http::StatusCode::BAD_REQUEST,
+                )
+            })?;
+            if expires_at <= chrono::Utc::now().naive_utc() {
+                return Err(poem::Error::from_string(
+                    "'expires_at' must be in the future",
+                    poem::http::StatusCode::BAD_REQUEST,
+                ));
+            }
+            Some(expires_at)
+        }
+        None => None,
+    };
+
+    let key = generate_api_key();
+    let mut locked = s.lock().unwrap();
+    let created = locked.create_api_key(
+        auth.user_id.clone(),
+        data.name.trim().to_string(),
+        &key,
+        data.scope.as_str().to_string(),
+        expires_at,
+    ).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to create API key");
+        poem::Error::from_string(
+            "Failed to create API key",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+    audit::record(&mut locked, req, Some(&auth.user_id), AuditEvent {
+        action: "api_key.create",
+        target_type: "api_key",
+        target_id: Some(created.id.clone()),
+        after: Some(serde_json::json!({
+            "name": created.name,
+            "prefix": created.prefix,
+            "scope": created.scope,
+            "expires_at": created.expires_at.map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        })),
+        ..Default::default()
+    });
+
+    Ok(Json(CreateApiKeyOutput {
+        key,
+        item: to_item(created),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/api-keys",
+    tag = "api-keys",
+    summary = "List the caller's API keys",
+    responses(
+        (status = 200, description = "API keys, without their secrets", body = crate::request_outputs::ListApiKeysOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn list_api_keys(
+    auth: AuthSession,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<ListApiKeysOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let keys = locked.list_api_keys(auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to list API keys");
+        poem::Error::from_string(
+            "Failed to list API keys",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+
+    Ok(Json(ListApiKeysOutput {
+        items: keys.into_iter().map(to_item).collect(),
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/api-keys/{key_id}",
+    tag = "api-keys",
+    summary = "Revoke an API key",
+    params(("key_id" = String, Path, description = "API key id")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn revoke_api_key(
+    Path(id): Path<String>,
+    auth: AuthSession,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<serde_json::Value>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    locked.revoke_api_key(id.clone(), auth.user_id.clone()).map_err(|e| {
+        tracing::error!(api_key_id = %id, error = ?e, "failed to revoke API key");
+        match e {
+            DieselError::NotFound => poem::Error::from_string(
+                "API key not found",
+                poem::http::StatusCode::NOT_FOUND,
+            ),
+            _ => poem::Error::from_string(
+                "Failed to revoke API key",
+                poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        }
+    })?;
+    audit::record(&mut locked, req, Some(&auth.user_id), AuditEvent {
+        action: "api_key.revoke",
+        target_type: "api_key",
+        target_id: Some(id.clone()),
+        ..Default::default()
+    });
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "API key revoked",
+    })))
+}
@@ -0,0 +1,113 @@
+use std::sync::{Arc, Mutex};
+
+use poem::{
+    handler,
+    web::{Data, Json, Query},
+};
+use store::models::audit_log::AuditFilter;
+use store::models::check_history::HistoryCursor;
+use store::store::Store;
+
+use crate::auth::AuthUser;
+use crate::authz::{Permission, authorize_organization};
+use crate::cursor::{decode_cursor, encode_cursor, parse_timestamp};
+use crate::request_inputs::AuditLogQuery;
+use crate::request_outputs::{AuditLogItem, AuditLogOutput};
+
+fn parse_time_param(value: Option<&str>, name: &str) -> Result<Option<chrono::NaiveDateTime>, poem::Error> {
+    match value {
+        Some(v) => parse_timestamp(v).map(Some).ok_or_else(|| {
+            poem::Error::from_string(
+                format!("Invalid '{}' timestamp, expected YYYY-MM-DDTHH:MM:SS or RFC 3339", name),
+                poem::http::StatusCode::BAD_REQUEST,
+            )
+        }),
+        None => Ok(None),
+    }
+}
+
+/// An organization's audit trail, newest first; admins and owners only
+#[utoipa::path(
+    get,
+    path = "/v1/audit-log",
+    tag = "audit",
+    summary = "Page through an organization's audit log",
+    params(AuditLogQuery),
+    responses(
+        (status = 200, description = "One page of entries, newest first", body = crate::request_outputs::AuditLogOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn list_audit_log(
+    Query(query): Query<AuditLogQuery>,
+    AuthUser(user_id): AuthUser,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<AuditLogOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &query.organization_id, Permission::ManageMembers)?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    // Only ever paged backwards in time, so only `Older` cursors are meaningful here
+    let older_than = match query.cursor.as_deref() {
+        Some(token) => match decode_cursor(token) {
+            Some(HistoryCursor::Older { checked_at, id }) => Some((checked_at, id)),
+            _ => return Err(poem::Error::from_string(
+                "Invalid cursor",
+                poem::http::StatusCode::BAD_REQUEST,
+            )),
+        },
+        None => None,
+    };
+
+    let filter = AuditFilter {
+        actor_id: query.actor_id,
+        action: query.action,
+        target_type: query.target_type,
+        target_id: query.target_id,
+        from: parse_time_param(query.from.as_deref(), "from")?,
+        to: parse_time_param(query.to.as_deref(), "to")?,
+    };
+
+    // Fetch one extra row to learn whether another page exists
+    let mut entries = locked.list_audit_log(query.organization_id.clone(), &filter, older_than, limit + 1)
+    .map_err(|e| {
+        tracing::error!(organization_id = %query.organization_id, error = ?e, "failed to fetch audit log");
+        poem::Error::from_string(
+            "Failed to fetch audit log",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+
+    let has_more = entries.len() as i64 > limit;
+    entries.truncate(limit as usize);
+    let next_cursor = if has_more {
+        entries.last().map(|entry| encode_cursor(&HistoryCursor::Older {
+            checked_at: entry.created_at,
+            id: entry.id.clone(),
+        }))
+    } else {
+        None
+    };
+
+    let items = entries.into_iter()
+    .map(|entry| AuditLogItem {
+        id: entry.id,
+        organization_id: entry.organization_id,
+        actor_id: entry.actor_id,
+        action: entry.action,
+        target_type: entry.target_type,
+        target_id: entry.target_id,
+        before: entry.before,
+        after: entry.after,
+        ip: entry.ip,
+        created_at: entry.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    })
+    .collect();
+
+    Ok(Json(AuditLogOutput { items, next_cursor }))
+}
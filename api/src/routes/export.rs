@@ -0,0 +1,211 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, NaiveDateTime};
+use diesel::result::Error as DieselError;
+use futures_util::stream;
+use poem::{
+    Body, Response, handler,
+    http::{StatusCode, header},
+    web::{Data, Path, Query},
+};
+use serde::Serialize;
+
+use crate::auth::AuthUser;
+use crate::authz::{Permission, authorize_website};
+use crate::cursor::parse_timestamp;
+use crate::request_inputs::{ExportFormat, ExportQuery};
+use crate::request_outputs::{CheckHistoryItem, IncidentItem};
+use store::models::check_history::{HistoryCursor, HistoryFilter};
+use store::store::Store;
+
+/// Rows fetched from the store per chunk; the store lock is only held for one batch at a time
+const EXPORT_BATCH_SIZE: i64 = 1000;
+
+#[utoipa::path(
+    get,
+    path = "/v1/websites/{website_id}/history/export",
+    tag = "websites",
+    summary = "Download a website's full check history",
+    params(("website_id" = String, Path, description = "Website id"), ExportQuery),
+    responses(
+        (status = 200, description = "Checks as CSV or one JSON object per line", content((String = "text/csv"), (crate::request_outputs::CheckHistoryItem = "application/x-ndjson"))),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn export_website_history(
+    Path(id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ExportQuery>,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Response, poem::Error> {
+    authorize_website(&mut s.lock().unwrap(), &user_id, &id, Permission::ViewWebsite)?;
+    let (from, to) = parse_range(&query)?;
+
+    let store = s.clone();
+    let website_id = id.clone();
+    let filter = HistoryFilter { from, to, is_up: None };
+    // Start just before the window and walk forward so the export is oldest first
+    let mut cursor = HistoryCursor::Newer {
+        checked_at: from.unwrap_or(DateTime::UNIX_EPOCH.naive_utc()),
+        id: String::new(),
+    };
+    let mut done = false;
+
+    let body = export_body(query.format, move || {
+        if done {
+            return Ok(None);
+        }
+        let mut rows = store.lock().unwrap()
+            .get_website_history(website_id.clone(), &filter, Some(&cursor), EXPORT_BATCH_SIZE)?;
+        rows.reverse();
+        done = (rows.len() as i64) < EXPORT_BATCH_SIZE;
+        let Some(last) = rows.last() else {
+            return Ok(None);
+        };
+        cursor = HistoryCursor::Newer { checked_at: last.checked_at, id: last.id.clone() };
+
+        Ok(Some(rows.into_iter()
+            .map(|h| CheckHistoryItem {
+                checked_at: h.checked_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                is_up: h.is_up,
+                response_time_ms: h.response_time_ms,
+                status_code: h.status_code,
+                error_message: h.error_message,
+            })
+            .collect()))
+    });
+
+    Ok(export_response(body, query.format, &format!("{}-history", id)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/websites/{website_id}/incidents/export",
+    tag = "websites",
+    summary = "Download a website's incidents",
+    params(("website_id" = String, Path, description = "Website id"), ExportQuery),
+    responses(
+        (status = 200, description = "Incidents as CSV or one JSON object per line", content((String = "text/csv"), (crate::request_outputs::IncidentItem = "application/x-ndjson"))),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn export_website_incidents(
+    Path(id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ExportQuery>,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Response, poem::Error> {
+    authorize_website(&mut s.lock().unwrap(), &user_id, &id, Permission::ViewWebsite)?;
+    let (from, to) = parse_range(&query)?;
+
+    let store = s.clone();
+    let website_id = id.clone();
+    let mut after: Option<(NaiveDateTime, String)> = None;
+    let mut done = false;
+
+    let body = export_body(query.format, move || {
+        if done {
+            return Ok(None);
+        }
+        let rows = store.lock().unwrap()
+            .get_incidents(website_id.clone(), from, to, after.clone(), EXPORT_BATCH_SIZE)?;
+        done = (rows.len() as i64) < EXPORT_BATCH_SIZE;
+        let Some(last) = rows.last() else {
+            return Ok(None);
+        };
+        after = Some((last.started_at, last.id.clone()));
+
+        Ok(Some(rows.into_iter()
+            .map(|i| IncidentItem {
+                id: i.id,
+                started_at: i.started_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                resolved_at: i.resolved_at.map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string()),
+                duration_seconds: i.resolved_at.map(|dt| (dt - i.started_at).num_seconds()),
+                cause: i.cause,
+            })
+            .collect()))
+    });
+
+    Ok(export_response(body, query.format, &format!("{}-incidents", id)))
+}
+
+fn parse_range(query: &ExportQuery) -> Result<(Option<NaiveDateTime>, Option<NaiveDateTime>), poem::Error> {
+    let parse = |value: Option<&str>, name: &str| match value {
+        Some(v) => parse_timestamp(v).map(Some).ok_or_else(|| {
+            poem::Error::from_string(
+                format!("Invalid '{}' timestamp, expected YYYY-MM-DDTHH:MM:SS or RFC 3339", name),
+                StatusCode::BAD_REQUEST,
+            )
+        }),
+        None => Ok(None),
+    };
+    Ok((parse(query.from.as_deref(), "from")?, parse(query.to.as_deref(), "to")?))
+}
+
+/// Turn a batch fetcher into a streaming body; `fetch` returns `None` once the rows run out
+fn export_body<T, F>(format: ExportFormat, fetch: F) -> Body
+where
+    T: Serialize + Send + 'static,
+    F: FnMut() -> Result<Option<Vec<T>>, DieselError> + Send + 'static,
+{
+    let chunks = stream::unfold((fetch, true), move |(mut fetch, first)| async move {
+        let rows = match fetch() {
+            Ok(Some(rows)) => rows,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::error!(error = ?e, "failed to stream export");
+                return Some((Err(std::io::Error::other("export failed")), (fetch, false)));
+            }
+        };
+        Some((encode_rows(format, &rows, first), (fetch, false)))
+    });
+    Body::from_bytes_stream(chunks)
+}
+
+fn encode_rows<T: Serialize>(format: ExportFormat, rows: &[T], with_header: bool) -> Result<Vec<u8>, std::io::Error> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(with_header)
+                .from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row).map_err(std::io::Error::other)?;
+            }
+            writer.into_inner().map_err(|e| std::io::Error::other(e.to_string()))
+        }
+        ExportFormat::Ndjson => {
+            let mut buf = Vec::new();
+            for row in rows {
+                serde_json::to_writer(&mut buf, row)?;
+                buf.push(b'\n');
+            }
+            Ok(buf)
+        }
+    }
+}
+
+fn export_response(body: Body, format: ExportFormat, name: &str) -> Response {
+    let (content_type, extension) = match format {
+        ExportFormat::Csv => ("text/csv", "csv"),
+        ExportFormat::Ndjson => ("application/x-ndjson", "ndjson"),
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.{}\"", name, extension),
+        )
+        .body(body)
+}
@@ -0,0 +1,63 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::stream;
+use poem::{
+    handler,
+    web::{Data, sse::{Event, SSE}},
+};
+use store::store::Store;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::auth::AuthUser;
+use crate::request_outputs::LiveEvent;
+
+/// Server-Sent Events feed of check results and status changes for websites in the
+/// caller's organizations. Memberships are read once, when the stream opens.
+#[utoipa::path(
+    get,
+    path = "/v1/websites/live",
+    tag = "websites",
+    summary = "Stream check results as server-sent events",
+    responses(
+        (status = 200, description = "An event per check of a website the caller can see", content_type = "text/event-stream"),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn live_events(
+    AuthUser(user_id): AuthUser,
+    Data(tx): Data<&broadcast::Sender<LiveEvent>>,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<SSE, poem::Error> {
+    let organization_ids = s.lock().unwrap().user_organization_ids(user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %user_id, error = ?e, "failed to fetch organizations");
+        poem::Error::from_string(
+            "Failed to open live feed",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+    let rx = tx.subscribe();
+
+    let events = stream::unfold((rx, user_id, organization_ids), |(mut rx, user_id, organization_ids)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) if organization_ids.iter().any(|id| id == event.organization_id()) => {
+                    let data = serde_json::to_string(&event).unwrap_or_default();
+                    let sse = Event::message(data).event_type(event.event_type());
+                    return Some((sse, (rx, user_id, organization_ids)));
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(user_id = %user_id, skipped, "live subscriber lagged");
+                    continue;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Ok(SSE::new(events).keep_alive(Duration::from_secs(15)))
+}
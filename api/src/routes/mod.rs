@@ -1,2 +1,12 @@
+pub mod account;
+pub mod api_key;
+pub mod audit_log;
+pub mod export;
+pub mod live;
+pub mod monitors;
+pub mod notification;
+pub mod oidc;
+pub mod organization;
+pub mod two_factor;
 pub mod user;
 pub mod website;
\ No newline at end of file
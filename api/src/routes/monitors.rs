@@ -0,0 +1,430 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use poem::{
+    Body, Request, Response, handler,
+    web::{Data, Json, Path, Query},
+};
+use serde_json::Value;
+use store::models::website::{Website, WebsiteChanges, WebsiteDetails, WebsiteSync};
+use store::store::Store;
+
+use crate::audit::{self, AuditEvent};
+use crate::auth::AuthUser;
+use crate::authz::{Permission, authorize_organization};
+use crate::error::{AppError, Entity};
+use crate::monitor::{self, assertions_of};
+use crate::request_inputs::{DocumentFormat, ExportMonitorsQuery, ImportMonitorsQuery, MonitorDocument, MonitorSpec};
+use crate::request_outputs::{MonitorDeletion, MonitorFieldChange, MonitorPlanOutput, MonitorUpdate};
+use crate::routes::website::{MAX_DESCRIPTION_LEN, MAX_NAME_LEN, text_field, validate_url, website_snapshot};
+
+/// Keeps a single import to one reasonably sized transaction
+const MAX_MONITORS: usize = 1000;
+const MAX_KEY_LEN: usize = 128;
+
+/// A website as it appears in a document. Websites created outside of one are keyed by
+/// their id, so an exported document can be applied back as it is.
+fn monitor_spec(website: &Website) -> MonitorSpec {
+    MonitorSpec {
+        key: website.external_key.clone().unwrap_or_else(|| website.id.clone()),
+        url: website.url.clone(),
+        name: website.name.clone(),
+        description: website.description.clone(),
+        interval_seconds: website.check_interval_seconds,
+        assertions: assertions_of(website),
+        tags: crate::tags::from_json(&website.tags),
+    }
+}
+
+/// Check every monitor up front, trimming names and descriptions the way the website
+/// routes do, so a document fails as a whole before anything is planned
+fn validate_document(document: &mut MonitorDocument) -> Result<(), AppError> {
+    if document.monitors.len() > MAX_MONITORS {
+        return Err(AppError::bad_request(format!("A document can hold at most {} monitors", MAX_MONITORS)));
+    }
+    let mut keys = HashSet::new();
+    for spec in &mut document.monitors {
+        let valid_key = !spec.key.is_empty()
+            && spec.key.len() <= MAX_KEY_LEN
+            && spec.key.chars().all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c));
+        if !valid_key {
+            return Err(AppError::bad_request(format!(
+                "Invalid monitor key '{}': use 1-{} letters, digits, '-', '_', '.' or '/'",
+                spec.key, MAX_KEY_LEN
+            )));
+        }
+        if !keys.insert(spec.key.clone()) {
+            return Err(AppError::bad_request(format!("Monitor key '{}' appears more than once", spec.key)));
+        }
+        validate_spec(spec).map_err(|e| AppError { message: format!("Monitor '{}': {}", spec.key, e.message), ..e })?;
+    }
+    Ok(())
+}
+
+fn validate_spec(spec: &mut MonitorSpec) -> Result<(), AppError> {
+    validate_url(&spec.url)?;
+    spec.name = text_field(spec.name.take(), "name", MAX_NAME_LEN)?;
+    spec.description = text_field(spec.description.take(), "description", MAX_DESCRIPTION_LEN)?;
+    monitor::validate_interval(spec.interval_seconds)?;
+    monitor::validate_assertions(&spec.assertions)?;
+    crate::tags::validate(&spec.tags)
+}
+
+/// What applying a document to an organization's websites takes
+struct Plan {
+    sync: WebsiteSync,
+    output: MonitorPlanOutput,
+    /// Websites the sync updates or deletes, by id, as they are now
+    previous: HashMap<String, Website>,
+}
+
+fn field_change<T: serde::Serialize + PartialEq>(
+    changes: &mut Vec<MonitorFieldChange>,
+    field: &str,
+    before: &T,
+    after: &T,
+) -> bool {
+    if before == after {
+        return false;
+    }
+    changes.push(MonitorFieldChange {
+        field: field.to_string(),
+        before: serde_json::to_value(before).unwrap_or(Value::Null),
+        after: serde_json::to_value(after).unwrap_or(Value::Null),
+    });
+    true
+}
+
+/// Match the document's monitors to websites by key: first by `external_key`, then by id
+/// for websites that haven't been given one. A website matched by id keeps no key, as
+/// an export lists it that way, and only keyed websites are ever deleted, so websites
+/// created by hand are never removed by leaving them out of a document.
+fn plan(document: MonitorDocument, websites: Vec<Website>) -> Plan {
+    let mut keyed = BTreeMap::new();
+    let mut unkeyed = HashMap::new();
+    for website in websites {
+        match website.external_key.clone() {
+            Some(key) => keyed.insert(key, website),
+            None => unkeyed.insert(website.id.clone(), website),
+        };
+    }
+
+    let mut plan = Plan {
+        sync: WebsiteSync::default(),
+        output: MonitorPlanOutput {
+            dry_run: false,
+            create: Vec::new(),
+            update: Vec::new(),
+            delete: Vec::new(),
+            unchanged: Vec::new(),
+        },
+        previous: HashMap::new(),
+    };
+
+    for spec in document.monitors {
+        let Some(website) = keyed.remove(&spec.key).or_else(|| unkeyed.remove(&spec.key)) else {
+            plan.output.create.push(spec.key.clone());
+            plan.sync.create.push((spec.url, WebsiteDetails {
+                name: spec.name,
+                description: spec.description,
+                tags: crate::tags::to_json(spec.tags),
+                external_key: Some(spec.key),
+                check_interval_seconds: spec.interval_seconds,
+                assertions: serde_json::to_value(&spec.assertions).unwrap_or_default(),
+            }));
+            continue;
+        };
+
+        let current = monitor_spec(&website);
+        let mut changes = Vec::new();
+        let url_changed = field_change(&mut changes, "url", &current.url, &spec.url);
+        let name_changed = field_change(&mut changes, "name", &current.name, &spec.name);
+        let description_changed = field_change(&mut changes, "description", &current.description, &spec.description);
+        let interval_changed = field_change(&mut changes, "interval_seconds", &current.interval_seconds, &spec.interval_seconds);
+        let assertions_changed = field_change(&mut changes, "assertions", &current.assertions, &spec.assertions);
+        let tags_changed = field_change(&mut changes, "tags", &current.tags, &spec.tags);
+
+        if changes.is_empty() {
+            plan.output.unchanged.push(spec.key);
+            continue;
+        }
+        plan.sync.update.push((website.id.clone(), WebsiteChanges {
+            url: url_changed.then(|| spec.url.clone()),
+            name: name_changed.then(|| spec.name.clone()),
+            description: description_changed.then(|| spec.description.clone()),
+            tags: tags_changed.then(|| crate::tags::to_json(spec.tags.clone())),
+            external_key: None,
+            check_interval_seconds: interval_changed.then_some(spec.interval_seconds),
+            assertions: assertions_changed.then(|| serde_json::to_value(&spec.assertions).unwrap_or_default()),
+        }));
+        plan.output.update.push(MonitorUpdate { key: spec.key, website_id: website.id.clone(), changes });
+        plan.previous.insert(website.id.clone(), website);
+    }
+
+    for (key, website) in keyed {
+        plan.output.delete.push(MonitorDeletion { key, website_id: website.id.clone(), url: website.url.clone() });
+        plan.sync.delete.push(website.id.clone());
+        plan.previous.insert(website.id.clone(), website);
+    }
+    plan
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/organizations/{org_id}/monitors",
+    tag = "websites",
+    summary = "Export an organization's websites as a monitors document",
+    params(("org_id" = String, Path, description = "Organization id"), ExportMonitorsQuery),
+    responses(
+        (status = 200, description = "The monitors document, in YAML or JSON", content((String = "application/yaml"), (crate::request_inputs::MonitorDocument = "application/json"))),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn export_monitors(
+    Path(org_id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ExportMonitorsQuery>,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Response, AppError> {
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &org_id, Permission::ViewWebsite)?;
+    let websites = locked.list_organization_websites(org_id.clone())
+    .map_err(|e| AppError::store_for(Entity::Organization(&org_id), e, "Organization not found", "Failed to list websites"))?;
+    let document = MonitorDocument { monitors: websites.iter().map(monitor_spec).collect() };
+
+    let (body, content_type) = match query.format {
+        DocumentFormat::Yaml => (serde_yaml::to_string(&document).map_err(|e| {
+            tracing::error!(error = %e, "failed to serialize monitors document");
+            AppError::internal("Failed to export monitors")
+        })?, "application/yaml"),
+        DocumentFormat::Json => (serde_json::to_string_pretty(&document).unwrap_or_default(), "application/json"),
+    };
+    Ok(Response::builder().content_type(content_type).body(body))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/organizations/{org_id}/monitors",
+    tag = "websites",
+    summary = "Make an organization's websites match a monitors document",
+    description = "Takes the document as YAML or JSON. Monitors are matched to websites by `key`; \
+        missing ones are created, differing ones updated, and websites with a key the document \
+        doesn't mention are deleted along with their history. Applying the same document twice \
+        changes nothing the second time. Use `dry_run` to review the plan first.",
+    params(("org_id" = String, Path, description = "Organization id"), ImportMonitorsQuery),
+    request_body(content = crate::request_inputs::MonitorDocument, content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "What was, or with `dry_run` would be, changed", body = crate::request_outputs::MonitorPlanOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub async fn import_monitors(
+    Path(org_id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ImportMonitorsQuery>,
+    req: &Request,
+    body: Body,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<MonitorPlanOutput>, AppError> {
+    let body = body.into_string().await
+    .map_err(|_| AppError::bad_request("Failed to read request body"))?;
+    // YAML is a superset of JSON, so this reads both
+    let mut document: MonitorDocument = serde_yaml::from_str(&body)
+    .map_err(|e| AppError::bad_request(format!("Invalid monitors document: {}", e)))?;
+    validate_document(&mut document)?;
+
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &org_id, Permission::EditWebsite)?;
+    let websites = locked.list_organization_websites(org_id.clone())
+    .map_err(|e| AppError::store_for(Entity::Organization(&org_id), e, "Organization not found", "Failed to list websites"))?;
+    let Plan { sync, mut output, mut previous } = plan(document, websites);
+    if !sync.delete.is_empty() {
+        authorize_organization(&mut locked, &user_id, &org_id, Permission::DeleteWebsite)?;
+    }
+    if query.dry_run {
+        output.dry_run = true;
+        return Ok(Json(output));
+    }
+
+    let synced = locked.sync_websites(user_id.clone(), org_id.clone(), sync)
+    .map_err(|e| AppError::store_for(Entity::Organization(&org_id), e, "Website not found", "Failed to apply monitors document"))?;
+    for website in &synced.created {
+        audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+            action: "website.create",
+            target_type: "website",
+            target_id: Some(website.id.clone()),
+            organization_id: Some(website.organization_id.clone()),
+            after: Some(website_snapshot(website)),
+            ..Default::default()
+        });
+    }
+    for website in &synced.updated {
+        let before = previous.remove(&website.id).map(|previous| website_snapshot(&previous)).unwrap_or_default();
+        let (before, after) = audit::diff(before, website_snapshot(website));
+        audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+            action: "website.update",
+            target_type: "website",
+            target_id: Some(website.id.clone()),
+            organization_id: Some(website.organization_id.clone()),
+            before,
+            after,
+        });
+    }
+    for website in &synced.deleted {
+        audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+            action: "website.delete",
+            target_type: "website",
+            target_id: Some(website.id.clone()),
+            organization_id: Some(website.organization_id.clone()),
+            before: Some(website_snapshot(website)),
+            ..Default::default()
+        });
+    }
+    Ok(Json(output))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::request_inputs::CheckAssertions;
+
+    use super::*;
+
+    fn spec(key: &str, url: &str) -> MonitorSpec {
+        MonitorSpec {
+            key: key.to_string(),
+            url: url.to_string(),
+            name: None,
+            description: None,
+            interval_seconds: 60,
+            assertions: CheckAssertions::default(),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// The website `spec` describes, as an apply creates it, or as made by hand with no key
+    fn website(id: &str, spec: &MonitorSpec, keyed: bool) -> Website {
+        Website {
+            id: id.to_string(),
+            url: spec.url.clone(),
+            user_id: "user".to_string(),
+            time_added: chrono::NaiveDateTime::default(),
+            is_up: None,
+            last_checked: None,
+            last_down_time: None,
+            response_time_ms: None,
+            organization_id: "org".to_string(),
+            name: spec.name.clone(),
+            description: spec.description.clone(),
+            tags: crate::tags::to_json(spec.tags.clone()),
+            paused_at: None,
+            resume_at: None,
+            external_key: keyed.then(|| spec.key.clone()),
+            check_interval_seconds: spec.interval_seconds,
+            assertions: serde_json::to_value(&spec.assertions).unwrap(),
+        }
+    }
+
+    fn document(monitors: Vec<MonitorSpec>) -> MonitorDocument {
+        MonitorDocument { monitors }
+    }
+
+    fn is_empty(plan: &Plan) -> bool {
+        plan.sync.create.is_empty() && plan.sync.update.is_empty() && plan.sync.delete.is_empty()
+            && plan.output.create.is_empty() && plan.output.update.is_empty() && plan.output.delete.is_empty()
+    }
+
+    #[test]
+    fn plans_creates_updates_and_deletes() {
+        let api = spec("api", "https://api.example.com");
+        let web = spec("web", "https://example.com");
+        let old = spec("old", "https://old.example.com");
+        let hand = spec("hand", "https://hand.example.com");
+        let websites = vec![
+            website("w1", &api, true),
+            website("w2", &web, true),
+            website("w3", &old, true),
+            website("w4", &hand, false),
+        ];
+        let moved = MonitorSpec { url: "https://www.example.com".to_string(), ..web.clone() };
+        let docs = spec("docs", "https://docs.example.com");
+
+        let plan = plan(document(vec![api, moved, docs]), websites);
+        assert_eq!(plan.output.create, vec!["docs"]);
+        assert_eq!(plan.sync.create[0].1.external_key.as_deref(), Some("docs"));
+        assert_eq!(plan.output.unchanged, vec!["api"]);
+
+        assert_eq!(plan.output.update.len(), 1);
+        let update = &plan.output.update[0];
+        assert_eq!((update.key.as_str(), update.website_id.as_str()), ("web", "w2"));
+        assert_eq!(update.changes.len(), 1);
+        assert_eq!(update.changes[0].field, "url");
+        let (id, changes) = &plan.sync.update[0];
+        assert_eq!(id, "w2");
+        assert_eq!(changes.url.as_deref(), Some("https://www.example.com"));
+        assert!(changes.name.is_none() && changes.tags.is_none() && changes.external_key.is_none());
+
+        assert_eq!(plan.sync.delete, vec!["w3"], "the website made by hand is not deleted");
+        assert_eq!(plan.output.delete[0].key, "old");
+        assert!(plan.previous.contains_key("w2") && plan.previous.contains_key("w3"));
+    }
+
+    #[test]
+    fn matches_unkeyed_websites_by_id() {
+        let hand = spec("w1", "https://example.com");
+        let renamed = MonitorSpec { name: Some("Home".to_string()), ..hand.clone() };
+
+        let plan = plan(document(vec![renamed]), vec![website("w1", &hand, false)]);
+        assert!(plan.output.create.is_empty(), "matched rather than created");
+        let update = &plan.output.update[0];
+        assert_eq!(update.website_id, "w1");
+        assert_eq!(update.changes.len(), 1);
+        assert_eq!(update.changes[0].field, "name");
+        assert!(plan.sync.update[0].1.external_key.is_none());
+    }
+
+    #[test]
+    fn an_export_applies_back_unchanged() {
+        let websites = vec![
+            website("w1", &spec("api", "https://api.example.com"), true),
+            website("w2", &spec("ignored", "https://example.com"), false),
+        ];
+        let export = document(websites.iter().map(monitor_spec).collect());
+
+        let plan = plan(export, websites);
+        assert!(is_empty(&plan));
+        assert_eq!(plan.output.unchanged, vec!["api", "w2"]);
+    }
+
+    #[test]
+    fn applying_a_document_twice_changes_nothing_the_second_time() {
+        let mut tagged = spec("web", "https://example.com");
+        tagged.tags.insert("team".to_string(), "payments".to_string());
+        tagged.assertions.max_response_time_ms = Some(500);
+        let monitors = vec![spec("api", "https://api.example.com"), tagged];
+
+        let first = plan(document(monitors.clone()), Vec::new());
+        assert_eq!(first.output.create, vec!["api", "web"]);
+        // What the first apply leaves behind
+        let websites = monitors.iter().enumerate()
+            .map(|(i, spec)| website(&format!("w{}", i), spec, true))
+            .collect();
+
+        let second = plan(document(monitors), websites);
+        assert!(is_empty(&second));
+        assert_eq!(second.output.unchanged, vec!["api", "web"]);
+    }
+}
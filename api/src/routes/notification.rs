@@ -0,0 +1,155 @@
+use std::sync::{Arc, Mutex};
+
+use poem::{
+    handler,
+    Request,
+    web::{Data, Json, Path},
+};
+use store::models::notification::NotificationChannel;
+use store::store::Store;
+
+use crate::audit::{self, AuditEvent};
+use crate::auth::AuthUser;
+use crate::authz::{Permission, authorize_organization};
+use crate::error::{AppError, Entity};
+use crate::request_inputs::CreateNotificationChannelInput;
+use crate::request_outputs::{ListNotificationChannelsOutput, NotificationChannelItem};
+
+fn to_channel_item(channel: NotificationChannel) -> NotificationChannelItem {
+    NotificationChannelItem {
+        match_tags: crate::tags::from_json(&channel.match_tags),
+        id: channel.id,
+        organization_id: channel.organization_id,
+        name: channel.name,
+        url: channel.url,
+        created_at: channel.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    }
+}
+
+/// What the audit log keeps of a channel. The URL is left out since webhook URLs
+/// often embed a secret.
+fn channel_snapshot(channel: &NotificationChannel) -> serde_json::Value {
+    serde_json::json!({
+        "name": channel.name,
+        "match_tags": channel.match_tags,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/organizations/{org_id}/notification-channels",
+    tag = "organizations",
+    summary = "List an organization's notification channels",
+    params(("org_id" = String, Path, description = "Organization id")),
+    responses(
+        (status = 200, description = "Notification channels", body = crate::request_outputs::ListNotificationChannelsOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn list_notification_channels(
+    Path(org_id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<ListNotificationChannelsOutput>, AppError> {
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &org_id, Permission::ManageNotifications)?;
+    let channels = locked.list_notification_channels(org_id.clone())
+    .map_err(|e| AppError::store_for(Entity::Organization(&org_id), e, "Organization not found", "Failed to list notification channels"))?;
+    Ok(Json(ListNotificationChannelsOutput {
+        items: channels.into_iter().map(to_channel_item).collect(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/organizations/{org_id}/notification-channels",
+    tag = "organizations",
+    summary = "Send incident notifications for matching websites to a webhook",
+    params(("org_id" = String, Path, description = "Organization id")),
+    request_body = crate::request_inputs::CreateNotificationChannelInput,
+    responses(
+        (status = 201, description = "Channel created", body = crate::request_outputs::NotificationChannelItem, headers(("Location" = String, description = "URL of the new notification channel"))),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn create_notification_channel(
+    Path(org_id): Path<String>,
+    Json(data): Json<CreateNotificationChannelInput>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<NotificationChannelItem>, AppError> {
+    let name = data.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::bad_request("Channel name cannot be empty"));
+    }
+    if !data.url.starts_with("http://") && !data.url.starts_with("https://") {
+        return Err(AppError::bad_request("URL must start with http:// or https://"));
+    }
+    crate::tags::validate(&data.match_tags)?;
+
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &org_id, Permission::ManageNotifications)?;
+    let channel = locked.create_notification_channel(org_id.clone(), name, data.url, crate::tags::to_json(data.match_tags))
+    .map_err(|e| AppError::store_for(Entity::Organization(&org_id), e, "Organization not found", "Failed to create notification channel"))?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "notification_channel.create",
+        target_type: "notification_channel",
+        target_id: Some(channel.id.clone()),
+        organization_id: Some(channel.organization_id.clone()),
+        after: Some(channel_snapshot(&channel)),
+        ..Default::default()
+    });
+    Ok(Json(to_channel_item(channel)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/organizations/{org_id}/notification-channels/{channel_id}",
+    tag = "organizations",
+    summary = "Stop sending notifications to a channel",
+    params(("org_id" = String, Path, description = "Organization id"), ("channel_id" = String, Path, description = "Notification channel id")),
+    responses(
+        (status = 204, description = "Channel deleted"),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn delete_notification_channel(
+    Path((org_id, channel_id)): Path<(String, String)>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &org_id, Permission::ManageNotifications)?;
+    let channel = locked.delete_notification_channel(org_id, channel_id.clone())
+    .map_err(|e| AppError::store_for(Entity::NotificationChannel(&channel_id), e, "Notification channel not found", "Failed to delete notification channel"))?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "notification_channel.delete",
+        target_type: "notification_channel",
+        target_id: Some(channel.id.clone()),
+        organization_id: Some(channel.organization_id.clone()),
+        before: Some(channel_snapshot(&channel)),
+        ..Default::default()
+    });
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Notification channel deleted",
+    })))
+}
@@ -0,0 +1,259 @@
+use std::sync::{Arc, Mutex};
+
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use poem::{
+    IntoResponse, Request, Response, handler,
+    web::{Data, Json, Query, Redirect},
+};
+use store::store::Store;
+
+use crate::audit::{self, AuditEvent};
+use crate::auth::AuthSession;
+use crate::oidc::{AuthorizationRequest, OidcClient};
+use crate::request_outputs::OidcAuthorizationOutput;
+use crate::session;
+
+/// How long the user has to finish logging in at the provider, in seconds
+const LOGIN_STATE_TTL_SECONDS: i64 = 10 * 60;
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+fn internal_error(message: &'static str) -> poem::Error {
+    poem::Error::from_string(message, poem::http::StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn sso_client(client: &Option<Arc<OidcClient>>) -> Result<Arc<OidcClient>, poem::Error> {
+    client.clone().ok_or_else(|| poem::Error::from_string(
+        "SSO is not configured",
+        poem::http::StatusCode::NOT_FOUND,
+    ))
+}
+
+async fn begin(
+    client: &OidcClient,
+    s: &Arc<Mutex<Store>>,
+    link_user_id: Option<String>,
+) -> Result<AuthorizationRequest, poem::Error> {
+    let request = client.authorization_request().await?;
+    let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::seconds(LOGIN_STATE_TTL_SECONDS);
+    s.lock().unwrap().create_oidc_state(
+        &request.state,
+        request.code_verifier.clone(),
+        request.nonce.clone(),
+        link_user_id,
+        expires_at,
+    ).map_err(|e| {
+        tracing::error!(error = ?e, "failed to store OIDC login state");
+        internal_error("Failed to start SSO login")
+    })?;
+    Ok(request)
+}
+
+/// Start an SSO login by sending the browser to the identity provider
+#[utoipa::path(
+    get,
+    path = "/v1/auth/oidc/login",
+    tag = "sso",
+    summary = "Start an SSO login",
+    responses(
+        (status = 303, description = "Redirect to the identity provider"),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    )
+)]
+#[handler]
+pub async fn oidc_login(
+    Data(client): Data<&Option<Arc<OidcClient>>>,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Redirect, poem::Error> {
+    let client = sso_client(client)?;
+    let request = begin(&client, s, None).await?;
+    Ok(Redirect::see_other(request.url))
+}
+
+/// Start linking a provider account to the signed-in user. Returns the URL instead of
+/// redirecting, since the caller authenticates with a bearer token.
+#[utoipa::path(
+    post,
+    path = "/v1/auth/oidc/link",
+    tag = "sso",
+    summary = "Start linking an SSO account to the caller",
+    responses(
+        (status = 200, description = "URL to send the browser to", body = crate::request_outputs::OidcAuthorizationOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub async fn oidc_link(
+    auth: AuthSession,
+    Data(client): Data<&Option<Arc<OidcClient>>>,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<OidcAuthorizationOutput>, poem::Error> {
+    let client = sso_client(client)?;
+    let request = begin(&client, s, Some(auth.user_id)).await?;
+    Ok(Json(OidcAuthorizationOutput { authorization_url: request.url }))
+}
+
+/// Where the provider sends the browser back. Signs the user in with the same tokens
+/// `sign_in` issues, provisioning an account on first login, or finishes a link.
+/// Local 2FA is not asked for here; the provider enforces its own.
+#[utoipa::path(
+    get,
+    path = "/v1/auth/oidc/callback",
+    tag = "sso",
+    summary = "Finish an SSO login or link",
+    params(CallbackQuery),
+    responses(
+        (status = 200, description = "Tokens after a login, or a confirmation after a link", body = crate::request_outputs::SignInOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    )
+)]
+#[handler]
+pub async fn oidc_callback(
+    Query(query): Query<CallbackQuery>,
+    req: &Request,
+    Data(client): Data<&Option<Arc<OidcClient>>>,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Response, poem::Error> {
+    let client = sso_client(client)?;
+    if let Some(error) = query.error {
+        tracing::warn!(error = %error, description = ?query.error_description, "SSO login failed at provider");
+        return Err(poem::Error::from_string(
+            format!("SSO login failed: {}", query.error_description.unwrap_or(error)),
+            poem::http::StatusCode::BAD_REQUEST,
+        ));
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Err(poem::Error::from_string(
+            "Missing 'code' or 'state'",
+            poem::http::StatusCode::BAD_REQUEST,
+        ));
+    };
+
+    let login_state = s.lock().unwrap().take_oidc_state(&state).map_err(|e| {
+        tracing::error!(error = ?e, "failed to fetch OIDC login state");
+        internal_error("Failed to complete SSO login")
+    })?
+    .ok_or_else(|| poem::Error::from_string(
+        "Invalid or expired login state",
+        poem::http::StatusCode::BAD_REQUEST,
+    ))?;
+
+    let claims = client.exchange_code(&code, &login_state.code_verifier, &login_state.nonce).await?;
+
+    let mut locked = s.lock().unwrap();
+    let existing = locked.find_identity_user(claims.iss.clone(), claims.sub.clone()).map_err(|e| {
+        tracing::error!(error = ?e, "failed to look up identity");
+        internal_error("Failed to complete SSO login")
+    })?;
+
+    if let Some(link_user_id) = login_state.link_user_id {
+        match existing {
+            Some(owner) if owner == link_user_id => {}
+            Some(_) => return Err(poem::Error::from_string(
+                "This SSO account is already linked to another user",
+                poem::http::StatusCode::CONFLICT,
+            )),
+            None => {
+                locked.link_identity(link_user_id.clone(), claims.iss.clone(), claims.sub.clone()).map_err(|e| {
+                    tracing::error!(user_id = %link_user_id, error = ?e, "failed to link identity");
+                    internal_error("Failed to link SSO account")
+                })?;
+                tracing::info!(user_id = %link_user_id, issuer = %claims.iss, "linked SSO identity");
+                audit::record(&mut locked, req, Some(&link_user_id), AuditEvent {
+                    action: "user.sso_link",
+                    target_type: "user",
+                    target_id: Some(link_user_id.clone()),
+                    after: Some(serde_json::json!({ "issuer": claims.iss, "subject": claims.sub })),
+                    ..Default::default()
+                });
+            }
+        }
+        return Ok(Json(serde_json::json!({
+            "success": true,
+            "message": "SSO account linked",
+        })).into_response());
+    }
+
+    let user_id = match existing {
+        Some(user_id) => user_id,
+        None => {
+            let username = claims.username_hint();
+            let by_username = if client.config.link_by_username {
+                match locked.find_user_id_by_username(username.clone()) {
+                    Ok(user_id) => Some(user_id),
+                    Err(DieselError::NotFound) => None,
+                    Err(e) => {
+                        tracing::error!(error = ?e, "failed to look up user");
+                        return Err(internal_error("Failed to complete SSO login"));
+                    }
+                }
+            } else {
+                None
+            };
+
+            match by_username {
+                Some(user_id) => {
+                    locked.link_identity(user_id.clone(), claims.iss.clone(), claims.sub.clone()).map_err(|e| {
+                        tracing::error!(user_id = %user_id, error = ?e, "failed to link identity");
+                        match e {
+                            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => poem::Error::from_string(
+                                "This SSO account is already linked to another user",
+                                poem::http::StatusCode::CONFLICT,
+                            ),
+                            _ => internal_error("Failed to complete SSO login"),
+                        }
+                    })?;
+                    tracing::info!(user_id = %user_id, issuer = %claims.iss, "linked SSO identity by username");
+                    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+                        action: "user.sso_link",
+                        target_type: "user",
+                        target_id: Some(user_id.clone()),
+                        after: Some(serde_json::json!({ "issuer": claims.iss, "subject": claims.sub })),
+                        ..Default::default()
+                    });
+                    user_id
+                }
+                None => {
+                    let user_id = locked.provision_sso_user(username.clone(), claims.iss.clone(), claims.sub.clone())
+                    .map_err(|e| {
+                        tracing::error!(error = ?e, "failed to provision SSO user");
+                        internal_error("Failed to complete SSO login")
+                    })?;
+                    tracing::info!(user_id = %user_id, issuer = %claims.iss, "provisioned SSO user");
+                    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+                        action: "user.sign_up",
+                        target_type: "user",
+                        target_id: Some(user_id.clone()),
+                        after: Some(serde_json::json!({ "issuer": claims.iss, "subject": claims.sub })),
+                        ..Default::default()
+                    });
+                    user_id
+                }
+            }
+        }
+    };
+
+    let response = session::start_session(&mut locked, &user_id)?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "user.sign_in",
+        target_type: "user",
+        target_id: Some(user_id.clone()),
+        after: Some(serde_json::json!({ "factor": "sso", "issuer": claims.iss })),
+        ..Default::default()
+    });
+    Ok(Json(response).into_response())
+}
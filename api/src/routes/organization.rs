@@ -0,0 +1,670 @@
+use std::sync::{Arc, Mutex};
+
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use poem::{
+    handler,
+    Request,
+    web::{Data, Json, Path},
+};
+use store::models::organization::OrganizationInvitation;
+use store::store::Store;
+
+use crate::audit::{self, AuditEvent};
+use crate::auth::AuthUser;
+use crate::authz::{Permission, Role, authorize_organization};
+use crate::error::AppError;
+use crate::request_inputs::{CreateInvitationInput, CreateOrganizationInput, UpdateMemberInput};
+use crate::request_outputs::{
+    InvitationItem, ListInvitationsOutput, ListMembersOutput, ListOrganizationsOutput, MemberItem,
+    OrganizationItem,
+};
+
+const INVITATION_TTL_DAYS: i64 = 7;
+
+fn internal_error(message: &'static str) -> poem::Error {
+    poem::Error::from_string(message, poem::http::StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn parse_role(value: &str) -> Result<Role, poem::Error> {
+    Role::parse(value).ok_or_else(|| {
+        poem::Error::from_string(
+            "Invalid role, expected one of owner, admin, editor, viewer",
+            poem::http::StatusCode::BAD_REQUEST,
+        )
+    })
+}
+
+fn to_invitation_item(
+    invitation: OrganizationInvitation,
+    organization_name: Option<String>,
+    invitee_username: Option<String>,
+) -> InvitationItem {
+    InvitationItem {
+        id: invitation.id,
+        organization_id: invitation.organization_id,
+        organization_name,
+        invitee_user_id: invitation.invitee_user_id,
+        invitee_username,
+        role: invitation.role,
+        status: invitation.status,
+        created_at: invitation.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        expires_at: invitation.expires_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    }
+}
+
+/// Refuse changes that would leave the organization without an owner
+fn ensure_not_last_owner(store: &mut Store, org_id: &str) -> Result<(), poem::Error> {
+    let owners = store.count_owners(org_id.to_string()).map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to count owners");
+        internal_error("Failed to update membership")
+    })?;
+    if owners <= 1 {
+        return Err(poem::Error::from_string(
+            "An organization must keep at least one owner",
+            poem::http::StatusCode::CONFLICT,
+        ));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/organizations",
+    tag = "organizations",
+    summary = "Create an organization",
+    request_body = crate::request_inputs::CreateOrganizationInput,
+    responses(
+        (status = 201, description = "Organization created, with the caller as owner", body = crate::request_outputs::OrganizationItem, headers(("Location" = String, description = "URL of the new organization"))),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn create_organization(
+    Json(data): Json<CreateOrganizationInput>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<OrganizationItem>, poem::Error> {
+    let name = data.name.trim();
+    if name.is_empty() {
+        return Err(poem::Error::from_string(
+            "Name cannot be empty",
+            poem::http::StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    let mut locked = s.lock().unwrap();
+    let org = locked.create_organization(name.to_string(), user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %user_id, error = ?e, "failed to create organization");
+        internal_error("Failed to create organization")
+    })?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "organization.create",
+        target_type: "organization",
+        target_id: Some(org.id.clone()),
+        organization_id: Some(org.id.clone()),
+        after: Some(serde_json::json!({ "name": org.name })),
+        ..Default::default()
+    });
+
+    Ok(Json(OrganizationItem {
+        id: org.id,
+        name: org.name,
+        personal: org.personal,
+        role: Role::Owner.as_str().to_string(),
+        created_at: org.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/organizations",
+    tag = "organizations",
+    summary = "List the caller's organizations",
+    responses(
+        (status = 200, description = "Organizations and the caller's role in each", body = crate::request_outputs::ListOrganizationsOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn list_organizations(
+    AuthUser(user_id): AuthUser,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<ListOrganizationsOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let orgs = locked.list_user_organizations(user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %user_id, error = ?e, "failed to list organizations");
+        internal_error("Failed to list organizations")
+    })?;
+
+    Ok(Json(ListOrganizationsOutput {
+        items: orgs.into_iter().map(|(org, role)| OrganizationItem {
+            id: org.id,
+            name: org.name,
+            personal: org.personal,
+            role,
+            created_at: org.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        }).collect(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/organizations/{org_id}/members",
+    tag = "organizations",
+    summary = "List an organization's members",
+    params(("org_id" = String, Path, description = "Organization id")),
+    responses(
+        (status = 200, description = "Members", body = crate::request_outputs::ListMembersOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn list_members(
+    Path(org_id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<ListMembersOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &org_id, Permission::ViewOrganization)?;
+    let members = locked.list_members(org_id.clone()).map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to list members");
+        internal_error("Failed to list members")
+    })?;
+
+    Ok(Json(ListMembersOutput {
+        items: members.into_iter().map(|(member, username)| MemberItem {
+            user_id: member.user_id,
+            username,
+            role: member.role,
+            joined_at: member.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        }).collect(),
+    }))
+}
+
+/// Change a member's role. Admins manage editors and viewers; only owners can grant
+/// or take away ownership.
+#[utoipa::path(
+    put,
+    path = "/v1/organizations/{org_id}/members/{user_id}",
+    tag = "organizations",
+    summary = "Change a member's role",
+    params(("org_id" = String, Path, description = "Organization id"), ("user_id" = String, Path, description = "Member's user id")),
+    request_body = crate::request_inputs::UpdateMemberInput,
+    responses(
+        (status = 200, description = "Member updated", body = crate::request_outputs::MemberItem),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn update_member(
+    Path((org_id, member_id)): Path<(String, String)>,
+    Json(data): Json<UpdateMemberInput>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<MemberItem>, poem::Error> {
+    let new_role = parse_role(&data.role)?;
+
+    let mut locked = s.lock().unwrap();
+    let caller_role = authorize_organization(&mut locked, &user_id, &org_id, Permission::ManageMembers)?;
+    let current_role = locked.organization_role(org_id.clone(), member_id.clone())
+    .map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to fetch membership");
+        internal_error("Failed to update membership")
+    })?
+    .and_then(|role| Role::parse(&role))
+    .ok_or_else(|| poem::Error::from_string("Member not found", poem::http::StatusCode::NOT_FOUND))?;
+
+    if (new_role == Role::Owner || current_role == Role::Owner) && caller_role != Role::Owner {
+        return Err(poem::Error::from_string(
+            "Only owners can grant or revoke ownership",
+            poem::http::StatusCode::FORBIDDEN,
+        ));
+    }
+    if current_role == Role::Owner && new_role != Role::Owner {
+        ensure_not_last_owner(&mut locked, &org_id)?;
+    }
+
+    locked.set_member_role(org_id.clone(), member_id.clone(), new_role.as_str().to_string())
+    .map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to update member role");
+        match e {
+            DieselError::NotFound => poem::Error::from_string(
+                "Member not found",
+                poem::http::StatusCode::NOT_FOUND,
+            ),
+            _ => internal_error("Failed to update membership"),
+        }
+    })?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "member.update",
+        target_type: "user",
+        target_id: Some(member_id.clone()),
+        organization_id: Some(org_id.clone()),
+        before: Some(serde_json::json!({ "role": current_role.as_str() })),
+        after: Some(serde_json::json!({ "role": new_role.as_str() })),
+    });
+
+    let (member, username) = locked.list_members(org_id.clone())
+    .map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to list members");
+        internal_error("Failed to update membership")
+    })?
+    .into_iter()
+    .find(|(member, _)| member.user_id == member_id)
+    .ok_or_else(|| poem::Error::from_string("Member not found", poem::http::StatusCode::NOT_FOUND))?;
+
+    Ok(Json(MemberItem {
+        user_id: member.user_id,
+        username,
+        role: member.role,
+        joined_at: member.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    }))
+}
+
+/// Remove a member, or leave the organization when `member_id` is the caller
+#[utoipa::path(
+    delete,
+    path = "/v1/organizations/{org_id}/members/{user_id}",
+    tag = "organizations",
+    summary = "Remove a member, or leave the organization",
+    params(("org_id" = String, Path, description = "Organization id"), ("user_id" = String, Path, description = "Member's user id")),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn remove_member(
+    Path((org_id, member_id)): Path<(String, String)>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<serde_json::Value>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let permission = if member_id == user_id { Permission::ViewOrganization } else { Permission::ManageMembers };
+    let caller_role = authorize_organization(&mut locked, &user_id, &org_id, permission)?;
+    let current_role = locked.organization_role(org_id.clone(), member_id.clone())
+    .map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to fetch membership");
+        internal_error("Failed to remove member")
+    })?
+    .and_then(|role| Role::parse(&role))
+    .ok_or_else(|| poem::Error::from_string("Member not found", poem::http::StatusCode::NOT_FOUND))?;
+
+    if current_role == Role::Owner {
+        if caller_role != Role::Owner {
+            return Err(poem::Error::from_string(
+                "Only owners can remove an owner",
+                poem::http::StatusCode::FORBIDDEN,
+            ));
+        }
+        ensure_not_last_owner(&mut locked, &org_id)?;
+    }
+
+    locked.remove_member(org_id.clone(), member_id.clone()).map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to remove member");
+        match e {
+            DieselError::NotFound => poem::Error::from_string(
+                "Member not found",
+                poem::http::StatusCode::NOT_FOUND,
+            ),
+            _ => internal_error("Failed to remove member"),
+        }
+    })?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: if member_id == user_id { "member.leave" } else { "member.remove" },
+        target_type: "user",
+        target_id: Some(member_id.clone()),
+        organization_id: Some(org_id.clone()),
+        before: Some(serde_json::json!({ "role": current_role.as_str() })),
+        ..Default::default()
+    });
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Member {} removed successfully", member_id)
+    })))
+}
+
+/// Invite an existing user by username; the invitation expires after a week
+#[utoipa::path(
+    post,
+    path = "/v1/organizations/{org_id}/invitations",
+    tag = "organizations",
+    summary = "Invite a user to an organization",
+    params(("org_id" = String, Path, description = "Organization id")),
+    request_body = crate::request_inputs::CreateInvitationInput,
+    responses(
+        (status = 201, description = "Invitation created", body = crate::request_outputs::InvitationItem, headers(("Location" = String, description = "URL of the new invitation"))),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn create_invitation(
+    Path(org_id): Path<String>,
+    Json(data): Json<CreateInvitationInput>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<InvitationItem>, poem::Error> {
+    let role = parse_role(&data.role)?;
+
+    let mut locked = s.lock().unwrap();
+    let caller_role = authorize_organization(&mut locked, &user_id, &org_id, Permission::ManageMembers)?;
+    if role == Role::Owner && caller_role != Role::Owner {
+        return Err(poem::Error::from_string(
+            "Only owners can invite new owners",
+            poem::http::StatusCode::FORBIDDEN,
+        ));
+    }
+
+    let invitee_id = locked.find_user_id_by_username(data.username.clone()).map_err(|e| match e {
+        DieselError::NotFound => poem::Error::from_string(
+            "User not found",
+            poem::http::StatusCode::NOT_FOUND,
+        ),
+        _ => {
+            tracing::error!(organization_id = %org_id, error = ?e, "failed to look up invitee");
+            internal_error("Failed to create invitation")
+        }
+    })?;
+    let existing = locked.organization_role(org_id.clone(), invitee_id.clone()).map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to fetch membership");
+        internal_error("Failed to create invitation")
+    })?;
+    if existing.is_some() {
+        return Err(poem::Error::from_string(
+            "User is already a member of this organization",
+            poem::http::StatusCode::CONFLICT,
+        ));
+    }
+
+    let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::days(INVITATION_TTL_DAYS);
+    let invitation = locked.create_invitation(
+        org_id.clone(),
+        invitee_id,
+        user_id.clone(),
+        role.as_str().to_string(),
+        expires_at,
+    ).map_err(|e| match e {
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            AppError::conflict("An invitation is already pending").into()
+        }
+        _ => {
+            tracing::error!(organization_id = %org_id, error = ?e, "failed to create invitation");
+            internal_error("Failed to create invitation")
+        }
+    })?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "invitation.create",
+        target_type: "invitation",
+        target_id: Some(invitation.id.clone()),
+        organization_id: Some(org_id.clone()),
+        after: Some(serde_json::json!({
+            "invitee_user_id": invitation.invitee_user_id,
+            "role": invitation.role,
+        })),
+        ..Default::default()
+    });
+
+    Ok(Json(to_invitation_item(invitation, None, Some(data.username))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/organizations/{org_id}/invitations",
+    tag = "organizations",
+    summary = "List an organization's pending invitations",
+    params(("org_id" = String, Path, description = "Organization id")),
+    responses(
+        (status = 200, description = "Invitations", body = crate::request_outputs::ListInvitationsOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn list_organization_invitations(
+    Path(org_id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<ListInvitationsOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &org_id, Permission::ManageMembers)?;
+    let invitations = locked.list_organization_invitations(org_id.clone()).map_err(|e| {
+        tracing::error!(organization_id = %org_id, error = ?e, "failed to list invitations");
+        internal_error("Failed to list invitations")
+    })?;
+
+    Ok(Json(ListInvitationsOutput {
+        items: invitations.into_iter()
+            .map(|(invitation, username)| to_invitation_item(invitation, None, Some(username)))
+            .collect(),
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/organizations/{org_id}/invitations/{invitation_id}",
+    tag = "organizations",
+    summary = "Revoke a pending invitation",
+    params(("org_id" = String, Path, description = "Organization id"), ("invitation_id" = String, Path, description = "Invitation id")),
+    responses(
+        (status = 204, description = "Invitation revoked"),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn revoke_invitation(
+    Path((org_id, invitation_id)): Path<(String, String)>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<serde_json::Value>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    authorize_organization(&mut locked, &user_id, &org_id, Permission::ManageMembers)?;
+
+    let not_found = || poem::Error::from_string("Invitation not found", poem::http::StatusCode::NOT_FOUND);
+    let invitation = locked.get_invitation(invitation_id.clone()).map_err(|e| match e {
+        DieselError::NotFound => not_found(),
+        _ => {
+            tracing::error!(invitation_id = %invitation_id, error = ?e, "failed to fetch invitation");
+            internal_error("Failed to revoke invitation")
+        }
+    })?;
+    if invitation.organization_id != org_id {
+        return Err(not_found());
+    }
+
+    locked.close_invitation(invitation_id.clone(), "revoked").map_err(|e| match e {
+        DieselError::NotFound => poem::Error::from_string(
+            "Invitation is no longer pending",
+            poem::http::StatusCode::CONFLICT,
+        ),
+        _ => {
+            tracing::error!(invitation_id = %invitation_id, error = ?e, "failed to revoke invitation");
+            internal_error("Failed to revoke invitation")
+        }
+    })?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "invitation.revoke",
+        target_type: "invitation",
+        target_id: Some(invitation_id.clone()),
+        organization_id: Some(org_id.clone()),
+        before: Some(serde_json::json!({ "status": invitation.status })),
+        after: Some(serde_json::json!({ "status": "revoked" })),
+    });
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Invitation {} revoked successfully", invitation_id)
+    })))
+}
+
+/// Pending invitations addressed to the caller
+#[utoipa::path(
+    get,
+    path = "/v1/invitations",
+    tag = "organizations",
+    summary = "List invitations addressed to the caller",
+    responses(
+        (status = 200, description = "Pending invitations", body = crate::request_outputs::ListInvitationsOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn list_my_invitations(
+    AuthUser(user_id): AuthUser,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<ListInvitationsOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let invitations = locked.list_user_invitations(user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %user_id, error = ?e, "failed to list invitations");
+        internal_error("Failed to list invitations")
+    })?;
+
+    Ok(Json(ListInvitationsOutput {
+        items: invitations.into_iter()
+            .map(|(invitation, org_name)| to_invitation_item(invitation, Some(org_name), None))
+            .collect(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/invitations/{invitation_id}/accept",
+    tag = "organizations",
+    summary = "Accept an invitation",
+    params(("invitation_id" = String, Path, description = "Invitation id")),
+    responses(
+        (status = 200, description = "Invitation accepted", body = crate::request_outputs::InvitationItem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn accept_invitation(
+    Path(invitation_id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<InvitationItem>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let invitation = locked.accept_invitation(invitation_id.clone(), user_id.clone()).map_err(|e| match e {
+        DieselError::NotFound => poem::Error::from_string(
+            "Invitation not found or no longer pending",
+            poem::http::StatusCode::NOT_FOUND,
+        ),
+        _ => {
+            tracing::error!(invitation_id = %invitation_id, error = ?e, "failed to accept invitation");
+            internal_error("Failed to accept invitation")
+        }
+    })?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "invitation.accept",
+        target_type: "invitation",
+        target_id: Some(invitation.id.clone()),
+        organization_id: Some(invitation.organization_id.clone()),
+        after: Some(serde_json::json!({ "role": invitation.role })),
+        ..Default::default()
+    });
+
+    Ok(Json(to_invitation_item(invitation, None, None)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/invitations/{invitation_id}/decline",
+    tag = "organizations",
+    summary = "Decline an invitation",
+    params(("invitation_id" = String, Path, description = "Invitation id")),
+    responses(
+        (status = 200, description = "Invitation declined", body = crate::request_outputs::InvitationItem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn decline_invitation(
+    Path(invitation_id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<InvitationItem>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let not_found = || poem::Error::from_string(
+        "Invitation not found or no longer pending",
+        poem::http::StatusCode::NOT_FOUND,
+    );
+    let invitation = locked.get_invitation(invitation_id.clone()).map_err(|e| match e {
+        DieselError::NotFound => not_found(),
+        _ => {
+            tracing::error!(invitation_id = %invitation_id, error = ?e, "failed to fetch invitation");
+            internal_error("Failed to decline invitation")
+        }
+    })?;
+    if invitation.invitee_user_id != user_id {
+        return Err(not_found());
+    }
+
+    let invitation = locked.close_invitation(invitation_id.clone(), "declined").map_err(|e| match e {
+        DieselError::NotFound => not_found(),
+        _ => {
+            tracing::error!(invitation_id = %invitation_id, error = ?e, "failed to decline invitation");
+            internal_error("Failed to decline invitation")
+        }
+    })?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "invitation.decline",
+        target_type: "invitation",
+        target_id: Some(invitation.id.clone()),
+        organization_id: Some(invitation.organization_id.clone()),
+        ..Default::default()
+    });
+
+    Ok(Json(to_invitation_item(invitation, None, None)))
+}
@@ -0,0 +1,328 @@
+use std::sync::{Arc, Mutex};
+
+use diesel::result::Error as DieselError;
+use poem::{
+    handler,
+    Request,
+    web::{Data, Json},
+};
+use store::store::Store;
+
+use crate::audit::{self, AuditEvent};
+use crate::auth::AuthSession;
+use crate::request_inputs::{TwoFactorCodeInput, TwoFactorSignInInput};
+use crate::request_outputs::{
+    RecoveryCodesOutput, SignInOutput, TotpEnrollmentOutput, TwoFactorStatusOutput,
+};
+use crate::session;
+use crate::two_factor;
+
+// Managing 2FA takes `AuthSession`, so an API key can't switch it off
+
+fn internal_error(message: &'static str) -> poem::Error {
+    poem::Error::from_string(message, poem::http::StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn invalid_code() -> poem::Error {
+    poem::Error::from_string("Invalid code", poem::http::StatusCode::UNAUTHORIZED)
+}
+
+fn not_enabled() -> poem::Error {
+    poem::Error::from_string(
+        "Two-factor authentication is not enabled",
+        poem::http::StatusCode::CONFLICT,
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/2fa",
+    tag = "two-factor",
+    summary = "Whether 2FA is enabled",
+    responses(
+        (status = 200, description = "2FA status", body = crate::request_outputs::TwoFactorStatusOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn two_factor_status(
+    auth: AuthSession,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<TwoFactorStatusOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let enabled = two_factor::enabled_totp(&mut locked, &auth.user_id)?.is_some();
+    let remaining = if enabled {
+        locked.count_unused_recovery_codes(auth.user_id.clone()).map_err(|e| {
+            tracing::error!(user_id = %auth.user_id, error = ?e, "failed to count recovery codes");
+            internal_error("Failed to fetch two-factor settings")
+        })?
+    } else {
+        0
+    };
+
+    Ok(Json(TwoFactorStatusOutput {
+        enabled,
+        recovery_codes_remaining: remaining,
+    }))
+}
+
+/// Start enrollment with a new secret. Nothing changes for sign-in until the secret
+/// is confirmed with `/2fa/verify`.
+#[utoipa::path(
+    post,
+    path = "/v1/2fa/enroll",
+    tag = "two-factor",
+    summary = "Start TOTP enrollment",
+    responses(
+        (status = 200, description = "Secret to add to an authenticator app", body = crate::request_outputs::TotpEnrollmentOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn enroll_totp(
+    auth: AuthSession,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<TotpEnrollmentOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    if two_factor::enabled_totp(&mut locked, &auth.user_id)?.is_some() {
+        return Err(poem::Error::from_string(
+            "Two-factor authentication is already enabled",
+            poem::http::StatusCode::CONFLICT,
+        ));
+    }
+    let username = locked.get_username(auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to fetch user");
+        internal_error("Failed to start enrollment")
+    })?;
+
+    let secret = two_factor::generate_secret();
+    let totp = two_factor::totp_for(&secret, &username)?;
+    locked.start_totp_enrollment(auth.user_id.clone(), secret.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to store TOTP secret");
+        internal_error("Failed to start enrollment")
+    })?;
+
+    Ok(Json(TotpEnrollmentOutput {
+        secret,
+        otpauth_uri: totp.get_url(),
+    }))
+}
+
+/// Confirm enrollment with a code from the authenticator; turns 2FA on and returns
+/// the recovery codes
+#[utoipa::path(
+    post,
+    path = "/v1/2fa/verify",
+    tag = "two-factor",
+    summary = "Confirm TOTP enrollment",
+    request_body = crate::request_inputs::TwoFactorCodeInput,
+    responses(
+        (status = 200, description = "2FA enabled; recovery codes are shown once", body = crate::request_outputs::RecoveryCodesOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn verify_totp(
+    Json(data): Json<TwoFactorCodeInput>,
+    auth: AuthSession,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<RecoveryCodesOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let pending = locked.get_totp(auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to fetch two-factor settings");
+        internal_error("Failed to verify code")
+    })?
+    .filter(|t| t.enabled_at.is_none())
+    .ok_or_else(|| poem::Error::from_string(
+        "No two-factor enrollment in progress",
+        poem::http::StatusCode::CONFLICT,
+    ))?;
+
+    let totp = two_factor::totp_for(&pending.secret, "")?;
+    let step = two_factor::matching_step(&totp, data.code.trim()).ok_or_else(invalid_code)?;
+
+    let codes = two_factor::generate_recovery_codes();
+    let normalized: Vec<String> = codes.iter().map(|c| two_factor::normalize_recovery_code(c)).collect();
+    locked.enable_totp(auth.user_id.clone(), step, &normalized).map_err(|e| match e {
+        DieselError::NotFound => poem::Error::from_string(
+            "No two-factor enrollment in progress",
+            poem::http::StatusCode::CONFLICT,
+        ),
+        _ => {
+            tracing::error!(user_id = %auth.user_id, error = ?e, "failed to enable 2FA");
+            internal_error("Failed to enable two-factor authentication")
+        }
+    })?;
+    tracing::info!(user_id = %auth.user_id, "two-factor authentication enabled");
+    audit::record(&mut locked, req, Some(&auth.user_id), AuditEvent {
+        action: "user.2fa_enable",
+        target_type: "user",
+        target_id: Some(auth.user_id.clone()),
+        before: Some(serde_json::json!({ "two_factor_enabled": false })),
+        after: Some(serde_json::json!({ "two_factor_enabled": true })),
+        ..Default::default()
+    });
+
+    Ok(Json(RecoveryCodesOutput { recovery_codes: codes }))
+}
+
+/// Turn 2FA off; needs a current code (or recovery code) as well as a session
+#[utoipa::path(
+    post,
+    path = "/v1/2fa/disable",
+    tag = "two-factor",
+    summary = "Turn 2FA off",
+    request_body = crate::request_inputs::TwoFactorCodeInput,
+    responses(
+        (status = 200, description = "2FA disabled", body = crate::openapi::SuccessMessage),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn disable_totp(
+    Json(data): Json<TwoFactorCodeInput>,
+    auth: AuthSession,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<serde_json::Value>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let totp = two_factor::enabled_totp(&mut locked, &auth.user_id)?.ok_or_else(not_enabled)?;
+    if !two_factor::verify_second_factor(&mut locked, &totp, &data.code)? {
+        return Err(invalid_code());
+    }
+
+    locked.disable_totp(auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to disable 2FA");
+        internal_error("Failed to disable two-factor authentication")
+    })?;
+    tracing::info!(user_id = %auth.user_id, "two-factor authentication disabled");
+    audit::record(&mut locked, req, Some(&auth.user_id), AuditEvent {
+        action: "user.2fa_disable",
+        target_type: "user",
+        target_id: Some(auth.user_id.clone()),
+        before: Some(serde_json::json!({ "two_factor_enabled": true })),
+        after: Some(serde_json::json!({ "two_factor_enabled": false })),
+        ..Default::default()
+    });
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Two-factor authentication disabled",
+    })))
+}
+
+/// Replace all recovery codes, e.g. after some have been used up
+#[utoipa::path(
+    post,
+    path = "/v1/2fa/recovery-codes",
+    tag = "two-factor",
+    summary = "Replace the recovery codes",
+    request_body = crate::request_inputs::TwoFactorCodeInput,
+    responses(
+        (status = 200, description = "New recovery codes; the old ones stop working", body = crate::request_outputs::RecoveryCodesOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn regenerate_recovery_codes(
+    Json(data): Json<TwoFactorCodeInput>,
+    auth: AuthSession,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<RecoveryCodesOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let totp = two_factor::enabled_totp(&mut locked, &auth.user_id)?.ok_or_else(not_enabled)?;
+    if !two_factor::verify_second_factor(&mut locked, &totp, &data.code)? {
+        return Err(invalid_code());
+    }
+
+    let codes = two_factor::generate_recovery_codes();
+    let normalized: Vec<String> = codes.iter().map(|c| two_factor::normalize_recovery_code(c)).collect();
+    locked.replace_recovery_codes(auth.user_id.clone(), &normalized).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to replace recovery codes");
+        internal_error("Failed to generate recovery codes")
+    })?;
+    audit::record(&mut locked, req, Some(&auth.user_id), AuditEvent {
+        action: "user.recovery_codes_regenerate",
+        target_type: "user",
+        target_id: Some(auth.user_id.clone()),
+        ..Default::default()
+    });
+
+    Ok(Json(RecoveryCodesOutput { recovery_codes: codes }))
+}
+
+/// Second step of sign-in: trade the challenge from `/sign-in` and a code for tokens
+#[utoipa::path(
+    post,
+    path = "/v1/sign-in/2fa",
+    tag = "auth",
+    summary = "Finish signing in with a TOTP or recovery code",
+    request_body = crate::request_inputs::TwoFactorSignInInput,
+    responses(
+        (status = 200, description = "Tokens", body = crate::request_outputs::SignInOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 429, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    )
+)]
+#[handler]
+pub fn sign_in_two_factor(
+    Json(data): Json<TwoFactorSignInInput>,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<SignInOutput>, poem::Error> {
+    let mut locked = s.lock().unwrap();
+    let challenge = locked.attempt_two_factor_challenge(&data.challenge_token).map_err(|e| {
+        tracing::error!(error = ?e, "failed to fetch two-factor challenge");
+        internal_error("Failed to verify code")
+    })?
+    .ok_or_else(|| poem::Error::from_string(
+        "Invalid or expired challenge",
+        poem::http::StatusCode::UNAUTHORIZED,
+    ))?;
+
+    let totp = two_factor::enabled_totp(&mut locked, &challenge.user_id)?.ok_or_else(invalid_code)?;
+    if !two_factor::verify_second_factor(&mut locked, &totp, &data.code)? {
+        tracing::warn!(user_id = %challenge.user_id, attempts = challenge.attempts, "invalid two-factor code");
+        let organization_id = locked.personal_organization(challenge.user_id.clone()).ok().map(|org| org.id);
+        audit::record(&mut locked, req, None, AuditEvent {
+            action: "user.sign_in_failed",
+            target_type: "user",
+            target_id: Some(challenge.user_id.clone()),
+            organization_id,
+            after: Some(serde_json::json!({ "factor": "totp" })),
+            ..Default::default()
+        });
+        return Err(invalid_code());
+    }
+
+    if let Err(e) = locked.delete_two_factor_challenge(challenge.id.clone()) {
+        tracing::warn!(user_id = %challenge.user_id, error = ?e, "failed to delete two-factor challenge");
+    }
+    let response = session::start_session(&mut locked, &challenge.user_id)?;
+    audit::record(&mut locked, req, Some(&challenge.user_id), AuditEvent {
+        action: "user.sign_in",
+        target_type: "user",
+        target_id: Some(challenge.user_id.clone()),
+        after: Some(serde_json::json!({ "factor": "totp" })),
+        ..Default::default()
+    });
+    Ok(Json(response))
+}
@@ -2,25 +2,44 @@ use std::sync::{Arc, Mutex};
 
 use poem::web::{Data, Json};
 use poem::{
-    handler
+    handler, IntoResponse, Request, Response
 };
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
 
-use crate::request_inputs::{CreateUserInput};
+use crate::request_inputs::{CreateUserInput, RefreshTokenInput};
 use crate::request_outputs::{CreateUserOutput};
+use store::models::session::RefreshOutcome;
 use store::store::Store;
 
-use crate::request_outputs::SignInOutput;
-use crate::jwt;
+use crate::audit::{self, AuditEvent};
+use crate::auth::AuthSession;
+use crate::request_outputs::{SignInOutput, SignInResponse, TwoFactorChallengeOutput};
 use crate::password;
+use crate::rate_limit::TokensIssued;
+use crate::session;
+use crate::two_factor;
 
+#[utoipa::path(
+    post,
+    path = "/v1/sign-up",
+    tag = "auth",
+    summary = "Create an account",
+    request_body = crate::request_inputs::CreateUserInput,
+    responses(
+        (status = 200, description = "Account created", body = crate::request_outputs::CreateUserOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 429, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    )
+)]
 #[handler]
-pub fn sign_up(Json(data): Json<CreateUserInput>, Data(s):Data<&Arc<Mutex<Store>>>) -> Result<Json<CreateUserOutput>, poem::Error> {
+pub fn sign_up(Json(data): Json<CreateUserInput>, req: &Request, Data(s):Data<&Arc<Mutex<Store>>>) -> Result<Json<CreateUserOutput>, poem::Error> {
     validate_user_input(&data.username, &data.password)?;
     let hashed_password = match password::hash_password(&data.password) {
         Ok(hash) => hash,
         Err(e) => {
-            eprintln!("Password hashing error: {:?}", e);
+            tracing::error!(error = ?e, "password hashing failed");
             return Err(poem::Error::from_string(
                 "Failed to process password",
                 poem::http::StatusCode::INTERNAL_SERVER_ERROR,
@@ -30,13 +49,20 @@ pub fn sign_up(Json(data): Json<CreateUserInput>, Data(s):Data<&Arc<Mutex<Store>
     let mut locked_s= s.lock().unwrap();
     match locked_s.sign_up(data.username.clone(), hashed_password) {
         Ok(id) => {
+            audit::record(&mut locked_s, req, Some(&id), AuditEvent {
+                action: "user.sign_up",
+                target_type: "user",
+                target_id: Some(id.clone()),
+                after: Some(serde_json::json!({ "username": data.username })),
+                ..Default::default()
+            });
             let response = CreateUserOutput{
                 id: id.to_string()
             };
             Ok(Json(response))
         }
         Err(e) => {
-            eprintln!("Sign up error for user '{}': {:?}", data.username, e);
+            tracing::warn!(username = %data.username, error = ?e, "sign up failed");
             match e {
                 DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
                     Err(poem::Error::from_string(
@@ -55,30 +81,69 @@ pub fn sign_up(Json(data): Json<CreateUserInput>, Data(s):Data<&Arc<Mutex<Store>
     }
 }
 
+/// With 2FA on, a correct password only earns a challenge token for `/sign-in/2fa`
+#[utoipa::path(
+    post,
+    path = "/v1/sign-in",
+    tag = "auth",
+    summary = "Sign in with a username and password",
+    request_body = crate::request_inputs::CreateUserInput,
+    responses(
+        (status = 200, description = "Tokens, or a challenge when the account has 2FA enabled", body = crate::request_outputs::SignInResponse),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 429, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    )
+)]
 #[handler]
-pub fn sign_in(Json(data): Json<CreateUserInput>, Data(s):Data<&Arc<Mutex<Store>>>) -> Result<Json<SignInOutput>, poem::Error> {
+pub fn sign_in(Json(data): Json<CreateUserInput>, req: &Request, Data(s):Data<&Arc<Mutex<Store>>>) -> Result<Response, poem::Error> {
     validate_user_input(&data.username, &data.password)?;
     let mut locked_s= s.lock().unwrap();
     match locked_s.sign_in(data.username.clone(), data.password.clone()) {
         Ok(user_id) => {
-            match jwt::generate_jwt(&user_id) {
-                Ok(token) => {
-                    let response = SignInOutput {
-                        jwt: token
-                    };
-                    Ok(Json(response))
-                }
-                Err(e) => {
-                    eprintln!("JWT generation error: {:?}", e);
-                    Err(poem::Error::from_string(
-                        "Failed to generate token",
+            if two_factor::enabled_totp(&mut locked_s, &user_id)?.is_some() {
+                let token = two_factor::generate_challenge_token();
+                let expires_at = chrono::Utc::now().naive_utc()
+                    + chrono::Duration::seconds(two_factor::CHALLENGE_TTL_SECONDS);
+                locked_s.create_two_factor_challenge(user_id.clone(), &token, expires_at).map_err(|e| {
+                    tracing::error!(user_id = %user_id, error = ?e, "failed to create two-factor challenge");
+                    poem::Error::from_string(
+                        "Failed to sign in",
                         poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    ))
-                }
+                    )
+                })?;
+                return Ok(Json(SignInResponse::Challenge(TwoFactorChallengeOutput {
+                    two_factor_required: true,
+                    challenge_token: token,
+                    expires_in: two_factor::CHALLENGE_TTL_SECONDS as u64,
+                })).into_response());
             }
+            let response = session::start_session(&mut locked_s, &user_id)?;
+            audit::record(&mut locked_s, req, Some(&user_id), AuditEvent {
+                action: "user.sign_in",
+                target_type: "user",
+                target_id: Some(user_id.clone()),
+                ..Default::default()
+            });
+            let mut resp = Json(SignInResponse::Tokens(response)).into_response();
+            resp.extensions_mut().insert(TokensIssued);
+            Ok(resp)
         }
         Err(e) => {
-            eprintln!("Sign in error for user '{}': {:?}", data.username, e);
+            tracing::warn!(username = %data.username, error = ?e, "sign in failed");
+            // Only attempts against a real account are worth an entry; it lands in that user's log
+            if let Ok(target) = locked_s.find_user_id_by_username(data.username.clone())
+                && let Ok(org) = locked_s.personal_organization(target.clone())
+            {
+                audit::record(&mut locked_s, req, None, AuditEvent {
+                    action: "user.sign_in_failed",
+                    target_type: "user",
+                    target_id: Some(target),
+                    organization_id: Some(org.id),
+                    ..Default::default()
+                });
+            }
             Err(poem::Error::from_string(
                 "Invalid username or password",
                 poem::http::StatusCode::UNAUTHORIZED,
@@ -87,6 +152,123 @@ pub fn sign_in(Json(data): Json<CreateUserInput>, Data(s):Data<&Arc<Mutex<Store>
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/v1/tokens/refresh",
+    tag = "auth",
+    summary = "Exchange a refresh token for new tokens",
+    request_body = crate::request_inputs::RefreshTokenInput,
+    responses(
+        (status = 200, description = "A new token pair; the old refresh token is spent", body = crate::request_outputs::SignInOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    )
+)]
+#[handler]
+pub fn refresh_token(Json(data): Json<RefreshTokenInput>, Data(s):Data<&Arc<Mutex<Store>>>) -> Result<Json<SignInOutput>, poem::Error> {
+    let replacement = session::generate_refresh_token();
+    let mut locked_s = s.lock().unwrap();
+    let outcome = locked_s.rotate_refresh_token(&data.refresh_token, &replacement, session::refresh_token_expiry())
+    .map_err(|e| {
+        tracing::error!(error = ?e, "failed to rotate refresh token");
+        poem::Error::from_string(
+            "Failed to refresh token",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+
+    match outcome {
+        RefreshOutcome::Rotated { user_id, session_id } => {
+            let response = session::token_pair(&user_id, &session_id, replacement)?;
+            Ok(Json(response))
+        }
+        RefreshOutcome::Reused => {
+            tracing::warn!("refresh token reuse detected, session revoked");
+            Err(poem::Error::from_string(
+                "Invalid refresh token",
+                poem::http::StatusCode::UNAUTHORIZED,
+            ))
+        }
+        RefreshOutcome::Invalid => Err(poem::Error::from_string(
+            "Invalid refresh token",
+            poem::http::StatusCode::UNAUTHORIZED,
+        )),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/sign-out",
+    tag = "auth",
+    summary = "Revoke the current session",
+    responses(
+        (status = 200, description = "Session revoked", body = crate::openapi::SuccessMessage),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn sign_out(auth: AuthSession, req: &Request, Data(s):Data<&Arc<Mutex<Store>>>) -> Result<Json<serde_json::Value>, poem::Error> {
+    let mut locked_s = s.lock().unwrap();
+    locked_s.revoke_session(auth.session_id.clone(), auth.user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %auth.user_id, error = ?e, "failed to revoke session");
+        poem::Error::from_string(
+            "Failed to sign out",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+    audit::record(&mut locked_s, req, Some(&auth.user_id), AuditEvent {
+        action: "session.revoke",
+        target_type: "session",
+        target_id: Some(auth.session_id.clone()),
+        ..Default::default()
+    });
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Signed out",
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/sign-out-all",
+    tag = "auth",
+    summary = "Revoke every session of the caller",
+    description = "Needs a signed-in session; API keys are refused.",
+    responses(
+        (status = 200, description = "Sessions revoked", body = crate::openapi::SuccessMessage),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+// Takes `AuthSession`, so an API key can't sign its owner out everywhere
+#[handler]
+pub fn sign_out_all(auth: AuthSession, req: &Request, Data(s):Data<&Arc<Mutex<Store>>>) -> Result<Json<serde_json::Value>, poem::Error> {
+    let user_id = auth.user_id;
+    let mut locked_s = s.lock().unwrap();
+    let revoked = locked_s.revoke_all_sessions(user_id.clone()).map_err(|e| {
+        tracing::error!(user_id = %user_id, error = ?e, "failed to revoke sessions");
+        poem::Error::from_string(
+            "Failed to sign out",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+    audit::record(&mut locked_s, req, Some(&user_id), AuditEvent {
+        action: "session.revoke_all",
+        target_type: "user",
+        target_id: Some(user_id.clone()),
+        after: Some(serde_json::json!({ "revoked_sessions": revoked })),
+        ..Default::default()
+    });
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Signed out of {} sessions", revoked),
+    })))
+}
+
 fn validate_user_input(username: &str, password: &str) -> Result<(), poem::Error> {
     if username.trim().is_empty() || password.trim().is_empty() {
         return Err(poem::Error::from_string(
@@ -101,6 +283,10 @@ fn validate_user_input(username: &str, password: &str) -> Result<(), poem::Error
         ));
     }
 
+    validate_password(password)
+}
+
+pub(crate) fn validate_password(password: &str) -> Result<(), poem::Error> {
     if password.len() < 8 {
         return Err(poem::Error::from_string(
             "Password must be at least 8 characters",
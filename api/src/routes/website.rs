@@ -1,231 +1,412 @@
 use std::sync::{Arc, Mutex};
-use diesel::{ result::Error as DieselError};
 use poem::{
     handler,
+    Request,
+    Body,
     web::{Data, Json, Path, Query},
 };
 
-use crate::{monitor::check_website,
+use crate::{monitor::{self, assertions_of, check_website},
     request_inputs::{
+        default_check_interval,
         CreateWebsiteInput, 
+        HistoryQuery,
+        ListWebsitesQuery,
+        OrderParam,
+        PauseWebsiteInput,
+        SortParam,
+        StatusParam,
         UpdateWebsiteInput
     }};
 use crate::request_outputs::{
+    CheckNowOutput,
     CreateWebsiteOutput,
     GetWebsiteOutput,
     ListWebsiteOutput,
     WebsiteItem,
     WebsiteHistoryOutput,
     CheckHistoryItem,
-    WebsiteStatusOutput
+    WebsiteStatusOutput,
+    IncidentItem
 };
 use store::store::Store;
+use store::models::website::{
+    Website, WebsiteChanges, WebsiteDetails, WebsiteListQuery, WebsiteSort, WebsiteState, WebsiteSummary,
+};
+use store::models::check_history::{HistoryCursor, HistoryFilter};
+use crate::audit::{self, AuditEvent};
 use crate::auth::AuthUser;
+use crate::authz::{Permission, authorize_organization, authorize_website};
+use crate::cursor::{decode_cursor, encode_cursor, parse_timestamp};
+use crate::error::{AppError, Entity};
+
+pub(crate) fn validate_url(url: &str) -> Result<(), AppError> {
+    if url.trim().is_empty() {
+        return Err(AppError::bad_request("URL cannot be empty"));
+    }
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(AppError::bad_request("URL must start with http:// or https://"));
+    }
+    Ok(())
+}
+
+pub(crate) const MAX_NAME_LEN: usize = 200;
+pub(crate) const MAX_DESCRIPTION_LEN: usize = 2000;
+
+/// Trim a display field, treating blank as unset
+pub(crate) fn text_field(value: Option<String>, field: &str, max_len: usize) -> Result<Option<String>, AppError> {
+    let value = value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    if value.as_ref().is_some_and(|v| v.chars().count() > max_len) {
+        return Err(AppError::bad_request(format!("'{}' is longer than {} characters", field, max_len)));
+    }
+    Ok(value)
+}
 
-#[derive(serde::Serialize)]
-pub struct CheckNowOutput {
-    pub is_up: bool,
-    pub response_time_ms: Option<i32>,
-    pub status_code: Option<i32>,
-    pub error_message: Option<String>,
+fn format_time(time: chrono::NaiveDateTime) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S").to_string()
 }
 
-#[derive(serde::Deserialize)]
-pub struct HistoryQuery {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>
+fn website_item(summary: WebsiteSummary) -> WebsiteItem {
+    let WebsiteSummary { website, open_incident, uptime_24h } = summary;
+    let status = match website.state() {
+        WebsiteState::Pending => "pending",
+        WebsiteState::Up => "up",
+        WebsiteState::Down => "down",
+        WebsiteState::Paused => "paused",
+    };
+    WebsiteItem {
+        status: status.to_string(),
+        assertions: assertions_of(&website),
+        paused_at: website.paused_at.map(format_time),
+        resume_at: website.resume_at.map(format_time),
+        id: website.id,
+        url: website.url,
+        organization_id: website.organization_id,
+        name: website.name,
+        description: website.description,
+        tags: crate::tags::from_json(&website.tags),
+        external_key: website.external_key,
+        check_interval_seconds: website.check_interval_seconds,
+        time_added: format_time(website.time_added),
+        last_checked: website.last_checked.map(format_time),
+        last_down_time: website.last_down_time.map(format_time),
+        response_time_ms: website.response_time_ms,
+        // Two decimals are plenty for a dashboard
+        uptime_24h: uptime_24h.map(|u| (u * 100.0).round() / 100.0),
+        open_incident: open_incident.map(|i| IncidentItem {
+            id: i.id,
+            started_at: format_time(i.started_at),
+            resolved_at: None,
+            duration_seconds: None,
+            cause: i.cause,
+        }),
+    }
 }
+
+/// The user-editable fields of a website, as recorded in the audit log
+pub(crate) fn website_snapshot(website: &Website) -> serde_json::Value {
+    serde_json::json!({
+        "url": website.url,
+        "organization_id": website.organization_id,
+        "name": website.name,
+        "description": website.description,
+        "tags": website.tags,
+        "external_key": website.external_key,
+        "check_interval_seconds": website.check_interval_seconds,
+        "assertions": website.assertions,
+        "paused_at": website.paused_at.map(format_time),
+        "resume_at": website.resume_at.map(format_time),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/websites/{website_id}",
+    tag = "websites",
+    summary = "Get a website",
+    params(("website_id" = String, Path, description = "Website id")),
+    responses(
+        (status = 200, description = "The website", body = crate::request_outputs::GetWebsiteOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
 #[handler]
 pub fn get_website(Path(id): Path<String>,
 AuthUser(user_id) : AuthUser,
 Data(s):Data<&Arc<Mutex<Store>>>)
--> Result<Json<GetWebsiteOutput>, poem::Error> {
+-> Result<Json<GetWebsiteOutput>, AppError> {
     let mut locked_s = s.lock().unwrap();
-    let website = locked_s.get_website(id.clone())
-    .map_err(|e| {
-        eprintln!("Error fetching website {}: {:?}", id, e);
-        match e {
-            diesel::result::Error::NotFound => {
-                poem::Error::from_string(
-                    "website not found",
-                    poem::http::StatusCode::NOT_FOUND,
-                )
-            }
-            _ => {
-                poem::Error::from_string(
-                    "Failed to fetch website",
-                    poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-                )
-            }
-        }
-    })?;
-    if website.user_id != user_id {
-        return Err(poem::Error::from_string(
-            "You don't have permission to access this website",
-            poem::http::StatusCode::FORBIDDEN,
-        ));
-    }
+    authorize_website(&mut locked_s, &user_id, &id, Permission::ViewWebsite)?;
+    let summary = locked_s.get_website_summary(id.clone())
+    .map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to fetch website"))?;
     Ok(Json(GetWebsiteOutput {
-        url: website.url,
+        website: website_item(summary),
     }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/v1/websites",
+    tag = "websites",
+    summary = "Start monitoring a website",
+    request_body = crate::request_inputs::CreateWebsiteInput,
+    responses(
+        (status = 201, description = "Website created", body = crate::request_outputs::CreateWebsiteOutput, headers(("Location" = String, description = "URL of the new website"))),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
 #[handler]
 pub fn create_website(Json(data):Json<CreateWebsiteInput>,
 AuthUser(user_id) : AuthUser,
+req: &Request,
 Data(s):Data<&Arc<Mutex<Store>>>)
- -> Result<Json<CreateWebsiteOutput>, poem::Error> {
-    if data.url.trim().is_empty() {
-        return Err(poem::Error::from_string(
-            "URL cannot be empty",
-            poem::http::StatusCode::BAD_REQUEST
-        ));
-    }
-    if !data.url.starts_with("http://") && !data.url.starts_with("https://") {
-        return Err(poem::Error::from_string(
-            "URL must start with http:// or https://",
-            poem::http::StatusCode::BAD_REQUEST
-        ));
-    }
+ -> Result<Json<CreateWebsiteOutput>, AppError> {
+    validate_url(&data.url)?;
+    crate::tags::validate(&data.tags)?;
+    let check_interval_seconds = data.check_interval_seconds.unwrap_or_else(default_check_interval);
+    monitor::validate_interval(check_interval_seconds)?;
+    monitor::validate_assertions(&data.assertions)?;
+    let details = WebsiteDetails {
+        name: text_field(data.name, "name", MAX_NAME_LEN)?,
+        description: text_field(data.description, "description", MAX_DESCRIPTION_LEN)?,
+        tags: crate::tags::to_json(data.tags),
+        external_key: None,
+        check_interval_seconds,
+        assertions: serde_json::to_value(&data.assertions).unwrap_or_default(),
+    };
     let mut locked_s=s.lock().unwrap();
+    let organization_id = match data.organization_id {
+        Some(org_id) => {
+            authorize_organization(&mut locked_s, &user_id, &org_id, Permission::EditWebsite)?;
+            org_id
+        }
+        None => locked_s.personal_organization(user_id.clone())
+            .map_err(|e| AppError::store_for(Entity::User(&user_id), e, "Personal organization not found", "Failed to create website"))?
+            .id,
+    };
     let website = locked_s.create_website(
-        user_id,
-        data.url
-    ).map_err(|e| {
-        eprintln!("Error creating website: {:?}", e);
-        poem::Error::from_string(
-            "Failed to create website",
-            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-        )
-    })?;
+        user_id.clone(),
+        organization_id.clone(),
+        data.url,
+        details
+    ).map_err(|e| AppError::store_for(Entity::Organization(&organization_id), e, "Organization not found", "Failed to create website"))?;
+    audit::record(&mut locked_s, req, Some(&user_id), AuditEvent {
+        action: "website.create",
+        target_type: "website",
+        target_id: Some(website.id.clone()),
+        organization_id: Some(website.organization_id.clone()),
+        after: Some(website_snapshot(&website)),
+        ..Default::default()
+    });
 
     Ok(Json(CreateWebsiteOutput {
         id: website.id,
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/websites",
+    tag = "websites",
+    summary = "List websites the caller can see",
+    params(ListWebsitesQuery),
+    responses(
+        (status = 200, description = "A page of websites with their current state", body = crate::request_outputs::ListWebsiteOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
 #[handler]
 pub fn list_websites(
     AuthUser(user_id) : AuthUser,
+    Query(query): Query<ListWebsitesQuery>,
     Data(s): Data<&Arc<Mutex<Store>>>
-) -> Result<Json<ListWebsiteOutput>, poem::Error> {
+) -> Result<Json<ListWebsiteOutput>, AppError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let offset = query.offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(AppError::bad_request("'offset' cannot be negative"));
+    }
+    let list_query = WebsiteListQuery {
+        organization_id: query.organization_id,
+        state: query.status.map(|status| match status {
+            StatusParam::Up => WebsiteState::Up,
+            StatusParam::Down => WebsiteState::Down,
+            StatusParam::Pending => WebsiteState::Pending,
+            StatusParam::Paused => WebsiteState::Paused,
+        }),
+        search: query.q.filter(|q| !q.trim().is_empty()),
+        tags: query.tag.as_deref().map(crate::tags::parse_filter).transpose()?.unwrap_or_default(),
+        sort: match query.sort {
+            SortParam::TimeAdded => WebsiteSort::TimeAdded,
+            SortParam::Url => WebsiteSort::Url,
+            SortParam::Status => WebsiteSort::Status,
+            SortParam::ResponseTime => WebsiteSort::ResponseTime,
+            SortParam::LastChecked => WebsiteSort::LastChecked,
+            SortParam::Uptime => WebsiteSort::Uptime,
+        },
+        descending: matches!(query.order, OrderParam::Desc),
+        limit,
+        offset,
+    };
+
     let mut locked_s = s.lock().unwrap();
-    let websites = locked_s.list_websites(user_id).map_err(|e| {
-        eprintln!("Error listing websites: {:?}", e);
-        poem::Error::from_string(
-            "Failed to list websites",
-            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-        )
-    })?;
-    let items = websites.into_iter()
-    .map(|w| WebsiteItem {
-        id: w.id,
-        url: w.url,
-        time_added: w.time_added.format("%Y-%m-%dT%H:%M:%S").to_string()
-    }).collect();
+    let (summaries, total) = locked_s.list_website_summaries(user_id.clone(), &list_query)
+    .map_err(|e| AppError::store_for(Entity::User(&user_id), e, "Organization not found", "Failed to list websites"))?;
+    let next_offset = Some(offset + summaries.len() as i64).filter(|next| *next < total);
     Ok(Json(ListWebsiteOutput {
-        items,
+        items: summaries.into_iter().map(website_item).collect(),
+        total,
+        next_offset,
     }))
 }
+#[utoipa::path(
+    put,
+    path = "/v1/websites/{website_id}",
+    tag = "websites",
+    summary = "Change a website's URL, details or check settings",
+    params(("website_id" = String, Path, description = "Website id")),
+    request_body = crate::request_inputs::UpdateWebsiteInput,
+    responses(
+        (status = 200, description = "Website updated", body = crate::request_outputs::CreateWebsiteOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
  #[handler]
  pub fn update_website(
     Path(id): Path<String>,
     Json(data): Json<UpdateWebsiteInput>,
     AuthUser(user_id) : AuthUser,
+    req: &Request,
     Data(s) : Data<&Arc<Mutex<Store>>>,
- ) -> Result <Json<CreateWebsiteOutput>, poem::Error> {
-    if data.url.trim().is_empty() {
-        return Err(poem::Error::from_string(
-            "URL cannot be empty",
-            poem::http::StatusCode::BAD_REQUEST,
-        ));
+ ) -> Result <Json<CreateWebsiteOutput>, AppError> {
+    validate_url(&data.url)?;
+    if let Some(tags) = &data.tags {
+        crate::tags::validate(tags)?;
     }
-    if !data.url.starts_with("http://") && !data.url.starts_with("https://") {
-        return Err(poem::Error::from_string(
-            "URL must start with http:// or https://",
-            poem::http::StatusCode::BAD_REQUEST,
-        ));
+    if let Some(seconds) = data.check_interval_seconds {
+        monitor::validate_interval(seconds)?;
     }
+    if let Some(assertions) = &data.assertions {
+        monitor::validate_assertions(assertions)?;
+    }
+    let changes = WebsiteChanges {
+        url: Some(data.url),
+        name: data.name.map(|name| text_field(Some(name), "name", MAX_NAME_LEN)).transpose()?,
+        description: data.description
+            .map(|description| text_field(Some(description), "description", MAX_DESCRIPTION_LEN))
+            .transpose()?,
+        tags: data.tags.map(crate::tags::to_json),
+        external_key: None,
+        check_interval_seconds: data.check_interval_seconds,
+        assertions: data.assertions.map(|assertions| serde_json::to_value(&assertions).unwrap_or_default()),
+    };
     let mut locked_s = s.lock().unwrap();
-    let website = locked_s.update_website(id.clone(), user_id, data.url).map_err(|e| {
-        eprintln!("Error updating website {}: {:?}", id, e);
-        match e {
-            DieselError::NotFound => poem::Error::from_string(
-                "Website not found or you don't have permission to update it",
-                poem::http::StatusCode::NOT_FOUND,
-            ),
-            _ => poem::Error::from_string(
-                "Failed to update website",
-                poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-            ),
-        }
-    })?;
+    let previous = authorize_website(&mut locked_s, &user_id, &id, Permission::EditWebsite)?;
+    let website = locked_s.update_website(id.clone(), changes)
+    .map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to update website"))?;
+    let (before, after) = audit::diff(website_snapshot(&previous), website_snapshot(&website));
+    audit::record(&mut locked_s, req, Some(&user_id), AuditEvent {
+        action: "website.update",
+        target_type: "website",
+        target_id: Some(website.id.clone()),
+        organization_id: Some(website.organization_id.clone()),
+        before,
+        after,
+    });
     Ok(Json(CreateWebsiteOutput { id:website.id }))
  } 
  
+#[utoipa::path(
+    delete,
+    path = "/v1/websites/{website_id}",
+    tag = "websites",
+    summary = "Delete a website",
+    params(("website_id" = String, Path, description = "Website id")),
+    responses(
+        (status = 204, description = "Website deleted"),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
  #[handler]
  pub fn delete_website(
     Path(id): Path<String>,
     AuthUser(user_id): AuthUser,
+    req: &Request,
     Data(s): Data<&Arc<Mutex<Store>>>
- ) -> Result <Json<serde_json::Value>, poem::Error> {
+ ) -> Result <Json<serde_json::Value>, AppError> {
     let mut locked_s = s.lock().unwrap();
+    let website = authorize_website(&mut locked_s, &user_id, &id, Permission::DeleteWebsite)?;
     locked_s
-.delete_website(id.clone(), user_id)
-.map_err(|e| {
-    eprintln!("Error deleting website: {}: {:?}", id, e);
-    match e {
-        DieselError::NotFound => poem::Error::from_string(
-            "Website not found or you don't have permission to delete it",
-            poem::http::StatusCode::NOT_FOUND,
-        ),
-        _ => poem::Error::from_string(
-            "Failed to delete website",
-            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-        ),
-    }
-})?;
+.delete_website(id.clone())
+.map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to delete website"))?;
+audit::record(&mut locked_s, req, Some(&user_id), AuditEvent {
+    action: "website.delete",
+    target_type: "website",
+    target_id: Some(website.id.clone()),
+    organization_id: Some(website.organization_id.clone()),
+    before: Some(website_snapshot(&website)),
+    ..Default::default()
+});
 Ok(Json(serde_json::json!({
     "success": true,
     "message": "Website deleted successfully",
 })))
 }
 
+#[utoipa::path(
+    post,
+    path = "/v1/websites/{website_id}/checks",
+    tag = "websites",
+    summary = "Check a website immediately",
+    params(("website_id" = String, Path, description = "Website id")),
+    responses(
+        (status = 200, description = "Result of the check", body = crate::request_outputs::CheckNowOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 409, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
 #[handler]
 pub async fn check_website_now(
     Path(id): Path<String>,
     AuthUser(user_id): AuthUser,
     Data(s): Data<&Arc<Mutex<Store>>>,
-) -> Result<Json<CheckNowOutput>, poem::Error> {
+) -> Result<Json<CheckNowOutput>, AppError> {
     // 1) DB access + auth check in its own block
-    let url = {
+    let website = {
         let mut locked = s.lock().unwrap();
-
-        let website = locked.get_website(id.clone())
-            .map_err(|e| {
-                eprintln!("Error fetching website {}: {:?}", id, e);
-                match e {
-                    DieselError::NotFound => poem::Error::from_string(
-                        "Website not found",
-                        poem::http::StatusCode::NOT_FOUND,
-                    ),
-                    _ => poem::Error::from_string(
-                        "Failed to fetch website",
-                        poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    ),
-                }
-            })?;
-
-        if website.user_id != user_id {
-            return Err(poem::Error::from_string(
-                "you don't have permission to access this website",
-                poem::http::StatusCode::FORBIDDEN,
-            ));
-        }
-
-        website.url.clone()  
-    }; 
-    let result = check_website(&url).await;
+        authorize_website(&mut locked, &user_id, &id, Permission::EditWebsite)?
+    };
+    if website.paused_at.is_some() {
+        return Err(AppError::conflict("Website is paused; resume it to check it"));
+    }
+    let result = check_website(&website.url, &assertions_of(&website)).await;
+    crate::metrics::record_check(&website, &result);
 
     {
         let mut locked = s.lock().unwrap();
@@ -236,25 +417,24 @@ pub async fn check_website_now(
             result.response_time_ms,
             result.status_code,
             result.error_message.clone(),
-        ).map_err(|e| {
-            eprintln!("Error recording check for website {}: {:?}", id, e);
-            poem::Error::from_string(
-                "Failed to record check history",
-                poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-            )
-        })?;
+        ).map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to record check history"))?;
 
         locked.update_website_status(
             id.clone(),
             result.is_up,
             result.response_time_ms,
-        ).map_err(|e| {
-            eprintln!("Error updating website status {}: {:?}", id, e);
-            poem::Error::from_string(
-                "Failed to update website status",
-                poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-            )
-        })?;
+        ).map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to update website status"))?;
+
+        crate::live::publish_check(&mut locked, &website, &result);
+
+        let change = locked.track_incident(
+            id.clone(),
+            result.is_up,
+            result.error_message.clone(),
+        ).map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to update incident"))?;
+        if let Some(change) = change {
+            crate::notify::incident_changed(&mut locked, &website, &change);
+        }
     }
 
     Ok(Json(CheckNowOutput {
@@ -265,35 +445,130 @@ pub async fn check_website_now(
     }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/v1/websites/{website_id}/pause",
+    tag = "websites",
+    summary = "Stop checking a website, keeping its history",
+    description = "Paused websites are skipped by the worker and the paused time doesn't count \
+        against uptime. Any open incident is resolved. The body is optional.",
+    params(("website_id" = String, Path, description = "Website id")),
+    request_body(content = crate::request_inputs::PauseWebsiteInput, description = "When to resume by itself"),
+    responses(
+        (status = 200, description = "The paused website", body = crate::request_outputs::GetWebsiteOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
 #[handler]
-pub fn get_website_status(
+pub async fn pause_website(
     Path(id): Path<String>,
     AuthUser(user_id): AuthUser,
+    req: &Request,
+    body: Body,
     Data(s): Data<&Arc<Mutex<Store>>>,
-) -> Result<Json<WebsiteStatusOutput>, poem::Error> {
-    let mut locked = s.lock().unwrap();
-    let website = locked.get_website(id.clone())
-    .map_err(|e| {
-        eprintln!("Error fetching website {} for status {:?}", id, e);
-    match e {
-        DieselError::NotFound => poem::Error::from_string(
-          "Website not found",
-          poem::http::StatusCode::NOT_FOUND,
-        ),
-        _ => poem::Error::from_string(
-            "Failed to fetch the website status",
-            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-        ),
+) -> Result<Json<GetWebsiteOutput>, AppError> {
+    let body = body.into_bytes().await
+    .map_err(|_| AppError::bad_request("Failed to read request body"))?;
+    let data: PauseWebsiteInput = if body.iter().all(u8::is_ascii_whitespace) {
+        PauseWebsiteInput::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| AppError::bad_request(format!("Invalid body: {}", e)))?
+    };
+    let until = parse_time_param(data.resume_at.as_deref(), "resume_at")?;
+    if until.is_some_and(|until| until <= chrono::Utc::now().naive_utc()) {
+        return Err(AppError::bad_request("resume_at must be in the future"));
     }
-})?;
 
-if website.user_id != user_id {
-    return Err(poem::Error::from_string (
-        "You don't have permission to access the website",
-        poem::http::StatusCode::FORBIDDEN,
-    ));
+    let mut locked = s.lock().unwrap();
+    let before = authorize_website(&mut locked, &user_id, &id, Permission::EditWebsite)?;
+    let after = locked.pause_website(id.clone(), until)
+    .map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to pause website"))?;
+    audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+        action: "website.pause",
+        target_type: "website",
+        target_id: Some(after.id.clone()),
+        organization_id: Some(after.organization_id.clone()),
+        before: Some(website_snapshot(&before)),
+        after: Some(website_snapshot(&after)),
+    });
+    let summary = locked.get_website_summary(id.clone())
+    .map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to fetch website"))?;
+    Ok(Json(GetWebsiteOutput {
+        website: website_item(summary),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/websites/{website_id}/resume",
+    tag = "websites",
+    summary = "Start checking a paused website again",
+    params(("website_id" = String, Path, description = "Website id")),
+    responses(
+        (status = 200, description = "The resumed website", body = crate::request_outputs::GetWebsiteOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn resume_website(
+    Path(id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    req: &Request,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<GetWebsiteOutput>, AppError> {
+    let mut locked = s.lock().unwrap();
+    let before = authorize_website(&mut locked, &user_id, &id, Permission::EditWebsite)?;
+    let after = locked.resume_website(id.clone())
+    .map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to resume website"))?;
+    if before.paused_at.is_some() {
+        audit::record(&mut locked, req, Some(&user_id), AuditEvent {
+            action: "website.resume",
+            target_type: "website",
+            target_id: Some(after.id.clone()),
+            organization_id: Some(after.organization_id.clone()),
+            before: Some(website_snapshot(&before)),
+            after: Some(website_snapshot(&after)),
+        });
+    }
+    let summary = locked.get_website_summary(id.clone())
+    .map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to fetch website"))?;
+    Ok(Json(GetWebsiteOutput {
+        website: website_item(summary),
+    }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/websites/{website_id}/status",
+    tag = "websites",
+    summary = "Current status of a website",
+    params(("website_id" = String, Path, description = "Website id")),
+    responses(
+        (status = 200, description = "Latest known status", body = crate::request_outputs::WebsiteStatusOutput),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
+#[handler]
+pub fn get_website_status(
+    Path(id): Path<String>,
+    AuthUser(user_id): AuthUser,
+    Data(s): Data<&Arc<Mutex<Store>>>,
+) -> Result<Json<WebsiteStatusOutput>, AppError> {
+    let mut locked = s.lock().unwrap();
+    let website = authorize_website(&mut locked, &user_id, &id, Permission::ViewWebsite)?;
+
 let last_checked_str = website.last_checked
 .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string());
 
@@ -310,47 +585,70 @@ let output = WebsiteStatusOutput{
 Ok(Json(output))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/websites/{website_id}/history",
+    tag = "websites",
+    summary = "Page through a website's check history",
+    params(("website_id" = String, Path, description = "Website id"), HistoryQuery),
+    responses(
+        (status = 200, description = "One page of checks, newest first", body = crate::request_outputs::WebsiteHistoryOutput),
+        (status = 400, response = crate::openapi::Problem),
+        (status = 401, response = crate::openapi::Problem),
+        (status = 403, response = crate::openapi::Problem),
+        (status = 404, response = crate::openapi::Problem),
+        (status = 500, response = crate::openapi::Problem)
+    ),
+    security(("bearer" = []))
+)]
 #[handler]
 pub fn get_website_history (
     Path(id): Path<String>,
     AuthUser(user_id): AuthUser,
     Query(query): Query<HistoryQuery>,
     Data(s): Data<&Arc<Mutex<Store>>>,
-) -> Result<Json<WebsiteHistoryOutput>, poem::Error> {
+) -> Result<Json<WebsiteHistoryOutput>, AppError> {
     let mut locked = s.lock().unwrap();
+    authorize_website(&mut locked, &user_id, &id, Permission::ViewWebsite)?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1,500);
+
+    let cursor = match query.cursor.as_deref() {
+        Some(token) => Some(decode_cursor(token).ok_or_else(|| AppError::bad_request("Invalid cursor"))?),
+        None => None,
+    };
 
-    let website = locked.get_website(id.clone())
-    .map_err(|e| {
-        eprintln!("Error fetching website {} for history: {:?}", id, e);
-        match e {
-            DieselError::NotFound => poem::Error::from_string(
-                "Website not found",
-                poem::http::StatusCode::NOT_FOUND,
-            ),
-            _ => poem::Error::from_string(
-                "Failed to fetch the website history",
-                poem::http::StatusCode::INTERNAL_SERVER_ERROR,
-            ),
+    let filter = HistoryFilter {
+        from: parse_time_param(query.from.as_deref(), "from")?,
+        to: parse_time_param(query.to.as_deref(), "to")?,
+        is_up: query.is_up,
+    };
+
+    // Fetch one extra row to learn whether another page exists
+    let mut history = locked.get_website_history(id.clone(), &filter, cursor.as_ref(), limit + 1)
+    .map_err(|e| AppError::store_for(Entity::Website(&id), e, "Website not found", "Failed to fetch website history"))?;
+
+    let has_more = history.len() as i64 > limit;
+    let going_newer = matches!(cursor, Some(HistoryCursor::Newer { .. }));
+    if has_more {
+        if going_newer {
+            history.remove(0);
+        } else {
+            history.truncate(limit as usize);
         }
-    })?;
-    if website.user_id != user_id {
-        return Err(poem::Error::from_string(
-            "You don't have permission to access the website",
-            poem::http::StatusCode::FORBIDDEN,
-        ));
     }
 
-    let limit = query.limit.unwrap_or(50).clamp(1,500);
-    let offset = query.offset.unwrap_or(0).max(0);
+    let older_than_last = history.last().map(|h| HistoryCursor::Older {
+        checked_at: h.checked_at,
+        id: h.id.clone(),
+    });
+    let newer_than_first = history.first().map(|h| HistoryCursor::Newer {
+        checked_at: h.checked_at,
+        id: h.id.clone(),
+    });
 
-    let history = locked.get_website_history(id.clone(), limit, offset)
-    .map_err(|e| {
-        eprintln!("Error fetching website history {}: {:?}", id, e);
-        poem::Error::from_string(
-            "failed to fetch website history",
-            poem::http::StatusCode::INTERNAL_SERVER_ERROR
-        )
-    })?;
+    let next_cursor = if has_more || going_newer { older_than_last } else { None };
+    let prev_cursor = if cursor.is_some() && (has_more || !going_newer) { newer_than_first } else { None };
 
     let items = history.into_iter()
     .map(|h| CheckHistoryItem {
@@ -362,5 +660,18 @@ pub fn get_website_history (
     })
     .collect();
 
-    Ok(Json(WebsiteHistoryOutput { items }))
+    Ok(Json(WebsiteHistoryOutput {
+        items,
+        next_cursor: next_cursor.as_ref().map(encode_cursor),
+        prev_cursor: prev_cursor.as_ref().map(encode_cursor),
+    }))
+}
+
+fn parse_time_param(value: Option<&str>, name: &str) -> Result<Option<chrono::NaiveDateTime>, AppError> {
+    match value {
+        Some(v) => parse_timestamp(v).map(Some).ok_or_else(|| {
+            AppError::bad_request(format!("Invalid '{}' timestamp, expected YYYY-MM-DDTHH:MM:SS or RFC 3339", name))
+        }),
+        None => Ok(None),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand_core::{OsRng, RngCore};
+use store::store::Store;
+
+use crate::jwt;
+use crate::request_outputs::SignInOutput;
+
+/// Lifetime of a refresh token in seconds
+pub fn refresh_token_ttl() -> i64 {
+    std::env::var("REFRESH_TOKEN_TTL_SECONDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30 * 24 * 60 * 60) // 30 days
+}
+
+/// 256 random bits, URL-safe so clients can store it anywhere
+pub fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub fn refresh_token_expiry() -> chrono::NaiveDateTime {
+    chrono::Utc::now().naive_utc() + chrono::Duration::seconds(refresh_token_ttl())
+}
+
+/// Start a new session for `user_id` and hand back its first access/refresh token pair
+pub fn start_session(store: &mut Store, user_id: &str) -> Result<SignInOutput, poem::Error> {
+    let refresh_token = generate_refresh_token();
+    let session = store.create_session(user_id.to_string(), &refresh_token, refresh_token_expiry())
+    .map_err(|e| {
+        tracing::error!(user_id = %user_id, error = ?e, "failed to create session");
+        poem::Error::from_string(
+            "Failed to create session",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+
+    token_pair(user_id, &session.id, refresh_token)
+}
+
+pub fn token_pair(user_id: &str, session_id: &str, refresh_token: String) -> Result<SignInOutput, poem::Error> {
+    let token = jwt::generate_jwt(user_id, session_id).map_err(|e| {
+        tracing::error!(user_id = %user_id, error = ?e, "JWT generation failed");
+        poem::Error::from_string(
+            "Failed to generate token",
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })?;
+
+    Ok(SignInOutput {
+        jwt: token,
+        refresh_token,
+        expires_in: jwt::access_token_ttl(),
+    })
+}
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::error::AppError;
+
+const MAX_TAGS: usize = 32;
+const MAX_KEY_LEN: usize = 64;
+const MAX_VALUE_LEN: usize = 256;
+
+/// Keys are short identifiers (`team`, `env`, `k8s.io/app`); values are free text
+pub fn validate(tags: &BTreeMap<String, String>) -> Result<(), AppError> {
+    if tags.len() > MAX_TAGS {
+        return Err(AppError::bad_request(format!("At most {} tags are allowed", MAX_TAGS)));
+    }
+    for (key, value) in tags {
+        let valid_key = !key.is_empty()
+            && key.len() <= MAX_KEY_LEN
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c));
+        if !valid_key {
+            return Err(AppError::bad_request(format!(
+                "Invalid tag key '{}': use 1-{} letters, digits, '-', '_', '.' or '/'",
+                key, MAX_KEY_LEN
+            )));
+        }
+        if value.len() > MAX_VALUE_LEN {
+            return Err(AppError::bad_request(format!("Tag '{}' is longer than {} bytes", key, MAX_VALUE_LEN)));
+        }
+    }
+    Ok(())
+}
+
+pub fn to_json(tags: BTreeMap<String, String>) -> Value {
+    Value::Object(tags.into_iter().map(|(k, v)| (k, Value::String(v))).collect())
+}
+
+/// Tags as stored; anything that isn't a string value is skipped
+pub fn from_json(tags: &Value) -> BTreeMap<String, String> {
+    tags.as_object()
+        .map(|map| map.iter()
+            .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+            .collect())
+        .unwrap_or_default()
+}
+
+/// Parse a `?tag=` filter: comma-separated `key=value` pairs, all of which must match
+pub fn parse_filter(filter: &str) -> Result<Vec<(String, String)>, AppError> {
+    filter.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            pair.split_once('=')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .filter(|(k, _)| !k.is_empty())
+                .ok_or_else(|| AppError::bad_request(format!("Invalid tag filter '{}', expected key=value", pair)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn parses_comma_separated_pairs() {
+        assert_eq!(parse_filter("team=payments,env=prod").unwrap(), pairs(&[("team", "payments"), ("env", "prod")]));
+        assert_eq!(parse_filter(" team = payments , env=prod ").unwrap(), pairs(&[("team", "payments"), ("env", "prod")]));
+        // Only the first `=` splits, so values may contain more
+        assert_eq!(parse_filter("query=a=b").unwrap(), pairs(&[("query", "a=b")]));
+        assert_eq!(parse_filter("team=").unwrap(), pairs(&[("team", "")]));
+    }
+
+    #[test]
+    fn skips_blank_pairs() {
+        assert_eq!(parse_filter("team=payments,,  ,").unwrap(), pairs(&[("team", "payments")]));
+        assert!(parse_filter("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_pairs_without_a_key() {
+        for filter in ["team", "team=payments,env", "=payments", " =x"] {
+            let err = parse_filter(filter).expect_err(filter);
+            assert_eq!(err.status, poem::http::StatusCode::BAD_REQUEST, "{}", filter);
+            assert!(err.message.contains("expected key=value"), "{}", err.message);
+        }
+    }
+}
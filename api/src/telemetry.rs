@@ -0,0 +1,132 @@
+use poem::{Endpoint, IntoResponse, Request, Response, Result};
+use tracing::{Instrument, field};
+use tracing_subscriber::{EnvFilter, Layer, Registry, layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Header carrying the request id; honoured on the way in and echoed on the way out
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Keeps the OTLP exporter alive and flushes pending spans when dropped
+pub struct TelemetryGuard {
+    #[cfg(feature = "otlp")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.provider.take()
+            && let Err(e) = provider.shutdown()
+        {
+            tracing::error!(error = ?e, "failed to shut down OTLP exporter");
+        }
+    }
+}
+
+/// Install the global subscriber.
+///
+/// - `RUST_LOG` sets levels (default `info`)
+/// - `LOG_FORMAT` is `json`, `pretty`, or anything else for single-line text
+/// - with the `otlp` feature, setting `OTEL_EXPORTER_OTLP_ENDPOINT` also exports spans
+pub fn init() -> TelemetryGuard {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let fmt_layer: Box<dyn Layer<Registry> + Send + Sync> =
+        match std::env::var("LOG_FORMAT").unwrap_or_default().as_str() {
+            "json" => tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .boxed(),
+            "pretty" => tracing_subscriber::fmt::layer().pretty().boxed(),
+            _ => tracing_subscriber::fmt::layer().boxed(),
+        };
+
+    #[cfg(feature = "otlp")]
+    let (otel_layer, provider) = match otlp_provider() {
+        Some(provider) => {
+            use opentelemetry::trace::TracerProvider as _;
+            let tracer = provider.tracer("better-uptime");
+            (Some(tracing_opentelemetry::layer().with_tracer(tracer)), Some(provider))
+        }
+        None => (None, None),
+    };
+
+    let registry = tracing_subscriber::registry().with(fmt_layer);
+    #[cfg(feature = "otlp")]
+    let registry = registry.with(otel_layer);
+    registry.with(filter).init();
+
+    TelemetryGuard {
+        #[cfg(feature = "otlp")]
+        provider,
+    }
+}
+
+#[cfg(feature = "otlp")]
+fn otlp_provider() -> Option<opentelemetry_sdk::trace::SdkTracerProvider> {
+    std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+
+    // The exporter reads the endpoint and headers from the standard OTEL_* variables
+    let exporter = match opentelemetry_otlp::SpanExporter::builder().with_http().build() {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!("Error building OTLP exporter, spans will not be exported: {:?}", e);
+            return None;
+        }
+    };
+    let resource = opentelemetry_sdk::Resource::builder()
+        .with_service_name("better-uptime")
+        .build();
+
+    Some(
+        opentelemetry_sdk::trace::SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource)
+            .build(),
+    )
+}
+
+/// The id `request_span` assigned, for inner middleware that renders its own errors
+#[derive(Clone)]
+pub struct RequestId(pub String);
+
+/// Middleware wrapping each request in a span carrying its request id.
+/// `user_id` is filled in by `AuthUser` once the caller is authenticated.
+pub async fn request_span<E: Endpoint>(ep: E, mut req: Request) -> Result<Response> {
+    let request_id = req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    req.set_data(RequestId(request_id.clone()));
+
+    let span = tracing::info_span!(
+        "http_request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %req.uri().path(),
+        user_id = field::Empty,
+        status = field::Empty,
+    );
+
+    async move {
+        let mut resp = match ep.call(req).await {
+            Ok(resp) => resp.into_response(),
+            Err(err) => crate::error::problem_response(err, &request_id),
+        };
+        tracing::Span::current().record("status", resp.status().as_u16());
+        if resp.status().is_server_error() {
+            tracing::error!("request failed");
+        } else {
+            tracing::info!("request completed");
+        }
+
+        if let Ok(value) = request_id.parse() {
+            resp.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+        Ok(resp)
+    }
+    .instrument(span)
+    .await
+}
@@ -0,0 +1,81 @@
+//! Fixtures for tests that serve the real API on a database of their own, shared by this
+//! crate's integration tests and the client's. Behind the `test-support` feature, which
+//! only dev-dependencies enable. Needs `TEST_DATABASE_URL`, a Postgres URL whose user may
+//! create databases.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Once};
+
+use diesel::{Connection, PgConnection, RunQueryDsl};
+use poem::Server;
+use poem::listener::{Acceptor, Listener, TcpListener};
+use store::store::Store;
+
+static INIT: Once = Once::new();
+static NEXT_DATABASE: AtomicUsize = AtomicUsize::new(0);
+
+/// A database created for one test, migrated with the migrations embedded in `store`, and
+/// dropped after it, connections and all
+pub struct TestDatabase {
+    admin_url: String,
+    name: String,
+    url: String,
+}
+
+impl TestDatabase {
+    /// A fresh database on the server `TEST_DATABASE_URL` points at
+    pub fn create() -> TestDatabase {
+        let admin_url = std::env::var("TEST_DATABASE_URL")
+            .expect("TEST_DATABASE_URL should be set to a Postgres URL whose user may create databases");
+        let name = format!("bu_test_{}_{}", std::process::id(), NEXT_DATABASE.fetch_add(1, Ordering::Relaxed));
+        let mut admin = PgConnection::establish(&admin_url).expect("TEST_DATABASE_URL should be reachable");
+        // Left over from an earlier run that had the same pid and didn't clean up
+        diesel::sql_query(format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", name)).execute(&mut admin).unwrap();
+        diesel::sql_query(format!("CREATE DATABASE {}", name)).execute(&mut admin)
+            .expect("TEST_DATABASE_URL's user should be allowed to create databases");
+
+        let database = TestDatabase { url: with_database(&admin_url, &name), admin_url, name };
+        Store::connect(&database.url).unwrap()
+            .run_pending_migrations()
+            .expect("migrations should apply to an empty database");
+        database
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        if let Ok(mut admin) = PgConnection::establish(&self.admin_url) {
+            let _ = diesel::sql_query(format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name)).execute(&mut admin);
+        }
+    }
+}
+
+/// `url` with its database name swapped for `name`
+fn with_database(url: &str, name: &str) -> String {
+    let (base, query) = match url.split_once('?') {
+        Some((base, query)) => (base, format!("?{}", query)),
+        None => (url, String::new()),
+    };
+    let (server, _) = base.rsplit_once('/').expect("TEST_DATABASE_URL should end in /<database>");
+    format!("{}/{}{}", server, name, query)
+}
+
+/// Serve the whole API on a free local port over `database`; returns its base URL and the
+/// store it uses. The background worker is left to the caller.
+pub async fn serve(database: &TestDatabase) -> (String, Arc<Mutex<Store>>) {
+    INIT.call_once(|| {
+        // SAFETY: runs once, before any server in this process reads the environment
+        unsafe { std::env::set_var("JWT_SECRET", "test-support-secret") };
+        crate::jwt::init().expect("JWT keys");
+    });
+
+    let store = Arc::new(Mutex::new(Store::connect(database.url()).unwrap()));
+    let acceptor = TcpListener::bind("127.0.0.1:0").into_acceptor().await.unwrap();
+    let addr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+    tokio::spawn(Server::new_with_acceptor(acceptor).run(crate::app(store.clone(), database.url().to_string())));
+    (format!("http://{}", addr), store)
+}
@@ -0,0 +1,117 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand_core::{OsRng, RngCore};
+use store::models::two_factor::UserTotp;
+use store::store::Store;
+use totp_rs::{Algorithm, TOTP};
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECONDS: u64 = 30;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// How long a sign-in challenge stays usable, in seconds
+pub const CHALLENGE_TTL_SECONDS: i64 = 5 * 60;
+
+fn issuer() -> String {
+    std::env::var("TOTP_ISSUER").unwrap_or_else(|_| "Better Uptime".to_string())
+}
+
+fn internal_error(message: &'static str) -> poem::Error {
+    poem::Error::from_string(message, poem::http::StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// 160 random bits, base32 encoded as authenticator apps expect
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    base32::encode(base32::Alphabet::Rfc4648 { padding: false }, &bytes)
+}
+
+/// Build the TOTP for a stored secret. Authenticator labels can't contain ':', so it
+/// is dropped from the account name.
+pub fn totp_for(secret: &str, account_name: &str) -> Result<TOTP, poem::Error> {
+    let bytes = base32::decode(base32::Alphabet::Rfc4648 { padding: false }, secret)
+        .ok_or_else(|| internal_error("Invalid two-factor secret"))?;
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        0,
+        TOTP_STEP_SECONDS,
+        bytes,
+        Some(issuer()),
+        account_name.replace(':', ""),
+    ).map_err(|e| {
+        tracing::error!(error = ?e, "failed to build TOTP");
+        internal_error("Invalid two-factor secret")
+    })
+}
+
+/// The time step `code` belongs to, allowing one step of clock drift either way
+pub fn matching_step(totp: &TOTP, code: &str) -> Option<i64> {
+    let now = chrono::Utc::now().timestamp() as u64 / TOTP_STEP_SECONDS;
+    (now - 1..=now + 1)
+        .find(|step| totp.check(code, step * TOTP_STEP_SECONDS))
+        .map(|step| step as i64)
+}
+
+/// Opaque token handed out by sign-in while the second factor is pending
+pub fn generate_challenge_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Recovery codes are shown as `XXXX-XXXX-XXXX-XXXX` (80 random bits each)
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT).map(|_| {
+        let mut bytes = [0u8; 10];
+        OsRng.fill_bytes(&mut bytes);
+        let encoded = base32::encode(base32::Alphabet::Rfc4648 { padding: false }, &bytes);
+        encoded.as_bytes()
+            .chunks(4)
+            .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("-")
+    }).collect()
+}
+
+/// Codes are hashed without dashes, spaces or case so users can type them loosely
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Check a second factor for a user with 2FA enabled: a current TOTP code (each one
+/// usable once) or an unused recovery code
+pub fn verify_second_factor(
+    store: &mut Store,
+    totp_row: &UserTotp,
+    code: &str,
+) -> Result<bool, poem::Error> {
+    let code = code.trim();
+    if code.len() == TOTP_DIGITS && code.chars().all(|c| c.is_ascii_digit()) {
+        let totp = totp_for(&totp_row.secret, "")?;
+        let Some(step) = matching_step(&totp, code) else {
+            return Ok(false);
+        };
+        return store.accept_totp_step(totp_row.user_id.clone(), step).map_err(|e| {
+            tracing::error!(user_id = %totp_row.user_id, error = ?e, "failed to record TOTP use");
+            internal_error("Failed to verify code")
+        });
+    }
+
+    store.use_recovery_code(totp_row.user_id.clone(), &normalize_recovery_code(code)).map_err(|e| {
+        tracing::error!(user_id = %totp_row.user_id, error = ?e, "failed to use recovery code");
+        internal_error("Failed to verify code")
+    })
+}
+
+/// The user's TOTP settings if 2FA is switched on for them
+pub fn enabled_totp(store: &mut Store, user_id: &str) -> Result<Option<UserTotp>, poem::Error> {
+    let totp = store.get_totp(user_id.to_string()).map_err(|e| {
+        tracing::error!(user_id = %user_id, error = ?e, "failed to fetch two-factor settings");
+        internal_error("Failed to fetch two-factor settings")
+    })?;
+    Ok(totp.filter(|t| t.enabled_at.is_some()))
+}
@@ -0,0 +1,84 @@
+use poem::{
+    Endpoint, IntoResponse, Request, Response, Result,
+    error::NotFoundError,
+    http::{HeaderValue, StatusCode, header},
+};
+
+use crate::telemetry::RequestId;
+
+/// When the unversioned routes were superseded by `/v1`, as an RFC 9745 `Deprecation` date
+const UNVERSIONED_DEPRECATED_AT: &str = "@1773619200";
+
+/// For `/v1` creates: turn the handler's `200` into `201 Created`, with a `Location`
+/// built from the request path and the `id` of the new resource in the body.
+pub async fn created<E: Endpoint>(ep: E, req: Request) -> Result<Response> {
+    let collection = req.original_uri().path().trim_end_matches('/').to_string();
+    let mut resp = ep.call(req).await?.into_response();
+    if resp.status() != StatusCode::OK {
+        return Ok(resp);
+    }
+
+    let body = resp.take_body().into_bytes().await?;
+    let id = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|value| value.get("id")?.as_str().map(str::to_string));
+    if let Some(location) = id.and_then(|id| HeaderValue::from_str(&format!("{}/{}", collection, id)).ok()) {
+        resp.headers_mut().insert(header::LOCATION, location);
+    }
+    resp.set_status(StatusCode::CREATED);
+    resp.set_body(body);
+    Ok(resp)
+}
+
+/// For `/v1` deletes: drop the handler's success message and answer `204 No Content`
+pub async fn no_content<E: Endpoint>(ep: E, req: Request) -> Result<Response> {
+    let resp = ep.call(req).await?.into_response();
+    if resp.status() != StatusCode::OK {
+        return Ok(resp);
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Mark a response from an unversioned alias as deprecated (RFC 9745), pointing at the
+/// docs for its `/v1` successor. Errors are rendered here so they carry the header too,
+/// except for paths no alias matches: those are plain 404s, not deprecated routes.
+pub async fn deprecated<E: Endpoint>(ep: E, req: Request) -> Result<Response> {
+    let request_id = req.data::<RequestId>().map(|id| id.0.clone()).unwrap_or_default();
+    let mut resp = match ep.call(req).await {
+        Ok(resp) => resp.into_response(),
+        Err(err) if err.is::<NotFoundError>() => return Err(err),
+        Err(err) => crate::error::problem_response(err, &request_id),
+    };
+    let headers = resp.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static(UNVERSIONED_DEPRECATED_AT));
+    headers.insert(header::LINK, HeaderValue::from_static(r#"</docs>; rel="deprecation"; type="text/html""#));
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::rate_limit::{LoginRateLimit, MemoryBackend, RateLimitConfig};
+
+    use super::*;
+
+    async fn get(path: &str) -> Result<Response> {
+        let app = crate::app::routes(LoginRateLimit::new(Arc::new(MemoryBackend::default()), RateLimitConfig::from_env()));
+        app.call(Request::builder().uri(path.parse().unwrap()).finish()).await
+    }
+
+    #[tokio::test]
+    async fn marks_only_the_unversioned_aliases() {
+        let legacy = get("/website/x/status").await.expect("rendered by the alias");
+        assert_eq!(legacy.status(), StatusCode::UNAUTHORIZED);
+        assert!(legacy.headers().contains_key("deprecation"));
+        assert!(legacy.headers().contains_key(header::LINK));
+
+        let err = get("/v2/websites").await.expect_err("no route");
+        assert!(err.is::<NotFoundError>());
+
+        let err = get("/v1/websites").await.expect_err("signed out");
+        assert_eq!(err.status(), StatusCode::UNAUTHORIZED);
+    }
+}
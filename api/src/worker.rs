@@ -1,54 +1,101 @@
 use std::sync::Arc;
 use std::sync::Mutex;
 use store::store::Store;
-use crate::monitor::check_website;
+use tracing::Instrument;
+use store::models::website::Website;
+use crate::monitor::{assertions_of, check_website};
+
+/// Whether a website's check interval has run out. Half a tick of slack keeps a website
+/// from slipping a whole tick behind because its last check finished late in a cycle.
+fn is_due(website: &Website, now: chrono::NaiveDateTime, tick_seconds: u64) -> bool {
+    match website.last_checked {
+        None => true,
+        Some(last) => {
+            let slack = chrono::Duration::milliseconds(tick_seconds as i64 * 500);
+            now - last + slack >= chrono::Duration::seconds(website.check_interval_seconds as i64)
+        }
+    }
+}
 
-pub async fn check_all_websites(store: Arc<Mutex<Store>>) {
-    println!("[Worker] Starting check cycle for all websites...");
+#[tracing::instrument(name = "check_cycle", skip_all)]
+pub async fn check_all_websites(store: Arc<Mutex<Store>>, tick_seconds: u64) {
+    tracing::info!("starting check cycle");
 
     let websites = {
         let mut locked = store.lock().unwrap();
-        match locked.get_all_websites() {
+        match locked.resume_due_websites(chrono::Utc::now().naive_utc()) {
+            Ok(resumed) => for website in resumed {
+                tracing::info!(website_id = %website.id, "pause ran out, resuming checks");
+            },
+            Err(e) => tracing::error!(error = ?e, "failed to resume paused websites"),
+        }
+        match locked.get_active_websites() {
             Ok(websites) => websites,
             Err(e) => {
-                eprintln!("[Worker] Error fetching websites: {:?}", e);
+                tracing::error!(error = ?e, "failed to fetch websites");
                 return; // Exit early if we can't fetch websites
             }
         }
     };
 
+    crate::metrics::retain_websites(&websites);
+
+    let now = chrono::Utc::now().naive_utc();
+    let websites: Vec<Website> = websites.into_iter()
+        .filter(|website| is_due(website, now, tick_seconds))
+        .collect();
+
     if websites.is_empty() {
-        println!("[Worker] No websites to check");
+        tracing::info!("no websites due for a check");
         return;
     }
 
-    println!("[Worker] Checking {} websites...", websites.len());
+    tracing::info!(count = websites.len(), "checking websites");
 
     for website in websites {
         let website_id = website.id.clone();
-        let url = website.url.clone();
-
-        println!("[Worker] checking website {}: {}", website_id, url);
-
-        let result = check_website(&url).await;
-
-        if result.is_up{ 
-            println!(
-                "[Worker] ✓ {} is UP ({}ms, status {})",
-                url,
-                result.response_time_ms.unwrap_or(0),
-                result.status_code.unwrap_or(0),
-            );
-        } else {
-            println!(
-                "[Worker] ✗ {} is DOWN: {}",
-                url,
-                result.error_message.as_deref().unwrap_or("Unknown error")
-            );
-        }
+        let span = tracing::info_span!(
+            "check",
+            website_id = %website.id,
+            user_id = %website.user_id,
+            url = %website.url,
+        );
+
+        async {
+            let result = check_website(&website.url, &assertions_of(&website)).await;
+
+            if result.is_up {
+                tracing::info!(
+                    response_time_ms = result.response_time_ms,
+                    status_code = result.status_code,
+                    "website is up",
+                );
+            } else {
+                tracing::warn!(
+                    response_time_ms = result.response_time_ms,
+                    status_code = result.status_code,
+                    error = result.error_message.as_deref().unwrap_or("Unknown error"),
+                    "website is down",
+                );
+            }
+
+            crate::metrics::record_check(&website, &result);
 
-        {
+            let _db = tracing::info_span!("db_write").entered();
             let mut locked = store.lock().unwrap();
+            // The check ran without the lock, so the website may have been paused or deleted
+            // meanwhile; writing the result back would undo what pausing resolved
+            match locked.get_website(website_id.clone()) {
+                Ok(current) if current.paused_at.is_none() => {}
+                Ok(_) => {
+                    tracing::info!("website was paused during its check, dropping the result");
+                    return;
+                }
+                Err(e) => {
+                    tracing::info!(error = ?e, "website is gone, dropping the result");
+                    return;
+                }
+            }
             match locked.record_check(
                 website_id.clone(),
                 result.is_up,
@@ -56,8 +103,8 @@ pub async fn check_all_websites(store: Arc<Mutex<Store>>) {
                 result.status_code,
                 result.error_message.clone(),
             ) {
-                Ok(_) => println!("[Worker] Recorded check history for {}", website_id),
-                Err(e) => eprintln!("[Worker] Error recording check: {:?}", e),
+                Ok(_) => tracing::debug!("recorded check history"),
+                Err(e) => tracing::error!(error = ?e, "failed to record check"),
             }
 
             match locked.update_website_status(
@@ -65,28 +112,43 @@ pub async fn check_all_websites(store: Arc<Mutex<Store>>) {
                 result.is_up,
                 result.response_time_ms,
             ) {
-                Ok(_) => println!("[Worker] Updated status for {}", website_id),
-                Err(e) => eprintln!("[Worker] Error updating status: {:?}", e),
+                Ok(_) => tracing::debug!("updated website status"),
+                Err(e) => tracing::error!(error = ?e, "failed to update website status"),
             }
-        }
 
+            crate::live::publish_check(&mut locked, &website, &result);
 
+            match locked.track_incident(
+                website_id.clone(),
+                result.is_up,
+                result.error_message.clone(),
+            ) {
+                Ok(Some(change)) => crate::notify::incident_changed(&mut locked, &website, &change),
+                Ok(None) => {}
+                Err(e) => tracing::error!(error = ?e, "failed to track incident"),
+            }
+        }
+        .instrument(span)
+        .await;
     }
-    println!("[Worker] Finished check cycle");
+    tracing::info!("finished check cycle");
 }
 
 
-/// Backgroud worker that periodically checks all websites
+/// Backgroud worker that wakes up every `interval_seconds` and checks the websites whose
+/// own check interval has run out
 pub fn start_background_worker(store: Arc<Mutex<Store>>, interval_seconds: u64) {
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_seconds));
         interval.tick().await;
 
-        println!("[Worker] Background worker started (checking every {} seconds)", interval_seconds);
+        tracing::info!(interval_seconds, "background worker started");
 
         loop {
             interval.tick().await;
-            check_all_websites(store.clone()).await;
+            let started = std::time::Instant::now();
+            check_all_websites(store.clone(), interval_seconds).await;
+            crate::metrics::observe_worker_cycle(started.elapsed());
         }
     });
-}
\ No newline at end of file
+}
@@ -0,0 +1,57 @@
+//! What API keys of each scope may do. See `harness` for what these need to run.
+
+mod harness;
+
+use api::request_inputs::{ApiKeyScope, CheckAssertions, CreateApiKeyInput, CreateWebsiteInput};
+use api::request_outputs::{CreateApiKeyOutput, CreateWebsiteOutput};
+use harness::TestApp;
+use reqwest::{Method, StatusCode};
+
+async fn api_key(app: &TestApp, scope: ApiKeyScope) -> String {
+    let input = CreateApiKeyInput { name: "script".to_string(), scope, expires_at: None };
+    app.post::<_, CreateApiKeyOutput>("/v1/api-keys", &input).await.key
+}
+
+/// Status of a request made with `key` alone, not the signed-in session
+async fn with_key(app: &TestApp, key: &str, method: Method, path: &str) -> StatusCode {
+    let resp = reqwest::Client::new().request(method, format!("{}{}", app.url, path)).bearer_auth(key).send().await;
+    resp.unwrap().status()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn read_keys_cannot_run_checks() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let website = CreateWebsiteInput {
+        url: format!("{}/openapi.json", app.url),
+        organization_id: None,
+        name: None,
+        description: None,
+        tags: Default::default(),
+        check_interval_seconds: None,
+        assertions: CheckAssertions::default(),
+    };
+    let id = app.post::<_, CreateWebsiteOutput>("/v1/websites", &website).await.id;
+    let key = api_key(&app, ApiKeyScope::Read).await;
+
+    assert_eq!(with_key(&app, &key, Method::GET, &format!("/website/{}/status", id)).await, StatusCode::OK);
+    assert_eq!(
+        with_key(&app, &key, Method::GET, &format!("/website/{}/check", id)).await,
+        StatusCode::FORBIDDEN,
+        "the legacy check records a result despite being a GET",
+    );
+    assert_eq!(with_key(&app, &key, Method::POST, &format!("/v1/websites/{}/checks", id)).await, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn keys_cannot_sign_out_every_session() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let key = api_key(&app, ApiKeyScope::Full).await;
+
+    assert_eq!(with_key(&app, &key, Method::POST, "/v1/sign-out-all").await, StatusCode::FORBIDDEN);
+    // The session that made the key is still signed in, and may sign out everywhere
+    app.post::<_, serde_json::Value>("/v1/sign-out-all", &serde_json::json!({})).await;
+}
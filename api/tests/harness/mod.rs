@@ -0,0 +1,161 @@
+//! Test harness: the whole API served in-process on a database of its own, and mock
+//! websites for it to check. The server and database come from `api::test_support`, so
+//! these need `TEST_DATABASE_URL` as described there; every `TestApp` gets a fresh
+//! database and drops it when done. Tests using it are `#[ignore]`d, so run them with
+//! `TEST_DATABASE_URL=... cargo test -- --ignored`; they fail without it.
+
+// Each test file uses only part of the harness
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use api::request_inputs::CreateUserInput;
+use api::request_outputs::{CreateUserOutput, SignInResponse};
+use api::test_support::{TestDatabase, serve};
+use poem::http::StatusCode;
+use poem::listener::{Acceptor, Listener, TcpListener};
+use poem::{Response, Server};
+use reqwest::Method;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use store::store::Store;
+
+static NEXT_USER: AtomicUsize = AtomicUsize::new(0);
+
+/// The API on a free local port, with a client that signs requests in as the last user
+/// from `sign_up`
+pub struct TestApp {
+    pub url: String,
+    pub store: Arc<Mutex<Store>>,
+    http: reqwest::Client,
+    token: Option<String>,
+    // Dropped last, after everything that may still be connected to it
+    _database: TestDatabase,
+}
+
+impl TestApp {
+    /// A fresh server on a fresh database
+    pub async fn start() -> TestApp {
+        let database = TestDatabase::create();
+        let (url, store) = serve(&database).await;
+        TestApp {
+            url,
+            store,
+            http: reqwest::Client::new(),
+            token: None,
+            _database: database,
+        }
+    }
+
+    /// Sign up a new user and sign in as them; returns their id
+    pub async fn sign_up(&mut self) -> String {
+        let user = CreateUserInput {
+            username: format!("user{}", NEXT_USER.fetch_add(1, Ordering::Relaxed)),
+            password: "password123".to_string(),
+        };
+        let created: CreateUserOutput = self.post("/v1/sign-up", &user).await;
+        match self.post("/v1/sign-in", &user).await {
+            SignInResponse::Tokens(tokens) => self.token = Some(tokens.jwt),
+            SignInResponse::Challenge(_) => panic!("new users don't have 2FA"),
+        }
+        created.id
+    }
+
+    /// A request to the API, signed in if anyone is
+    pub fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self.http.request(method, format!("{}{}", self.url, path));
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    /// Send a request that must succeed, and read its JSON body
+    pub async fn send<T: DeserializeOwned>(&self, builder: reqwest::RequestBuilder) -> T {
+        let resp = builder.send().await.unwrap();
+        let status = resp.status();
+        let body = resp.text().await.unwrap();
+        assert!(status.is_success(), "{} {}", status, body);
+        serde_json::from_str(&body).unwrap_or_else(|e| panic!("unexpected body {}: {}", body, e))
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> T {
+        self.send(self.request(Method::GET, path)).await
+    }
+
+    pub async fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> T {
+        self.send(self.request(Method::POST, path).json(body)).await
+    }
+
+    /// One pass of the background worker, as if every website's interval had run out
+    pub async fn worker_cycle(&self) {
+        // Half a tick of slack makes any interval up to the longest allowed due
+        let tick_seconds = 2 * api::monitor::MAX_CHECK_INTERVAL_SECONDS as u64;
+        api::worker::check_all_websites(self.store.clone(), tick_seconds).await;
+    }
+
+    /// One pass of the background worker at its production tick, which only checks
+    /// websites whose interval has run out
+    pub async fn scheduled_worker_cycle(&self) {
+        api::worker::check_all_websites(self.store.clone(), 15).await;
+    }
+}
+
+/// How a mock target answers
+#[derive(Clone, Copy, Debug)]
+pub enum Behavior {
+    /// 200 right away
+    Up,
+    /// 503 right away
+    Down,
+    /// 200 after a delay
+    Slow(Duration),
+}
+
+/// A local website for the API to check, answering every path as told
+pub struct MockTarget {
+    pub url: String,
+    behavior: Arc<Mutex<Behavior>>,
+    hits: Arc<AtomicUsize>,
+}
+
+impl MockTarget {
+    pub async fn start(behavior: Behavior) -> MockTarget {
+        let behavior = Arc::new(Mutex::new(behavior));
+        let hits = Arc::new(AtomicUsize::new(0));
+        let endpoint = {
+            let behavior = behavior.clone();
+            let hits = hits.clone();
+            poem::endpoint::make(move |_| {
+                hits.fetch_add(1, Ordering::SeqCst);
+                let behavior = *behavior.lock().unwrap();
+                async move {
+                    let status = match behavior {
+                        Behavior::Up => StatusCode::OK,
+                        Behavior::Down => StatusCode::SERVICE_UNAVAILABLE,
+                        Behavior::Slow(delay) => {
+                            tokio::time::sleep(delay).await;
+                            StatusCode::OK
+                        }
+                    };
+                    Response::builder().status(status).body(format!("{:?}", behavior))
+                }
+            })
+        };
+        let acceptor = TcpListener::bind("127.0.0.1:0").into_acceptor().await.unwrap();
+        let addr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(endpoint));
+        MockTarget { url: format!("http://{}/health", addr), behavior, hits }
+    }
+
+    pub fn set(&self, behavior: Behavior) {
+        *self.behavior.lock().unwrap() = behavior;
+    }
+
+    /// Requests received so far
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+}
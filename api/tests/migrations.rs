@@ -0,0 +1,38 @@
+//! Startup's schema check against a real database. See `harness` for what these need
+//! to run.
+
+mod harness;
+
+use diesel::RunQueryDsl;
+use harness::TestApp;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn refuses_a_schema_newer_than_the_build() {
+    let app = TestApp::start().await;
+    let mut store = app.store.lock().unwrap();
+    assert!(store.prepare_schema(false).unwrap().is_empty(), "freshly migrated");
+
+    diesel::sql_query("INSERT INTO __diesel_schema_migrations (version) VALUES ('29991231000000')")
+    .execute(&mut store.conn)
+    .unwrap();
+    let status = store.schema_status().unwrap();
+    assert!(status.is_newer());
+    assert_eq!(status.unknown, vec!["29991231000000"]);
+    let err = store.prepare_schema(true).expect_err("a newer schema is refused even when migrating");
+    assert!(err.to_string().contains("29991231000000"), "{}", err);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn applies_pending_migrations_only_when_asked() {
+    let app = TestApp::start().await;
+    let mut store = app.store.lock().unwrap();
+    let reverted = store.revert_last_migration().unwrap();
+    assert_eq!(store.schema_status().unwrap().pending().len(), 1);
+
+    let err = store.prepare_schema(false).expect_err("pending migrations are refused by default");
+    assert!(err.to_string().contains("1 pending migration"), "{}", err);
+    assert_eq!(store.prepare_schema(true).unwrap(), vec![reverted]);
+    assert!(store.schema_status().unwrap().pending().is_empty());
+}
@@ -0,0 +1,67 @@
+//! Which notification channels a website's incidents are routed to, by tag, against a
+//! real database. See `harness` for what these need to run.
+
+mod harness;
+
+use std::collections::BTreeMap;
+
+use api::request_inputs::{CreateNotificationChannelInput, CreateWebsiteInput};
+use api::request_outputs::{CreateWebsiteOutput, NotificationChannelItem};
+use harness::TestApp;
+
+fn tags(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+async fn add_website(app: &TestApp, url: &str, website_tags: &[(&str, &str)]) -> String {
+    let input = CreateWebsiteInput {
+        url: url.to_string(),
+        organization_id: None,
+        name: None,
+        description: None,
+        tags: tags(website_tags),
+        check_interval_seconds: None,
+        assertions: Default::default(),
+    };
+    app.post::<_, CreateWebsiteOutput>("/v1/websites", &input).await.id
+}
+
+/// Names of the channels an incident on the website would be sent to
+fn routed_to(app: &TestApp, website_id: &str) -> Vec<String> {
+    let mut store = app.store.lock().unwrap();
+    let website = store.get_website(website_id.to_string()).unwrap();
+    let mut names: Vec<String> = store.notification_channels_for(website.organization_id, website.tags)
+        .unwrap()
+        .into_iter()
+        .map(|channel| channel.name)
+        .collect();
+    names.sort();
+    names
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn channels_match_websites_carrying_all_their_tags() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let payments_prod = add_website(&app, "https://pay.example.com", &[("team", "payments"), ("env", "prod")]).await;
+    let payments_staging = add_website(&app, "https://pay-staging.example.com", &[("team", "payments"), ("env", "staging")]).await;
+    let search = add_website(&app, "https://search.example.com", &[("team", "search")]).await;
+    let untagged = add_website(&app, "https://plain.example.com", &[]).await;
+
+    let org_id = app.store.lock().unwrap().get_website(untagged.clone()).unwrap().organization_id;
+    let path = format!("/v1/organizations/{}/notification-channels", org_id);
+    for (name, match_tags) in [
+        ("everything", tags(&[])),
+        ("payments", tags(&[("team", "payments")])),
+        ("payments-prod", tags(&[("team", "payments"), ("env", "prod")])),
+    ] {
+        let input = CreateNotificationChannelInput { name: name.to_string(), url: "https://hooks.example.com".to_string(), match_tags };
+        app.post::<_, NotificationChannelItem>(&path, &input).await;
+    }
+
+    assert_eq!(routed_to(&app, &payments_prod), ["everything", "payments", "payments-prod"]);
+    assert_eq!(routed_to(&app, &payments_staging), ["everything", "payments"]);
+    assert_eq!(routed_to(&app, &search), ["everything"]);
+    assert_eq!(routed_to(&app, &untagged), ["everything"]);
+}
@@ -0,0 +1,59 @@
+//! Organizations and their invitations, through the routes and a real database. See
+//! `harness` for what these need to run.
+
+mod harness;
+
+use api::request_inputs::{CreateInvitationInput, CreateOrganizationInput, CreateUserInput};
+use api::request_outputs::{CreateUserOutput, InvitationItem, ListInvitationsOutput, OrganizationItem};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use harness::TestApp;
+use reqwest::{Method, StatusCode};
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn a_second_pending_invitation_is_a_conflict() {
+    let mut app = TestApp::start().await;
+    let invitee = CreateUserInput { username: "invitee".to_string(), password: "password123".to_string() };
+    app.post::<_, CreateUserOutput>("/v1/sign-up", &invitee).await;
+    app.sign_up().await;
+    let org: OrganizationItem = app.post("/v1/organizations", &CreateOrganizationInput { name: "Acme".to_string() }).await;
+
+    let path = format!("/v1/organizations/{}/invitations", org.id);
+    let invitation = CreateInvitationInput { username: "invitee".to_string(), role: "viewer".to_string() };
+    let first = app.request(Method::POST, &path).json(&invitation).send().await.unwrap();
+    assert_eq!(first.status(), StatusCode::CREATED);
+    let second = app.request(Method::POST, &path).json(&invitation).send().await.unwrap();
+    assert_eq!(second.status(), StatusCode::CONFLICT);
+    let problem: serde_json::Value = second.json().await.unwrap();
+    assert_eq!(problem["message"], "An invitation is already pending");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn an_expired_invitation_does_not_block_a_new_one() {
+    let mut app = TestApp::start().await;
+    let invitee = CreateUserInput { username: "invitee".to_string(), password: "password123".to_string() };
+    app.post::<_, CreateUserOutput>("/v1/sign-up", &invitee).await;
+    app.sign_up().await;
+    let org: OrganizationItem = app.post("/v1/organizations", &CreateOrganizationInput { name: "Acme".to_string() }).await;
+
+    let path = format!("/v1/organizations/{}/invitations", org.id);
+    let invitation = CreateInvitationInput { username: "invitee".to_string(), role: "viewer".to_string() };
+    let first = app.request(Method::POST, &path).json(&invitation).send().await.unwrap();
+    assert_eq!(first.status(), StatusCode::CREATED);
+    let first: InvitationItem = first.json().await.unwrap();
+    {
+        use store::schema::organization_invitation::dsl::*;
+        let yesterday = chrono::Utc::now().naive_utc() - chrono::Duration::days(1);
+        diesel::update(organization_invitation.filter(id.eq(&first.id)))
+            .set(expires_at.eq(yesterday))
+            .execute(&mut app.store.lock().unwrap().conn)
+            .unwrap();
+    }
+
+    let second = app.request(Method::POST, &path).json(&invitation).send().await.unwrap();
+    assert_eq!(second.status(), StatusCode::CREATED);
+    let listed: ListInvitationsOutput = app.get(&path).await;
+    assert_eq!(listed.items.len(), 1);
+    assert_ne!(listed.items[0].id, first.id);
+}
@@ -0,0 +1,100 @@
+//! Listing websites with filters, search, sorting and pages, through the routes and a
+//! real database. See `harness` for what these need to run.
+
+mod harness;
+
+use std::collections::BTreeMap;
+
+use api::request_inputs::{CreateWebsiteInput, PauseWebsiteInput};
+use api::request_outputs::{CreateWebsiteOutput, ListWebsiteOutput};
+use harness::{Behavior, MockTarget, TestApp};
+
+async fn add_website(app: &TestApp, url: &str, name: &str, tags: &[(&str, &str)]) -> String {
+    let input = CreateWebsiteInput {
+        url: url.to_string(),
+        organization_id: None,
+        name: Some(name.to_string()),
+        description: None,
+        tags: tags.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<BTreeMap<_, _>>(),
+        check_interval_seconds: None,
+        assertions: Default::default(),
+    };
+    app.post::<_, CreateWebsiteOutput>("/v1/websites", &input).await.id
+}
+
+/// Names of the listed websites, in order
+async fn names(app: &TestApp, query: &str) -> Vec<String> {
+    let list: ListWebsiteOutput = app.get(&format!("/v1/websites?{}", query)).await;
+    list.items.into_iter().map(|item| item.name.unwrap()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn filters_by_status() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let up = MockTarget::start(Behavior::Up).await;
+    let down = MockTarget::start(Behavior::Down).await;
+    add_website(&app, &up.url, "up", &[]).await;
+    add_website(&app, &down.url, "down", &[]).await;
+    let paused = add_website(&app, &up.url, "paused", &[]).await;
+    app.worker_cycle().await;
+    app.post::<_, serde_json::Value>(&format!("/v1/websites/{}/pause", paused), &PauseWebsiteInput { resume_at: None }).await;
+    add_website(&app, &up.url, "pending", &[]).await;
+
+    assert_eq!(names(&app, "status=up").await, ["up"]);
+    assert_eq!(names(&app, "status=down").await, ["down"]);
+    assert_eq!(names(&app, "status=paused").await, ["paused"]);
+    assert_eq!(names(&app, "status=pending").await, ["pending"]);
+    assert_eq!(names(&app, "sort=url&order=asc").await.len(), 4);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn searches_wildcards_literally_and_filters_by_tag() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    add_website(&app, "https://one.example.com", "100% uptime", &[("team", "payments"), ("env", "prod")]).await;
+    add_website(&app, "https://two.example.com", "100 uptime", &[("team", "payments"), ("env", "staging")]).await;
+    add_website(&app, "https://three.example.com", "a_b", &[("team", "search")]).await;
+    add_website(&app, "https://four.example.com", "axb", &[]).await;
+
+    // `%` and `_` match themselves, not any run of characters or any one character
+    assert_eq!(names(&app, "q=100%25").await, ["100% uptime"]);
+    assert_eq!(names(&app, "q=a_b").await, ["a_b"]);
+    assert_eq!(names(&app, "q=UPTIME&sort=url&order=asc").await, ["100% uptime", "100 uptime"]);
+    assert_eq!(names(&app, "q=four.example").await, ["axb"]);
+
+    assert_eq!(names(&app, "tag=team%3Dpayments&sort=url&order=asc").await, ["100% uptime", "100 uptime"]);
+    assert_eq!(names(&app, "tag=team%3Dpayments,env%3Dprod").await, ["100% uptime"]);
+    assert!(names(&app, "tag=team%3Dnobody").await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn sorts_by_uptime_with_unchecked_websites_last_and_pages() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let always_up = MockTarget::start(Behavior::Up).await;
+    let flapping = MockTarget::start(Behavior::Up).await;
+    let always_down = MockTarget::start(Behavior::Down).await;
+    add_website(&app, &always_up.url, "100%", &[]).await;
+    add_website(&app, &flapping.url, "50%", &[]).await;
+    add_website(&app, &always_down.url, "0%", &[]).await;
+    app.worker_cycle().await;
+    flapping.set(Behavior::Down);
+    app.worker_cycle().await;
+    add_website(&app, &always_up.url, "unchecked", &[]).await;
+
+    let list: ListWebsiteOutput = app.get("/v1/websites?sort=uptime&order=desc").await;
+    let uptimes: Vec<_> = list.items.iter().map(|item| item.uptime_24h).collect();
+    assert_eq!(uptimes, [Some(100.0), Some(50.0), Some(0.0), None]);
+    assert_eq!(names(&app, "sort=uptime&order=asc").await, ["0%", "50%", "100%", "unchecked"]);
+
+    let first: ListWebsiteOutput = app.get("/v1/websites?sort=uptime&order=asc&limit=2").await;
+    assert_eq!(first.total, 4);
+    assert_eq!(first.next_offset, Some(2));
+    let second: ListWebsiteOutput = app.get("/v1/websites?sort=uptime&order=asc&limit=2&offset=2").await;
+    assert_eq!(second.items.iter().map(|item| item.name.as_deref().unwrap()).collect::<Vec<_>>(), ["100%", "unchecked"]);
+    assert_eq!(second.next_offset, None);
+}
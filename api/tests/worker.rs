@@ -0,0 +1,154 @@
+//! From sign-up to the background worker's checks, through the routes and a real
+//! database. See `harness` for what these need to run.
+
+mod harness;
+
+use std::time::Duration;
+
+use api::request_inputs::{CheckAssertions, CreateWebsiteInput, PauseWebsiteInput};
+use api::request_outputs::{CreateWebsiteOutput, GetWebsiteOutput, WebsiteHistoryOutput, WebsiteStatusOutput};
+use harness::{Behavior, MockTarget, TestApp};
+
+async fn add_website(app: &TestApp, url: &str, assertions: CheckAssertions) -> String {
+    let input = CreateWebsiteInput {
+        url: url.to_string(),
+        organization_id: None,
+        name: None,
+        description: None,
+        tags: Default::default(),
+        check_interval_seconds: None,
+        assertions,
+    };
+    app.post::<_, CreateWebsiteOutput>("/v1/websites", &input).await.id
+}
+
+async fn history(app: &TestApp, website_id: &str) -> WebsiteHistoryOutput {
+    app.get(&format!("/v1/websites/{}/history", website_id)).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn worker_records_checks_and_tracks_incidents() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let target = MockTarget::start(Behavior::Up).await;
+    let id = add_website(&app, &target.url, CheckAssertions::default()).await;
+
+    app.worker_cycle().await;
+    assert_eq!(target.hits(), 1);
+    let status: WebsiteStatusOutput = app.get(&format!("/v1/websites/{}/status", id)).await;
+    assert_eq!(status.is_up, Some(true));
+    assert!(status.last_checked.is_some());
+    let checks = history(&app, &id).await.items;
+    assert_eq!(checks.len(), 1);
+    assert!(checks[0].is_up);
+    assert_eq!(checks[0].status_code, Some(200));
+
+    target.set(Behavior::Down);
+    app.worker_cycle().await;
+    let status: WebsiteStatusOutput = app.get(&format!("/v1/websites/{}/status", id)).await;
+    assert_eq!(status.is_up, Some(false));
+    assert!(status.last_down_time.is_some());
+    let checks = history(&app, &id).await.items;
+    assert_eq!(checks.len(), 2);
+    assert!(!checks[0].is_up, "newest first");
+    assert_eq!(checks[0].status_code, Some(503));
+    assert_eq!(checks[0].error_message.as_deref(), Some("HTTP 503"));
+    let website: GetWebsiteOutput = app.get(&format!("/v1/websites/{}", id)).await;
+    assert_eq!(website.website.status, "down");
+    let incident = website.website.open_incident.expect("going down opens an incident");
+    assert_eq!(incident.cause.as_deref(), Some("HTTP 503"));
+
+    target.set(Behavior::Up);
+    app.worker_cycle().await;
+    let website: GetWebsiteOutput = app.get(&format!("/v1/websites/{}", id)).await;
+    assert_eq!(website.website.status, "up");
+    assert!(website.website.open_incident.is_none(), "coming back up resolves the incident");
+    assert_eq!(target.hits(), 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn slow_responses_fail_the_response_time_assertion() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let target = MockTarget::start(Behavior::Slow(Duration::from_millis(300))).await;
+    let assertions = CheckAssertions { max_response_time_ms: Some(100), ..Default::default() };
+    let id = add_website(&app, &target.url, assertions).await;
+
+    app.worker_cycle().await;
+    let checks = history(&app, &id).await.items;
+    assert_eq!(checks.len(), 1);
+    assert!(!checks[0].is_up);
+    assert_eq!(checks[0].status_code, Some(200));
+    assert!(checks[0].response_time_ms.unwrap() >= 300);
+    assert!(checks[0].error_message.as_deref().unwrap().contains("over the 100ms limit"));
+
+    target.set(Behavior::Up);
+    app.worker_cycle().await;
+    let status: WebsiteStatusOutput = app.get(&format!("/v1/websites/{}/status", id)).await;
+    assert_eq!(status.is_up, Some(true));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn worker_waits_for_each_websites_interval() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let target = MockTarget::start(Behavior::Up).await;
+    let id = add_website(&app, &target.url, CheckAssertions::default()).await;
+
+    app.scheduled_worker_cycle().await;
+    assert_eq!(target.hits(), 1, "never checked, so due straight away");
+    app.scheduled_worker_cycle().await;
+    assert_eq!(target.hits(), 1, "checked moments ago, so not due for another minute");
+    assert_eq!(history(&app, &id).await.items.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn worker_skips_paused_websites() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let target = MockTarget::start(Behavior::Down).await;
+    let id = add_website(&app, &target.url, CheckAssertions::default()).await;
+
+    let paused: GetWebsiteOutput = app.post(&format!("/v1/websites/{}/pause", id), &PauseWebsiteInput { resume_at: None }).await;
+    assert_eq!(paused.website.status, "paused");
+    app.worker_cycle().await;
+    assert_eq!(target.hits(), 0);
+    assert!(history(&app, &id).await.items.is_empty());
+
+    app.post::<_, GetWebsiteOutput>(&format!("/v1/websites/{}/resume", id), &serde_json::json!({})).await;
+    app.worker_cycle().await;
+    assert_eq!(target.hits(), 1);
+    let status: WebsiteStatusOutput = app.get(&format!("/v1/websites/{}/status", id)).await;
+    assert_eq!(status.is_up, Some(false));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn pausing_during_a_check_drops_its_result() {
+    let mut app = TestApp::start().await;
+    app.sign_up().await;
+    let target = MockTarget::start(Behavior::Down).await;
+    let id = add_website(&app, &target.url, CheckAssertions::default()).await;
+    app.worker_cycle().await;
+    let website: GetWebsiteOutput = app.get(&format!("/v1/websites/{}", id)).await;
+    assert!(website.website.open_incident.is_some());
+
+    target.set(Behavior::Slow(Duration::from_millis(500)));
+    let cycle = tokio::spawn({
+        let store = app.store.clone();
+        async move { api::worker::check_all_websites(store, 2 * api::monitor::MAX_CHECK_INTERVAL_SECONDS as u64).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(target.hits(), 2, "the check is under way");
+    app.post::<_, GetWebsiteOutput>(&format!("/v1/websites/{}/pause", id), &PauseWebsiteInput { resume_at: None }).await;
+    cycle.await.unwrap();
+
+    let website: GetWebsiteOutput = app.get(&format!("/v1/websites/{}", id)).await;
+    assert_eq!(website.website.status, "paused");
+    assert!(website.website.open_incident.is_none(), "pausing resolved the incident and it stays resolved");
+    assert_eq!(history(&app, &id).await.items.len(), 1, "only the check from before the pause");
+}
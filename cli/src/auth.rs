@@ -0,0 +1,64 @@
+use std::io::{BufRead, Write};
+
+use api::request_inputs::{CreateUserInput, TwoFactorSignInInput};
+use api::request_outputs::{SignInOutput, SignInResponse};
+
+use crate::client::{Auth, CliError, Client, Result};
+use crate::credentials::{self, Credentials};
+
+fn prompt(label: &str) -> Result<String> {
+    print!("{}", label);
+    std::io::stdout().flush().ok();
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)
+        .map_err(|e| CliError(format!("Failed to read input: {}", e)))?;
+    Ok(line.trim().to_string())
+}
+
+/// Sign in, answering a 2FA challenge if the account has one, and cache the tokens
+pub fn login(url: &str, username: Option<String>, password_stdin: bool) -> Result<()> {
+    let username = match username {
+        Some(username) => username,
+        None => prompt("Username: ")?,
+    };
+    let password = if password_stdin {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)
+            .map_err(|e| CliError(format!("Failed to read password: {}", e)))?;
+        line.trim_end_matches(['\r', '\n']).to_string()
+    } else {
+        rpassword::prompt_password("Password: ").map_err(|e| CliError(format!("Failed to read password: {}", e)))?
+    };
+
+    let client = Client::new(url, Auth::None);
+    let tokens: SignInOutput = match client.post("/v1/sign-in", &CreateUserInput { username: username.clone(), password })? {
+        SignInResponse::Tokens(tokens) => tokens,
+        SignInResponse::Challenge(challenge) => {
+            let code = prompt("Authentication code: ")?;
+            client.post("/v1/sign-in/2fa", &TwoFactorSignInInput { challenge_token: challenge.challenge_token, code })?
+        }
+    };
+    credentials::save(&Credentials {
+        url: client.base_url().to_string(),
+        username: username.clone(),
+        access_token: tokens.jwt,
+        refresh_token: tokens.refresh_token,
+    })?;
+    println!("Signed in to {} as {}", client.base_url(), username);
+    Ok(())
+}
+
+/// Revoke the cached session on the server and forget it locally
+pub fn logout(url: &str) -> Result<()> {
+    let Some(cached) = credentials::load(url.trim_end_matches('/')) else {
+        println!("Not signed in");
+        return Ok(());
+    };
+    let client = Client::new(url, Auth::Session(cached));
+    if let Err(e) = client.post::<_, serde_json::Value>("/v1/sign-out", &serde_json::json!({})) {
+        eprintln!("warning: couldn't revoke the session on the server: {}", e);
+    }
+    credentials::remove()?;
+    println!("Signed out");
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+
+use api::request_inputs::RefreshTokenInput;
+use api::request_outputs::SignInOutput;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::credentials::{self, Credentials};
+
+/// What went wrong, ready to print
+#[derive(Debug)]
+pub struct CliError(pub String);
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<reqwest::Error> for CliError {
+    fn from(e: reqwest::Error) -> CliError {
+        CliError(format!("Request failed: {}", e))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, CliError>;
+
+/// How requests are authenticated
+pub enum Auth {
+    None,
+    /// A token or API key given on the command line; used as it is
+    Token(String),
+    /// Cached `login` credentials, refreshed when the access token runs out
+    Session(Credentials),
+}
+
+/// A thin blocking wrapper over the API that turns problem responses into `CliError`s
+pub struct Client {
+    base_url: String,
+    auth: RefCell<Auth>,
+    http: reqwest::blocking::Client,
+}
+
+impl Client {
+    pub fn new(base_url: &str, auth: Auth) -> Client {
+        Client {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            auth: RefCell::new(auth),
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn request(&self, http: &reqwest::blocking::Client, method: Method, path: &str) -> RequestBuilder {
+        let builder = http.request(method, format!("{}{}", self.base_url, path));
+        match &*self.auth.borrow() {
+            Auth::None => builder,
+            Auth::Token(token) => builder.bearer_auth(token),
+            Auth::Session(credentials) => builder.bearer_auth(&credentials.access_token),
+        }
+    }
+
+    /// Swap a spent access token for a new pair, keeping the cache up to date.
+    /// Returns whether there was anything to refresh.
+    fn refresh(&self) -> Result<bool> {
+        let refresh_token = match &*self.auth.borrow() {
+            Auth::Session(credentials) => credentials.refresh_token.clone(),
+            _ => return Ok(false),
+        };
+        let resp = self.http.post(format!("{}/v1/tokens/refresh", self.base_url))
+            .json(&RefreshTokenInput { refresh_token })
+            .send()?;
+        if !resp.status().is_success() {
+            return Err(CliError("Your session has expired; run `better-uptime login` again".to_string()));
+        }
+        let tokens: SignInOutput = resp.json()?;
+        if let Auth::Session(credentials) = &mut *self.auth.borrow_mut() {
+            credentials.access_token = tokens.jwt;
+            credentials.refresh_token = tokens.refresh_token;
+            credentials::save(credentials)?;
+        }
+        Ok(true)
+    }
+
+    /// Send a request, refreshing the session once if the server no longer accepts the
+    /// access token, and failing with the server's own message on an error status
+    pub fn call(
+        &self,
+        method: Method,
+        path: &str,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Response> {
+        self.call_with(&self.http, method, path, build)
+    }
+
+    fn call_with(
+        &self,
+        http: &reqwest::blocking::Client,
+        method: Method,
+        path: &str,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Response> {
+        let mut resp = build(self.request(http, method.clone(), path)).send()?;
+        if resp.status() == StatusCode::UNAUTHORIZED && self.refresh()? {
+            resp = build(self.request(http, method, path)).send()?;
+        }
+        if resp.status().is_success() {
+            return Ok(resp);
+        }
+        if resp.status() == StatusCode::UNAUTHORIZED && matches!(*self.auth.borrow(), Auth::None) {
+            return Err(CliError("Not signed in; run `better-uptime login` or pass --token".to_string()));
+        }
+        let status = resp.status();
+        let message = resp.json::<serde_json::Value>().ok()
+            .and_then(|problem| problem.get("message")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| status.canonical_reason().unwrap_or("Request failed").to_string());
+        Err(CliError(format!("{} ({})", message, status.as_u16())))
+    }
+
+    /// A request whose response is read for as long as the server keeps it open
+    pub fn stream(&self, path: &str) -> Result<Response> {
+        let http = reqwest::blocking::Client::builder().timeout(None).build()?;
+        self.call_with(&http, Method::GET, path, |builder| builder.header(reqwest::header::ACCEPT, "text/event-stream"))
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        Ok(self.call(Method::GET, path, |builder| builder)?.json()?)
+    }
+
+    pub fn get_text(&self, path: &str) -> Result<String> {
+        Ok(self.call(Method::GET, path, |builder| builder)?.text()?)
+    }
+
+    pub fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        Ok(self.call(Method::POST, path, |builder| builder.json(body))?.json()?)
+    }
+
+    pub fn put<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        Ok(self.call(Method::PUT, path, |builder| builder.json(body))?.json()?)
+    }
+
+    pub fn delete(&self, path: &str) -> Result<()> {
+        self.call(Method::DELETE, path, |builder| builder)?;
+        Ok(())
+    }
+}
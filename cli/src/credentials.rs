@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::{CliError, Result};
+
+/// Tokens from `better-uptime login`, kept between runs so commands don't sign in each time
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Credentials {
+    /// The server the tokens belong to; they are ignored for any other `--api-url`
+    pub url: String,
+    pub username: String,
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+/// `$BETTER_UPTIME_CREDENTIALS`, or `credentials.json` in the user's config directory
+fn path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os("BETTER_UPTIME_CREDENTIALS") {
+        return Ok(PathBuf::from(path));
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join("better-uptime").join("credentials.json"))
+        .ok_or_else(|| CliError("Can't find a config directory to keep credentials in".to_string()))
+}
+
+/// The cached credentials for `url`, if any
+pub fn load(url: &str) -> Option<Credentials> {
+    let contents = std::fs::read_to_string(path().ok()?).ok()?;
+    serde_json::from_str::<Credentials>(&contents).ok().filter(|credentials| credentials.url == url)
+}
+
+pub fn save(credentials: &Credentials) -> Result<()> {
+    let path = path()?;
+    let failed = |e: std::io::Error| CliError(format!("Failed to save credentials to {}: {}", path.display(), e));
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(failed)?;
+    }
+    let contents = serde_json::to_string_pretty(credentials).unwrap_or_default();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&path).map_err(failed)?;
+    // `mode` only applies to a new file; tighten one left looser before writing tokens to it
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600)).map_err(failed)?;
+    std::io::Write::write_all(&mut file, contents.as_bytes()).map_err(failed)
+}
+
+pub fn remove() -> Result<()> {
+    let path = path()?;
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(CliError(format!("Failed to remove {}: {}", path.display(), e)))
+        }
+        _ => Ok(()),
+    }
+}
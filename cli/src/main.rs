@@ -0,0 +1,249 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::client::{Auth, Client, Result};
+use crate::websites::{HistoryFilters, ListFilters, WebsiteFields};
+
+mod auth;
+mod client;
+mod credentials;
+mod monitors;
+mod output;
+mod websites;
+
+#[derive(Parser)]
+#[command(name = "better-uptime", about = "Command-line client for the Better Uptime API")]
+struct Cli {
+    /// Base URL of the API server
+    #[arg(long, global = true, env = "BETTER_UPTIME_URL", default_value = "http://localhost:3000")]
+    api_url: String,
+    /// Access token or API key (`bu_...`); overrides the credentials saved by `login`
+    #[arg(long, global = true, env = "BETTER_UPTIME_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Sign in and save the tokens for later commands
+    Login {
+        #[arg(long)]
+        username: Option<String>,
+        /// Read the password from the first line of stdin instead of prompting
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Sign out and forget the saved tokens
+    Logout,
+    /// Add, change and inspect monitored websites
+    #[command(subcommand, alias = "website")]
+    Websites(WebsitesCommand),
+    /// Follow check results for all your websites as they happen
+    Tail,
+    /// Manage monitors as code with YAML or JSON documents
+    #[command(subcommand)]
+    Monitors(MonitorsCommand),
+}
+
+#[derive(Args)]
+struct FieldArgs {
+    /// Display name; an empty string clears it
+    #[arg(long)]
+    name: Option<String>,
+    /// An empty string clears it
+    #[arg(long)]
+    description: Option<String>,
+    /// A `key=value` tag; repeat for several. Replaces all existing tags.
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Seconds between checks
+    #[arg(long)]
+    interval: Option<i32>,
+}
+
+impl From<FieldArgs> for WebsiteFields {
+    fn from(args: FieldArgs) -> WebsiteFields {
+        WebsiteFields { name: args.name, description: args.description, tags: args.tags, interval: args.interval }
+    }
+}
+
+#[derive(Subcommand)]
+enum WebsitesCommand {
+    /// List websites with their current status
+    #[command(alias = "ls")]
+    List {
+        /// Only websites in this organization
+        #[arg(long)]
+        org: Option<String>,
+        /// up, down, pending or paused
+        #[arg(long)]
+        status: Option<String>,
+        /// Search the URL, name and description
+        #[arg(short, long)]
+        query: Option<String>,
+        /// A `key=value` tag websites must carry; repeat for several
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// time_added, url, status, response_time, last_checked or uptime
+        #[arg(long)]
+        sort: Option<String>,
+        #[arg(long)]
+        limit: Option<i64>,
+    },
+    /// Show one website in detail
+    Get { id: String },
+    /// Start monitoring a URL
+    Add {
+        url: String,
+        /// Organization id; defaults to your personal organization
+        #[arg(long)]
+        org: Option<String>,
+        #[command(flatten)]
+        fields: FieldArgs,
+    },
+    /// Change a website; options left out stay as they are
+    Update {
+        id: String,
+        #[arg(long)]
+        url: Option<String>,
+        #[command(flatten)]
+        fields: FieldArgs,
+    },
+    /// Stop monitoring a website and delete its history
+    #[command(alias = "rm")]
+    Delete { id: String },
+    /// Stop checking a website without losing its history
+    Pause {
+        id: String,
+        /// Resume by itself at this time (`YYYY-MM-DDTHH:MM:SS` UTC or RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+    },
+    /// Start checking a paused website again
+    Resume { id: String },
+    /// Check a website right now
+    Check { id: String },
+    /// Latest known status of a website
+    Status { id: String },
+    /// Recent checks of a website, newest first
+    History {
+        id: String,
+        #[arg(long)]
+        limit: Option<i64>,
+        /// Only failed checks
+        #[arg(long)]
+        failures: bool,
+        /// Only checks at or after this time
+        #[arg(long)]
+        from: Option<String>,
+        /// Only checks before this time
+        #[arg(long)]
+        to: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MonitorsCommand {
+    /// Print an organization's monitors as a document
+    Export {
+        /// Organization id; defaults to your personal organization
+        #[arg(long)]
+        org: Option<String>,
+        #[arg(long, value_enum, default_value_t = DocumentFormat::Yaml)]
+        format: DocumentFormat,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Show what applying a document would change, without changing anything
+    Diff {
+        #[arg(long)]
+        org: Option<String>,
+        /// YAML or JSON document, or `-` for stdin
+        file: PathBuf,
+    },
+    /// Create, update and delete monitors until they match a document
+    Apply {
+        #[arg(long)]
+        org: Option<String>,
+        /// YAML or JSON document, or `-` for stdin
+        file: PathBuf,
+        /// Only show the changes, like `diff`
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DocumentFormat {
+    Yaml,
+    Json,
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let url = cli.api_url.trim_end_matches('/').to_string();
+    let json = cli.json;
+    match cli.command {
+        Command::Login { username, password_stdin } => return auth::login(&url, username, password_stdin),
+        Command::Logout => return auth::logout(&url),
+        _ => {}
+    }
+
+    let auth = match cli.token {
+        Some(token) => Auth::Token(token),
+        None => credentials::load(&url).map(Auth::Session).unwrap_or(Auth::None),
+    };
+    let client = Client::new(&url, auth);
+    match cli.command {
+        Command::Login { .. } | Command::Logout => unreachable!("handled before authenticating"),
+        Command::Websites(command) => match command {
+            WebsitesCommand::List { org, status, query, tags, sort, limit } => {
+                websites::list(&client, ListFilters { org, status, search: query, tags, sort, limit }, json)
+            }
+            WebsitesCommand::Get { id } => websites::get(&client, &id, json),
+            WebsitesCommand::Add { url, org, fields } => websites::add(&client, org, url, fields.into(), json),
+            WebsitesCommand::Update { id, url, fields } => websites::update(&client, &id, url, fields.into(), json),
+            WebsitesCommand::Delete { id } => websites::delete(&client, &id),
+            WebsitesCommand::Pause { id, until } => websites::pause(&client, &id, until, json),
+            WebsitesCommand::Resume { id } => websites::resume(&client, &id, json),
+            WebsitesCommand::Check { id } => websites::check(&client, &id, json),
+            WebsitesCommand::Status { id } => websites::status(&client, &id, json),
+            WebsitesCommand::History { id, limit, failures, from, to } => {
+                websites::history(&client, &id, HistoryFilters { limit, failures, from, to }, json)
+            }
+        },
+        Command::Tail => websites::tail(&client, json),
+        Command::Monitors(MonitorsCommand::Export { org, format, output }) => {
+            let org = monitors::organization(&client, org)?;
+            let format = match format {
+                DocumentFormat::Yaml => "yaml",
+                DocumentFormat::Json => "json",
+            };
+            monitors::export(&client, &org, format, output.as_deref())
+        }
+        Command::Monitors(MonitorsCommand::Diff { org, file }) => {
+            let org = monitors::organization(&client, org)?;
+            monitors::import(&client, &org, &file, true, json)
+        }
+        Command::Monitors(MonitorsCommand::Apply { org, file, dry_run }) => {
+            let org = monitors::organization(&client, org)?;
+            monitors::import(&client, &org, &file, dry_run, json)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use api::request_outputs::{ListOrganizationsOutput, MonitorPlanOutput};
+use reqwest::Method;
+
+use crate::client::{CliError, Client, Result};
+use crate::output;
+
+/// The organization to work on: the one given, or the caller's personal organization
+pub fn organization(client: &Client, org: Option<String>) -> Result<String> {
+    if let Some(org) = org {
+        return Ok(org);
+    }
+    let orgs: ListOrganizationsOutput = client.get("/v1/organizations")?;
+    orgs.items.into_iter()
+        .find(|org| org.personal)
+        .map(|org| org.id)
+        .ok_or_else(|| CliError("No personal organization found; pass --org".to_string()))
+}
+
+pub fn export(client: &Client, org: &str, format: &str, output: Option<&Path>) -> Result<()> {
+    let document = client.get_text(&format!("/v1/organizations/{}/monitors?format={}", org, format))?;
+    match output {
+        Some(path) => std::fs::write(path, document)
+            .map_err(|e| CliError(format!("Failed to write {}: {}", path.display(), e))),
+        None => {
+            print!("{}", document);
+            Ok(())
+        }
+    }
+}
+
+/// Read a document from a file, or from stdin for `-`
+fn read_document(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        return std::io::read_to_string(std::io::stdin())
+            .map_err(|e| CliError(format!("Failed to read stdin: {}", e)));
+    }
+    std::fs::read_to_string(path).map_err(|e| CliError(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+pub fn import(client: &Client, org: &str, path: &Path, dry_run: bool, json: bool) -> Result<()> {
+    let document = read_document(path)?;
+    let path = format!("/v1/organizations/{}/monitors?dry_run={}", org, dry_run);
+    let plan: MonitorPlanOutput = client
+        .call(Method::POST, &path, |builder| {
+            builder.header(reqwest::header::CONTENT_TYPE, "application/yaml").body(document.clone())
+        })?
+        .json()?;
+    if json {
+        output::print_json(&plan);
+    } else {
+        print_plan(&plan);
+    }
+    Ok(())
+}
+
+fn print_plan(plan: &MonitorPlanOutput) {
+    for key in &plan.create {
+        println!("+ {}", key);
+    }
+    for update in &plan.update {
+        println!("~ {}", update.key);
+        for change in &update.changes {
+            println!("    {}: {} -> {}", change.field, change.before, change.after);
+        }
+    }
+    for deletion in &plan.delete {
+        println!("- {} ({})", deletion.key, deletion.url);
+    }
+    let summary = format!(
+        "{} to create, {} to update, {} to delete, {} unchanged",
+        plan.create.len(), plan.update.len(), plan.delete.len(), plan.unchanged.len(),
+    );
+    if plan.dry_run {
+        println!("Dry run, nothing changed: {}", summary);
+    } else {
+        println!("Applied: {}", summary.replace(" to ", " "));
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+
+use api::request_inputs::{CheckAssertions, CreateWebsiteInput, PauseWebsiteInput, UpdateWebsiteInput};
+use api::request_outputs::{
+    CheckNowOutput, CreateWebsiteOutput, GetWebsiteOutput, ListWebsiteOutput, LiveEvent, WebsiteHistoryOutput, WebsiteItem,
+    WebsiteStatusOutput,
+};
+
+use crate::client::{CliError, Client, Result};
+use crate::output::{self, millis, or_dash};
+
+/// Filters for `websites list`, passed through to the API as they are
+pub struct ListFilters {
+    pub org: Option<String>,
+    pub status: Option<String>,
+    pub search: Option<String>,
+    pub tags: Vec<String>,
+    pub sort: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// What `websites add` and `websites update` can set besides the URL; `None` leaves a
+/// field alone
+pub struct WebsiteFields {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub interval: Option<i32>,
+}
+
+fn query_string(params: &[(&str, Option<String>)]) -> String {
+    let pairs: Vec<String> = params.iter()
+        .filter_map(|(name, value)| Some(format!("{}={}", name, encode(value.as_deref()?))))
+        .collect();
+    if pairs.is_empty() { String::new() } else { format!("?{}", pairs.join("&")) }
+}
+
+/// Percent-encode a query parameter value
+fn encode(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// `key=value` arguments as a tag map
+fn parse_tags(tags: &[String]) -> Result<BTreeMap<String, String>> {
+    tags.iter()
+        .map(|tag| {
+            tag.split_once('=')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .ok_or_else(|| CliError(format!("Invalid tag '{}', expected key=value", tag)))
+        })
+        .collect()
+}
+
+fn status_label(website: &WebsiteItem) -> String {
+    match (website.status.as_str(), &website.resume_at) {
+        ("paused", Some(until)) => format!("paused until {}", until),
+        (status, _) => status.to_string(),
+    }
+}
+
+fn print_websites(websites: &[WebsiteItem]) {
+    output::print_table(
+        &["ID", "NAME", "URL", "STATUS", "RESPONSE", "UPTIME 24H", "LAST CHECKED"],
+        websites.iter()
+            .map(|w| vec![
+                w.id.clone(),
+                or_dash(w.name.as_ref()),
+                w.url.clone(),
+                status_label(w),
+                millis(w.response_time_ms),
+                or_dash(w.uptime_24h.map(|u| format!("{:.2}%", u))),
+                or_dash(w.last_checked.as_ref()),
+            ])
+            .collect(),
+    );
+}
+
+fn print_website(website: &WebsiteItem) {
+    let tags = website.tags.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(", ");
+    let mut rows = vec![
+        ("ID", website.id.clone()),
+        ("Name", or_dash(website.name.as_ref())),
+        ("URL", website.url.clone()),
+        ("Description", or_dash(website.description.as_ref())),
+        ("Tags", if tags.is_empty() { "-".to_string() } else { tags }),
+        ("Status", status_label(website)),
+        ("Interval", format!("{}s", website.check_interval_seconds)),
+        ("Last checked", or_dash(website.last_checked.as_ref())),
+        ("Response time", millis(website.response_time_ms)),
+        ("Uptime 24h", or_dash(website.uptime_24h.map(|u| format!("{:.2}%", u)))),
+        ("Last down", or_dash(website.last_down_time.as_ref())),
+    ];
+    if website.assertions != CheckAssertions::default() {
+        rows.push(("Assertions", serde_json::to_string(&website.assertions).unwrap_or_default()));
+    }
+    if let Some(incident) = &website.open_incident {
+        rows.push(("Open incident", format!(
+            "since {}{}",
+            incident.started_at,
+            incident.cause.as_ref().map(|cause| format!(": {}", cause)).unwrap_or_default(),
+        )));
+    }
+    for (label, value) in rows {
+        println!("{:<14} {}", label, value);
+    }
+}
+
+pub fn list(client: &Client, filters: ListFilters, json: bool) -> Result<()> {
+    let query = query_string(&[
+        ("organization_id", filters.org),
+        ("status", filters.status),
+        ("q", filters.search),
+        ("tag", Some(filters.tags.join(",")).filter(|tags| !tags.is_empty())),
+        ("sort", filters.sort),
+        ("limit", filters.limit.map(|limit| limit.to_string())),
+    ]);
+    let websites: ListWebsiteOutput = client.get(&format!("/v1/websites{}", query))?;
+    if json {
+        output::print_json(&websites);
+    } else {
+        print_websites(&websites.items);
+        if websites.total > websites.items.len() as i64 {
+            println!("Showing {} of {} websites", websites.items.len(), websites.total);
+        }
+    }
+    Ok(())
+}
+
+fn show(website: &GetWebsiteOutput, json: bool) {
+    if json {
+        output::print_json(website);
+    } else {
+        print_website(&website.website);
+    }
+}
+
+pub fn get(client: &Client, id: &str, json: bool) -> Result<()> {
+    let website: GetWebsiteOutput = client.get(&format!("/v1/websites/{}", id))?;
+    show(&website, json);
+    Ok(())
+}
+
+pub fn add(client: &Client, org: Option<String>, url: String, fields: WebsiteFields, json: bool) -> Result<()> {
+    let input = CreateWebsiteInput {
+        url,
+        organization_id: org,
+        name: fields.name,
+        description: fields.description,
+        tags: parse_tags(&fields.tags)?,
+        check_interval_seconds: fields.interval,
+        assertions: CheckAssertions::default(),
+    };
+    let created: CreateWebsiteOutput = client.post("/v1/websites", &input)?;
+    if json {
+        output::print_json(&created);
+    } else {
+        println!("Added website {}", created.id);
+    }
+    Ok(())
+}
+
+pub fn update(client: &Client, id: &str, url: Option<String>, fields: WebsiteFields, json: bool) -> Result<()> {
+    // The API always takes the URL, so keep the current one unless a new one is given
+    let url = match url {
+        Some(url) => url,
+        None => client.get::<GetWebsiteOutput>(&format!("/v1/websites/{}", id))?.website.url,
+    };
+    let input = UpdateWebsiteInput {
+        url,
+        name: fields.name,
+        description: fields.description,
+        tags: if fields.tags.is_empty() { None } else { Some(parse_tags(&fields.tags)?) },
+        check_interval_seconds: fields.interval,
+        assertions: None,
+    };
+    client.put::<_, CreateWebsiteOutput>(&format!("/v1/websites/{}", id), &input)?;
+    get(client, id, json)
+}
+
+pub fn delete(client: &Client, id: &str) -> Result<()> {
+    client.delete(&format!("/v1/websites/{}", id))?;
+    println!("Deleted website {}", id);
+    Ok(())
+}
+
+pub fn pause(client: &Client, id: &str, until: Option<String>, json: bool) -> Result<()> {
+    let website: GetWebsiteOutput = client.post(
+        &format!("/v1/websites/{}/pause", id),
+        &PauseWebsiteInput { resume_at: until },
+    )?;
+    show(&website, json);
+    Ok(())
+}
+
+pub fn resume(client: &Client, id: &str, json: bool) -> Result<()> {
+    let website: GetWebsiteOutput = client.post(&format!("/v1/websites/{}/resume", id), &serde_json::json!({}))?;
+    show(&website, json);
+    Ok(())
+}
+
+pub fn check(client: &Client, id: &str, json: bool) -> Result<()> {
+    let result: CheckNowOutput = client.post(&format!("/v1/websites/{}/checks", id), &serde_json::json!({}))?;
+    if json {
+        output::print_json(&result);
+        return Ok(());
+    }
+    println!(
+        "{} {} {}{}",
+        if result.is_up { "UP  " } else { "DOWN" },
+        or_dash(result.status_code),
+        millis(result.response_time_ms),
+        result.error_message.map(|e| format!("  {}", e)).unwrap_or_default(),
+    );
+    Ok(())
+}
+
+pub fn status(client: &Client, id: &str, json: bool) -> Result<()> {
+    let status: WebsiteStatusOutput = client.get(&format!("/v1/websites/{}/status", id))?;
+    if json {
+        output::print_json(&status);
+        return Ok(());
+    }
+    output::print_table(
+        &["UP", "LAST CHECKED", "RESPONSE", "LAST DOWN"],
+        vec![vec![
+            or_dash(status.is_up),
+            or_dash(status.last_checked),
+            millis(status.response_time_ms),
+            or_dash(status.last_down_time),
+        ]],
+    );
+    Ok(())
+}
+
+pub struct HistoryFilters {
+    pub limit: Option<i64>,
+    pub failures: bool,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+pub fn history(client: &Client, id: &str, filters: HistoryFilters, json: bool) -> Result<()> {
+    let query = query_string(&[
+        ("limit", filters.limit.map(|limit| limit.to_string())),
+        ("is_up", filters.failures.then(|| "false".to_string())),
+        ("from", filters.from),
+        ("to", filters.to),
+    ]);
+    let history: WebsiteHistoryOutput = client.get(&format!("/v1/websites/{}/history{}", id, query))?;
+    if json {
+        output::print_json(&history);
+        return Ok(());
+    }
+    output::print_table(
+        &["CHECKED AT", "UP", "STATUS", "RESPONSE", "ERROR"],
+        history.items.iter()
+            .map(|check| vec![
+                check.checked_at.clone(),
+                check.is_up.to_string(),
+                or_dash(check.status_code),
+                millis(check.response_time_ms),
+                or_dash(check.error_message.as_ref()),
+            ])
+            .collect(),
+    );
+    Ok(())
+}
+
+/// Print check results and status changes as the server pushes them, until interrupted
+pub fn tail(client: &Client, json: bool) -> Result<()> {
+    let resp = client.stream("/v1/websites/live")?;
+    for line in BufReader::new(resp).lines() {
+        let line = line.map_err(|e| CliError(format!("Live feed interrupted: {}", e)))?;
+        let Some(data) = line.strip_prefix("data:") else {
+            continue;
+        };
+        let Ok(event) = serde_json::from_str::<LiveEvent>(data.trim()) else {
+            continue;
+        };
+        if json {
+            println!("{}", serde_json::to_string(&event).unwrap_or_default());
+            continue;
+        }
+        match event {
+            LiveEvent::Check { checked_at, url, is_up, response_time_ms, status_code, error_message, .. } => {
+                println!(
+                    "{}  {}  {:>4}  {:>7}  {}{}",
+                    checked_at,
+                    if is_up { "UP  " } else { "DOWN" },
+                    or_dash(status_code),
+                    millis(response_time_ms),
+                    url,
+                    error_message.map(|e| format!("  ({})", e)).unwrap_or_default(),
+                );
+            }
+            LiveEvent::StatusChange { changed_at, url, is_up, .. } => {
+                println!("{}  {} is now {}", changed_at, url, if is_up { "UP" } else { "DOWN" });
+            }
+        }
+    }
+    Ok(())
+}
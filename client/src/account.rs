@@ -0,0 +1,72 @@
+use api::request_inputs::{
+    AuditLogQuery, ChangePasswordInput, CreateApiKeyInput, DeleteAccountInput, PasswordResetConfirmInput,
+    PasswordResetRequestInput, TwoFactorCodeInput,
+};
+use api::request_outputs::{
+    AuditLogOutput, CreateApiKeyOutput, ListApiKeysOutput, RecoveryCodesOutput, SuccessMessage, TotpEnrollmentOutput,
+    TwoFactorStatusOutput,
+};
+use reqwest::Method;
+
+use crate::{Client, Result};
+
+/// The signed-in user's own account, second factor and API keys
+impl Client {
+    pub async fn change_password(&self, input: &ChangePasswordInput) -> Result<SuccessMessage> {
+        self.put("/v1/account/password", input).await
+    }
+
+    /// Answers the same whether or not the account exists
+    pub async fn request_password_reset(&self, input: &PasswordResetRequestInput) -> Result<SuccessMessage> {
+        self.post("/v1/password-reset", input).await
+    }
+
+    pub async fn confirm_password_reset(&self, input: &PasswordResetConfirmInput) -> Result<SuccessMessage> {
+        self.post("/v1/password-reset/confirm", input).await
+    }
+
+    /// Delete the account along with its personal organization and that organization's websites
+    pub async fn delete_account(&self, input: &DeleteAccountInput) -> Result<()> {
+        self.send(self.request(Method::DELETE, "/v1/account").json(input)).await?;
+        Ok(())
+    }
+
+    pub async fn two_factor_status(&self) -> Result<TwoFactorStatusOutput> {
+        self.get("/v1/2fa").await
+    }
+
+    /// Start enrolling a TOTP secret; it is enabled once `verify_totp` gets a valid code
+    pub async fn enroll_totp(&self) -> Result<TotpEnrollmentOutput> {
+        self.post_empty("/v1/2fa/enroll").await
+    }
+
+    pub async fn verify_totp(&self, input: &TwoFactorCodeInput) -> Result<RecoveryCodesOutput> {
+        self.post("/v1/2fa/verify", input).await
+    }
+
+    pub async fn disable_totp(&self, input: &TwoFactorCodeInput) -> Result<SuccessMessage> {
+        self.post("/v1/2fa/disable", input).await
+    }
+
+    pub async fn regenerate_recovery_codes(&self, input: &TwoFactorCodeInput) -> Result<RecoveryCodesOutput> {
+        self.post("/v1/2fa/recovery-codes", input).await
+    }
+
+    pub async fn list_api_keys(&self) -> Result<ListApiKeysOutput> {
+        self.get("/v1/api-keys").await
+    }
+
+    /// The key's secret is only in this response
+    pub async fn create_api_key(&self, input: &CreateApiKeyInput) -> Result<CreateApiKeyOutput> {
+        self.post("/v1/api-keys", input).await
+    }
+
+    pub async fn revoke_api_key(&self, key_id: &str) -> Result<()> {
+        self.delete(&format!("/v1/api-keys/{}", key_id)).await
+    }
+
+    /// One page of an organization's audit trail, newest first
+    pub async fn list_audit_log(&self, query: &AuditLogQuery) -> Result<AuditLogOutput> {
+        self.get_query("/v1/audit-log", query).await
+    }
+}
@@ -0,0 +1,42 @@
+use api::request_inputs::{CreateUserInput, RefreshTokenInput, TwoFactorSignInInput};
+use api::request_outputs::{CreateUserOutput, OidcAuthorizationOutput, SignInOutput, SignInResponse, SuccessMessage};
+
+use crate::{Client, Result};
+
+/// Accounts and sessions. Signing in doesn't change the client's token; pass the one
+/// you get back to `with_token` or `set_token`. The SSO login and callback are browser
+/// redirects and have no methods here.
+impl Client {
+    pub async fn sign_up(&self, input: &CreateUserInput) -> Result<CreateUserOutput> {
+        self.post("/v1/sign-up", input).await
+    }
+
+    /// Tokens, or a challenge to answer with `sign_in_two_factor` when the account has 2FA
+    pub async fn sign_in(&self, input: &CreateUserInput) -> Result<SignInResponse> {
+        self.post("/v1/sign-in", input).await
+    }
+
+    pub async fn sign_in_two_factor(&self, input: &TwoFactorSignInInput) -> Result<SignInOutput> {
+        self.post("/v1/sign-in/2fa", input).await
+    }
+
+    /// A new token pair; the refresh token passed in can't be used again
+    pub async fn refresh_token(&self, input: &RefreshTokenInput) -> Result<SignInOutput> {
+        self.post("/v1/tokens/refresh", input).await
+    }
+
+    /// Revoke the session the client's token belongs to
+    pub async fn sign_out(&self) -> Result<SuccessMessage> {
+        self.post_empty("/v1/sign-out").await
+    }
+
+    /// Revoke every session of the signed-in user
+    pub async fn sign_out_all(&self) -> Result<SuccessMessage> {
+        self.post_empty("/v1/sign-out-all").await
+    }
+
+    /// Where to send the user's browser to link their account to the SSO provider
+    pub async fn oidc_link(&self) -> Result<OidcAuthorizationOutput> {
+        self.post_empty("/v1/auth/oidc/link").await
+    }
+}
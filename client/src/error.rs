@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use api::request_outputs::Problem;
+use reqwest::{Response, StatusCode, header};
+
+/// Why a call failed. API errors carry the server's problem document, so callers can
+/// match on the variant for the status and on `Problem::code` for the exact reason.
+#[derive(Debug)]
+pub enum Error {
+    /// 400 or 422: the request was malformed or failed validation
+    BadRequest(Problem),
+    /// 401: no token, or one the server doesn't accept any more
+    Unauthorized(Problem),
+    /// 403: signed in, but not allowed to do this
+    Forbidden(Problem),
+    NotFound(Problem),
+    /// 409: e.g. a duplicate username, or checking a paused website
+    Conflict(Problem),
+    /// 429: try again after `retry_after`, when the server said how long to wait
+    RateLimited { problem: Problem, retry_after: Option<Duration> },
+    /// 5xx
+    Server(Problem),
+    /// Any other error status
+    Api(Problem),
+    /// The request never got a response, or the response body wasn't what was expected
+    Transport(reqwest::Error),
+    /// An event on the live feed couldn't be read
+    Decode(serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// The problem document, for errors the API reported
+    pub fn problem(&self) -> Option<&Problem> {
+        match self {
+            Error::BadRequest(problem)
+            | Error::Unauthorized(problem)
+            | Error::Forbidden(problem)
+            | Error::NotFound(problem)
+            | Error::Conflict(problem)
+            | Error::RateLimited { problem, .. }
+            | Error::Server(problem)
+            | Error::Api(problem) => Some(problem),
+            Error::Transport(_) | Error::Decode(_) => None,
+        }
+    }
+
+    /// The machine-readable reason, e.g. `not_found`
+    pub fn code(&self) -> Option<&str> {
+        self.problem().map(|problem| problem.code.as_str())
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Transport(e) => e.status().map(|status| status.as_u16()),
+            _ => self.problem().map(|problem| problem.status),
+        }
+    }
+
+    /// Read an error response. Bodies that aren't problem documents, such as those from a
+    /// proxy in front of the API, become one built from the status and the body text.
+    pub(crate) async fn from_response(resp: Response) -> Error {
+        let status = resp.status();
+        let retry_after = resp.headers().get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok()?.parse().ok())
+            .map(Duration::from_secs);
+        let body = match resp.text().await {
+            Ok(body) => body,
+            Err(e) => return Error::Transport(e),
+        };
+        let problem = serde_json::from_str::<Problem>(&body).unwrap_or_else(|_| {
+            let reason = status.canonical_reason().unwrap_or("Error");
+            Problem {
+                problem_type: "about:blank".to_string(),
+                title: reason.to_string(),
+                status: status.as_u16(),
+                code: reason.to_lowercase().replace(' ', "_"),
+                message: if body.trim().is_empty() { reason.to_string() } else { body.trim().to_string() },
+                request_id: None,
+            }
+        });
+        match status {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Error::BadRequest(problem),
+            StatusCode::UNAUTHORIZED => Error::Unauthorized(problem),
+            StatusCode::FORBIDDEN => Error::Forbidden(problem),
+            StatusCode::NOT_FOUND => Error::NotFound(problem),
+            StatusCode::CONFLICT => Error::Conflict(problem),
+            StatusCode::TOO_MANY_REQUESTS => Error::RateLimited { problem, retry_after },
+            s if s.is_server_error() => Error::Server(problem),
+            _ => Error::Api(problem),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "request failed: {}", e),
+            Error::Decode(e) => write!(f, "invalid live event: {}", e),
+            _ => {
+                let problem = self.problem().expect("API errors carry a problem");
+                write!(f, "{} ({} {})", problem.message, problem.status, problem.code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(e) => Some(e),
+            Error::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Error {
+        Error::Transport(e)
+    }
+}
@@ -0,0 +1,124 @@
+//! An async client for the Better Uptime API, for services that manage their own
+//! monitors. Requests and responses are the `api` crate's own types, re-exported here
+//! as `request_inputs` and `request_outputs`, so the two can't drift apart.
+//!
+//! ```no_run
+//! # async fn run() -> client::Result<()> {
+//! use client::Client;
+//! use client::request_inputs::CreateWebsiteInput;
+//!
+//! let client = Client::new("https://uptime.example.com").with_token("bu_...");
+//! let created = client.create_website(&CreateWebsiteInput {
+//!     url: "https://payments.internal/healthz".to_string(),
+//!     organization_id: None,
+//!     name: Some("payments".to_string()),
+//!     description: None,
+//!     tags: Default::default(),
+//!     check_interval_seconds: None,
+//!     assertions: Default::default(),
+//! }).await?;
+//! let status = client.website_status(&created.id).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::{Method, RequestBuilder, Response};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub use api::{request_inputs, request_outputs};
+
+pub use crate::error::{Error, Result};
+
+mod account;
+mod auth;
+mod error;
+mod organizations;
+mod websites;
+
+/// A handle on one API server. Cloning is cheap and shares the connection pool.
+#[derive(Clone)]
+pub struct Client {
+    base_url: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl Client {
+    pub fn new(base_url: &str) -> Client {
+        Client::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    /// Send requests through `http`, e.g. one with timeouts or a proxy configured.
+    /// Leave out a total timeout if you use `live_events`.
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Client {
+        Client { base_url: base_url.trim_end_matches('/').to_string(), token: None, http }
+    }
+
+    /// Authenticate with an access token from `sign_in` or an API key (`bu_...`)
+    pub fn with_token(mut self, token: impl Into<String>) -> Client {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Swap the token, e.g. after `refresh_token`; `None` signs requests out
+    pub fn set_token(&mut self, token: Option<String>) {
+        self.token = token;
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let builder = self.http.request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    /// Send a request, turning an error status into an `Error`
+    async fn send(&self, builder: RequestBuilder) -> Result<Response> {
+        let resp = builder.send().await?;
+        if resp.status().is_success() {
+            Ok(resp)
+        } else {
+            Err(Error::from_response(resp).await)
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        Ok(self.send(self.request(Method::GET, path)).await?.json().await?)
+    }
+
+    async fn get_query<Q: Serialize, T: DeserializeOwned>(&self, path: &str, query: &Q) -> Result<T> {
+        Ok(self.send(self.request(Method::GET, path).query(query)).await?.json().await?)
+    }
+
+    async fn get_text<Q: Serialize>(&self, path: &str, query: &Q) -> Result<String> {
+        Ok(self.send(self.request(Method::GET, path).query(query)).await?.text().await?)
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        Ok(self.send(self.request(Method::POST, path).json(body)).await?.json().await?)
+    }
+
+    /// A `POST` that takes no body
+    async fn post_empty<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        Ok(self.send(self.request(Method::POST, path)).await?.json().await?)
+    }
+
+    async fn put<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        Ok(self.send(self.request(Method::PUT, path).json(body)).await?.json().await?)
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        self.send(self.request(Method::DELETE, path)).await?;
+        Ok(())
+    }
+}
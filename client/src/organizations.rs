@@ -0,0 +1,95 @@
+use api::request_inputs::{
+    CreateInvitationInput, CreateNotificationChannelInput, CreateOrganizationInput, ExportMonitorsQuery,
+    ImportMonitorsQuery, MonitorDocument, UpdateMemberInput,
+};
+use api::request_outputs::{
+    InvitationItem, ListInvitationsOutput, ListMembersOutput, ListNotificationChannelsOutput, ListOrganizationsOutput,
+    MemberItem, MonitorPlanOutput, NotificationChannelItem, OrganizationItem,
+};
+use reqwest::Method;
+
+use crate::{Client, Result};
+
+/// Organizations, their members, invitations, notification channels and monitors
+impl Client {
+    /// Organizations the caller belongs to, with their role in each
+    pub async fn list_organizations(&self) -> Result<ListOrganizationsOutput> {
+        self.get("/v1/organizations").await
+    }
+
+    pub async fn create_organization(&self, input: &CreateOrganizationInput) -> Result<OrganizationItem> {
+        self.post("/v1/organizations", input).await
+    }
+
+    pub async fn list_members(&self, org_id: &str) -> Result<ListMembersOutput> {
+        self.get(&format!("/v1/organizations/{}/members", org_id)).await
+    }
+
+    pub async fn update_member(&self, org_id: &str, user_id: &str, input: &UpdateMemberInput) -> Result<MemberItem> {
+        self.put(&format!("/v1/organizations/{}/members/{}", org_id, user_id), input).await
+    }
+
+    pub async fn remove_member(&self, org_id: &str, user_id: &str) -> Result<()> {
+        self.delete(&format!("/v1/organizations/{}/members/{}", org_id, user_id)).await
+    }
+
+    pub async fn list_organization_invitations(&self, org_id: &str) -> Result<ListInvitationsOutput> {
+        self.get(&format!("/v1/organizations/{}/invitations", org_id)).await
+    }
+
+    pub async fn create_invitation(&self, org_id: &str, input: &CreateInvitationInput) -> Result<InvitationItem> {
+        self.post(&format!("/v1/organizations/{}/invitations", org_id), input).await
+    }
+
+    pub async fn revoke_invitation(&self, org_id: &str, invitation_id: &str) -> Result<()> {
+        self.delete(&format!("/v1/organizations/{}/invitations/{}", org_id, invitation_id)).await
+    }
+
+    /// Pending invitations addressed to the caller
+    pub async fn list_my_invitations(&self) -> Result<ListInvitationsOutput> {
+        self.get("/v1/invitations").await
+    }
+
+    pub async fn accept_invitation(&self, invitation_id: &str) -> Result<InvitationItem> {
+        self.post_empty(&format!("/v1/invitations/{}/accept", invitation_id)).await
+    }
+
+    pub async fn decline_invitation(&self, invitation_id: &str) -> Result<InvitationItem> {
+        self.post_empty(&format!("/v1/invitations/{}/decline", invitation_id)).await
+    }
+
+    pub async fn list_notification_channels(&self, org_id: &str) -> Result<ListNotificationChannelsOutput> {
+        self.get(&format!("/v1/organizations/{}/notification-channels", org_id)).await
+    }
+
+    pub async fn create_notification_channel(
+        &self,
+        org_id: &str,
+        input: &CreateNotificationChannelInput,
+    ) -> Result<NotificationChannelItem> {
+        self.post(&format!("/v1/organizations/{}/notification-channels", org_id), input).await
+    }
+
+    pub async fn delete_notification_channel(&self, org_id: &str, channel_id: &str) -> Result<()> {
+        self.delete(&format!("/v1/organizations/{}/notification-channels/{}", org_id, channel_id)).await
+    }
+
+    /// The organization's monitors as a YAML or JSON document, as the server sends it
+    pub async fn export_monitors(&self, org_id: &str, query: &ExportMonitorsQuery) -> Result<String> {
+        self.get_text(&format!("/v1/organizations/{}/monitors", org_id), query).await
+    }
+
+    /// Create, update and delete monitors until the organization matches `document`, or
+    /// with `query.dry_run` only report what would change
+    pub async fn import_monitors(
+        &self,
+        org_id: &str,
+        document: &MonitorDocument,
+        query: &ImportMonitorsQuery,
+    ) -> Result<MonitorPlanOutput> {
+        let builder = self.request(Method::POST, &format!("/v1/organizations/{}/monitors", org_id))
+            .query(query)
+            .json(document);
+        Ok(self.send(builder).await?.json().await?)
+    }
+}
@@ -0,0 +1,98 @@
+use api::request_inputs::{
+    CreateWebsiteInput, ExportQuery, HistoryQuery, ListWebsitesQuery, PauseWebsiteInput, UpdateWebsiteInput,
+};
+use api::request_outputs::{
+    CheckNowOutput, CreateWebsiteOutput, GetWebsiteOutput, ListWebsiteOutput, LiveEvent, WebsiteHistoryOutput,
+    WebsiteStatusOutput,
+};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use reqwest::{Method, header};
+
+use crate::{Client, Error, Result};
+
+/// Websites, their checks and history
+impl Client {
+    pub async fn list_websites(&self, query: &ListWebsitesQuery) -> Result<ListWebsiteOutput> {
+        self.get_query("/v1/websites", query).await
+    }
+
+    pub async fn get_website(&self, website_id: &str) -> Result<GetWebsiteOutput> {
+        self.get(&format!("/v1/websites/{}", website_id)).await
+    }
+
+    pub async fn create_website(&self, input: &CreateWebsiteInput) -> Result<CreateWebsiteOutput> {
+        self.post("/v1/websites", input).await
+    }
+
+    pub async fn update_website(&self, website_id: &str, input: &UpdateWebsiteInput) -> Result<CreateWebsiteOutput> {
+        self.put(&format!("/v1/websites/{}", website_id), input).await
+    }
+
+    /// Stop monitoring a website and delete its history
+    pub async fn delete_website(&self, website_id: &str) -> Result<()> {
+        self.delete(&format!("/v1/websites/{}", website_id)).await
+    }
+
+    /// Check a website right away and record the result like a scheduled check
+    pub async fn check_website_now(&self, website_id: &str) -> Result<CheckNowOutput> {
+        self.post_empty(&format!("/v1/websites/{}/checks", website_id)).await
+    }
+
+    pub async fn pause_website(&self, website_id: &str, input: &PauseWebsiteInput) -> Result<GetWebsiteOutput> {
+        self.post(&format!("/v1/websites/{}/pause", website_id), input).await
+    }
+
+    pub async fn resume_website(&self, website_id: &str) -> Result<GetWebsiteOutput> {
+        self.post_empty(&format!("/v1/websites/{}/resume", website_id)).await
+    }
+
+    pub async fn website_status(&self, website_id: &str) -> Result<WebsiteStatusOutput> {
+        self.get(&format!("/v1/websites/{}/status", website_id)).await
+    }
+
+    /// One page of checks, newest first; pass `next_cursor` back in `query.cursor` for the next
+    pub async fn website_history(&self, website_id: &str, query: &HistoryQuery) -> Result<WebsiteHistoryOutput> {
+        self.get_query(&format!("/v1/websites/{}/history", website_id), query).await
+    }
+
+    /// The full check history as CSV or NDJSON, as the server sends it
+    pub async fn export_website_history(&self, website_id: &str, query: &ExportQuery) -> Result<String> {
+        self.get_text(&format!("/v1/websites/{}/history/export", website_id), query).await
+    }
+
+    /// Every incident as CSV or NDJSON, as the server sends it
+    pub async fn export_website_incidents(&self, website_id: &str, query: &ExportQuery) -> Result<String> {
+        self.get_text(&format!("/v1/websites/{}/incidents/export", website_id), query).await
+    }
+
+    /// Check results and status changes for the caller's websites as they happen. The
+    /// stream ends when the server closes the connection.
+    pub async fn live_events(&self) -> Result<BoxStream<'static, Result<LiveEvent>>> {
+        let resp = self.send(
+            self.request(Method::GET, "/v1/websites/live").header(header::ACCEPT, "text/event-stream"),
+        ).await?;
+        let events = stream::unfold((resp.bytes_stream(), Vec::new()), |(mut body, mut buffer)| async move {
+            loop {
+                // Events are separated by a blank line; keep-alives carry no data
+                if let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                    let block: Vec<u8> = buffer.drain(..end + 2).collect();
+                    let data = String::from_utf8_lossy(&block).lines()
+                        .filter_map(|line| line.strip_prefix("data:"))
+                        .map(str::trim_start)
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    if data.is_empty() {
+                        continue;
+                    }
+                    let event = serde_json::from_str::<LiveEvent>(&data).map_err(Error::Decode);
+                    return Some((event, (body, buffer)));
+                }
+                match body.next().await? {
+                    Ok(chunk) => buffer.extend(chunk.iter().filter(|&&b| b != b'\r')),
+                    Err(e) => return Some((Err(Error::Transport(e)), (body, buffer))),
+                }
+            }
+        });
+        Ok(events.boxed())
+    }
+}
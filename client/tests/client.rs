@@ -0,0 +1,163 @@
+//! Runs the client against the real API, served in-process on a free port with a fresh
+//! database by `api::test_support`. Needs `TEST_DATABASE_URL` as described there; the
+//! tests are `#[ignore]`d, so run them with `cargo test -- --ignored`.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use api::test_support::{TestDatabase, serve};
+use client::request_inputs::{
+    CreateUserInput, CreateWebsiteInput, HistoryQuery, ListWebsitesQuery, PauseWebsiteInput, RefreshTokenInput,
+    UpdateWebsiteInput,
+};
+use client::request_outputs::{SignInOutput, SignInResponse};
+use client::{Client, Error};
+
+static NEXT_USER: AtomicU32 = AtomicU32::new(0);
+
+/// A client for a fresh server, and the database it runs on; keep the latter alive
+/// until the test is done
+async fn server() -> (Client, TestDatabase) {
+    let database = TestDatabase::create();
+    let (url, _) = serve(&database).await;
+    (Client::new(&url), database)
+}
+
+/// Credentials no other test has used
+fn new_user() -> CreateUserInput {
+    CreateUserInput {
+        username: format!("client{}", NEXT_USER.fetch_add(1, Ordering::Relaxed)),
+        password: "password123".to_string(),
+    }
+}
+
+async fn sign_in(client: &Client, user: &CreateUserInput) -> SignInOutput {
+    match client.sign_in(user).await.unwrap() {
+        SignInResponse::Tokens(tokens) => tokens,
+        SignInResponse::Challenge(_) => panic!("new users don't have 2FA"),
+    }
+}
+
+/// A client signed in as a new user
+async fn signed_in(client: &Client) -> Client {
+    let user = new_user();
+    client.sign_up(&user).await.unwrap();
+    client.clone().with_token(sign_in(client, &user).await.jwt)
+}
+
+fn website(url: String) -> CreateWebsiteInput {
+    CreateWebsiteInput {
+        url,
+        organization_id: None,
+        name: Some("openapi".to_string()),
+        description: None,
+        tags: [("env".to_string(), "test".to_string())].into(),
+        check_interval_seconds: None,
+        assertions: Default::default(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn signs_up_in_and_out() {
+    let (client, _database) = server().await;
+    let user = new_user();
+    let created = client.sign_up(&user).await.unwrap();
+    assert!(!created.id.is_empty());
+
+    let err = client.sign_up(&user).await.err().expect("should fail");
+    assert!(matches!(err, Error::Conflict(_)), "{:?}", err);
+
+    let wrong = CreateUserInput { username: user.username.clone(), password: "not-the-password".to_string() };
+    let err = client.sign_in(&wrong).await.err().expect("should fail");
+    assert!(matches!(err, Error::Unauthorized(_)), "{:?}", err);
+
+    let tokens = sign_in(&client, &user).await;
+    let refreshed = client.refresh_token(&RefreshTokenInput { refresh_token: tokens.refresh_token }).await.unwrap();
+    let signed_in = client.clone().with_token(refreshed.jwt);
+    signed_in.list_websites(&ListWebsitesQuery::default()).await.unwrap();
+    assert!(signed_in.sign_out().await.unwrap().success);
+    let err = signed_in.list_websites(&ListWebsitesQuery::default()).await.err().expect("should fail");
+    assert!(matches!(err, Error::Unauthorized(_)), "{:?}", err);
+    let err = client.refresh_token(&RefreshTokenInput { refresh_token: refreshed.refresh_token }).await
+        .err().expect("should fail");
+    assert!(matches!(err, Error::Unauthorized(_)), "a signed out session can't be refreshed: {:?}", err);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn maps_error_responses() {
+    let (client, _database) = server().await;
+
+    let short = CreateUserInput { username: new_user().username, password: "short".to_string() };
+    let err = client.sign_up(&short).await.err().expect("should fail");
+    assert!(matches!(err, Error::BadRequest(_)), "{:?}", err);
+    assert_eq!(err.code(), Some("bad_request"));
+    assert_eq!(err.status(), Some(400));
+
+    let err = client.list_websites(&ListWebsitesQuery::default()).await.err().expect("should fail");
+    assert!(matches!(err, Error::Unauthorized(_)), "{:?}", err);
+
+    let alice = signed_in(&client).await;
+    let err = alice.get_website("00000000-0000-0000-0000-000000000000").await.err().expect("should fail");
+    assert!(matches!(err, Error::NotFound(_)), "{:?}", err);
+    assert!(err.problem().unwrap().request_id.is_some());
+
+    let err = alice.create_website(&website("ftp://example.com".to_string())).await.err().expect("should fail");
+    assert!(matches!(err, Error::BadRequest(_)), "{:?}", err);
+
+    let id = alice.create_website(&website(format!("{}/openapi.json", client.base_url()))).await.unwrap().id;
+    let bob = signed_in(&client).await;
+    let err = bob.get_website(&id).await.err().expect("should fail");
+    assert!(matches!(err, Error::Forbidden(_)), "{:?}", err);
+    let err = bob.delete_website(&id).await.expect_err("should fail");
+    assert!(matches!(err, Error::Forbidden(_)), "{:?}", err);
+    alice.get_website(&id).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn manages_and_checks_websites() {
+    let (client, _database) = server().await;
+    let client = signed_in(&client).await;
+    // The server itself makes a handy target that is always up
+    let url = format!("{}/openapi.json", client.base_url());
+
+    let id = client.create_website(&website(url.clone())).await.unwrap().id;
+    let fetched = client.get_website(&id).await.unwrap().website;
+    assert_eq!(fetched.url, url);
+    assert_eq!(fetched.name.as_deref(), Some("openapi"));
+    assert_eq!(fetched.status, "pending");
+
+    client.update_website(&id, &UpdateWebsiteInput {
+        url: url.clone(),
+        name: Some("spec".to_string()),
+        description: Some("The OpenAPI document".to_string()),
+        tags: None,
+        check_interval_seconds: Some(300),
+        assertions: None,
+    }).await.unwrap();
+    let listed = client.list_websites(&ListWebsitesQuery { q: Some("spec".to_string()), ..Default::default() })
+        .await.unwrap();
+    assert_eq!(listed.items.len(), 1);
+    assert_eq!(listed.items[0].check_interval_seconds, 300);
+
+    let check = client.check_website_now(&id).await.unwrap();
+    assert!(check.is_up, "{:?}", check.error_message);
+    assert_eq!(check.status_code, Some(200));
+    let status = client.website_status(&id).await.unwrap();
+    assert_eq!(status.is_up, Some(true));
+    let history = client.website_history(&id, &HistoryQuery { limit: Some(10), ..Default::default() }).await.unwrap();
+    assert_eq!(history.items.len(), 1);
+    assert!(history.items[0].is_up);
+
+    let paused = client.pause_website(&id, &PauseWebsiteInput { resume_at: None }).await.unwrap().website;
+    assert_eq!(paused.status, "paused");
+    let err = client.check_website_now(&id).await.err().expect("should fail");
+    assert!(matches!(err, Error::Conflict(_)), "{:?}", err);
+    let resumed = client.resume_website(&id).await.unwrap().website;
+    assert!(resumed.paused_at.is_none());
+
+    client.delete_website(&id).await.unwrap();
+    let err = client.get_website(&id).await.err().expect("should fail");
+    assert!(matches!(err, Error::NotFound(_)), "{:?}", err);
+}
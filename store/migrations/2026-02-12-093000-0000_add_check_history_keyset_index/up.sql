@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Composite index backing keyset pagination on (checked_at, id) per website
+CREATE INDEX idx_check_history_website_checked_at_id
+ON check_history (website_id, checked_at DESC, id DESC);
@@ -0,0 +1,50 @@
+-- Your SQL goes here
+-- An incident spans from the first failed check until the next successful one
+CREATE TABLE incident (
+    id VARCHAR(255) PRIMARY KEY,
+    website_id VARCHAR(255) NOT NULL REFERENCES website(id) ON DELETE CASCADE,
+    started_at TIMESTAMP NOT NULL,
+    resolved_at TIMESTAMP,
+    cause TEXT
+);
+
+CREATE INDEX idx_incident_website_started_at ON incident(website_id, started_at, id);
+
+-- Backfill from existing check history: every run of consecutive failed checks is one incident
+WITH marked AS (
+    SELECT
+        id,
+        website_id,
+        checked_at,
+        is_up,
+        error_message,
+        CASE WHEN is_up = LAG(is_up) OVER w THEN 0 ELSE 1 END AS boundary
+    FROM check_history
+    WINDOW w AS (PARTITION BY website_id ORDER BY checked_at, id)
+), grouped AS (
+    SELECT
+        *,
+        SUM(boundary) OVER (PARTITION BY website_id ORDER BY checked_at, id) AS run
+    FROM marked
+), runs AS (
+    SELECT
+        website_id,
+        MIN(checked_at) AS started_at,
+        MAX(checked_at) AS last_failed_at,
+        (ARRAY_AGG(error_message ORDER BY checked_at, id))[1] AS cause
+    FROM grouped
+    WHERE NOT is_up
+    GROUP BY website_id, run
+)
+INSERT INTO incident (id, website_id, started_at, resolved_at, cause)
+SELECT
+    gen_random_uuid()::text,
+    r.website_id,
+    r.started_at,
+    (
+        SELECT MIN(c.checked_at)
+        FROM check_history c
+        WHERE c.website_id = r.website_id AND c.is_up AND c.checked_at > r.last_failed_at
+    ),
+    r.cause
+FROM runs r;
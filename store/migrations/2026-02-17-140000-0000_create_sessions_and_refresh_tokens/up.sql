@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- A session is one sign-in; every refresh token rotated from it belongs to the same family
+CREATE TABLE auth_session (
+    id VARCHAR(255) PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX idx_auth_session_user_id ON auth_session(user_id);
+
+-- Only the SHA-256 of each refresh token is stored
+CREATE TABLE refresh_token (
+    id VARCHAR(255) PRIMARY KEY,
+    session_id VARCHAR(255) NOT NULL REFERENCES auth_session(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP
+);
+
+CREATE INDEX idx_refresh_token_session_id ON refresh_token(session_id);
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- Long-lived keys for automation; only the SHA-256 of the key is stored
+CREATE TABLE api_key (
+    id VARCHAR(255) PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    prefix VARCHAR(16) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    scope VARCHAR(16) NOT NULL CHECK (scope IN ('read', 'full')),
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP,
+    last_used_at TIMESTAMP,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX idx_api_key_user_id ON api_key(user_id);
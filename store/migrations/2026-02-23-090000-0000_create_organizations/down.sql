@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE website DROP COLUMN organization_id;
+DROP TABLE IF EXISTS organization_invitation;
+DROP TABLE IF EXISTS organization_member;
+DROP TABLE IF EXISTS organization;
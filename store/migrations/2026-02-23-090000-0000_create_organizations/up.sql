@@ -0,0 +1,56 @@
+-- Your SQL goes here
+CREATE TABLE organization (
+    id VARCHAR(255) PRIMARY KEY,
+    name TEXT NOT NULL,
+    -- Created at sign-up; new websites land here unless another organization is given
+    personal BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE organization_member (
+    organization_id VARCHAR(255) NOT NULL REFERENCES organization(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    role VARCHAR(16) NOT NULL CHECK (role IN ('owner', 'admin', 'editor', 'viewer')),
+    created_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE INDEX idx_organization_member_user_id ON organization_member(user_id);
+
+CREATE TABLE organization_invitation (
+    id VARCHAR(255) PRIMARY KEY,
+    organization_id VARCHAR(255) NOT NULL REFERENCES organization(id) ON DELETE CASCADE,
+    invitee_user_id TEXT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    invited_by TEXT REFERENCES "user"(id) ON DELETE SET NULL,
+    role VARCHAR(16) NOT NULL CHECK (role IN ('owner', 'admin', 'editor', 'viewer')),
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'accepted', 'declined', 'revoked')),
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_organization_invitation_invitee ON organization_invitation(invitee_user_id);
+CREATE UNIQUE INDEX idx_organization_invitation_pending
+ON organization_invitation(organization_id, invitee_user_id)
+WHERE status = 'pending';
+
+-- Give every existing user a personal organization and move their websites into it
+CREATE TEMP TABLE personal_org ON COMMIT DROP AS
+SELECT id AS user_id, username, gen_random_uuid()::text AS organization_id
+FROM "user";
+
+INSERT INTO organization (id, name, personal, created_at)
+SELECT organization_id, username, true, NOW() FROM personal_org;
+
+INSERT INTO organization_member (organization_id, user_id, role, created_at)
+SELECT organization_id, user_id, 'owner', NOW() FROM personal_org;
+
+ALTER TABLE website ADD COLUMN organization_id VARCHAR(255) REFERENCES organization(id) ON DELETE CASCADE;
+
+UPDATE website w
+SET organization_id = p.organization_id
+FROM personal_org p
+WHERE w.user_id = p.user_id;
+
+ALTER TABLE website ALTER COLUMN organization_id SET NOT NULL;
+
+CREATE INDEX idx_website_organization_id ON website(organization_id);
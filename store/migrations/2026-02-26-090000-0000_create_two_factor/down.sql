@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS two_factor_challenge;
+DROP TABLE IF EXISTS recovery_code;
+DROP TABLE IF EXISTS user_totp;
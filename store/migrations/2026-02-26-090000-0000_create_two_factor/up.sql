@@ -0,0 +1,34 @@
+-- Your SQL goes here
+-- TOTP secrets have to be readable to verify codes, so they are stored as-is (base32).
+-- A row with enabled_at NULL is an enrollment that has not been confirmed yet.
+CREATE TABLE user_totp (
+    user_id TEXT PRIMARY KEY REFERENCES "user"(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    enabled_at TIMESTAMP,
+    -- Highest 30s time step accepted so far, so a code cannot be replayed
+    last_used_step BIGINT
+);
+
+-- Only the SHA-256 of each recovery code is stored
+CREATE TABLE recovery_code (
+    id VARCHAR(255) PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP
+);
+
+CREATE INDEX idx_recovery_code_user_id ON recovery_code(user_id);
+
+-- Issued by sign-in when a password was right but a second factor is still owed
+CREATE TABLE two_factor_challenge (
+    id VARCHAR(255) PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    attempts INT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX idx_two_factor_challenge_user_id ON two_factor_challenge(user_id);
@@ -0,0 +1,24 @@
+-- Your SQL goes here
+-- An account at an external OpenID Connect provider, linked to one of our users
+CREATE TABLE user_identity (
+    id VARCHAR(255) PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    UNIQUE (issuer, subject)
+);
+
+CREATE INDEX idx_user_identity_user_id ON user_identity(user_id);
+
+-- In-flight authorization code requests; each row is consumed by the callback.
+-- `link_user_id` is set when a signed-in user is linking a provider account.
+CREATE TABLE oidc_login_state (
+    id VARCHAR(255) PRIMARY KEY,
+    state_hash VARCHAR(64) NOT NULL UNIQUE,
+    code_verifier TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    link_user_id TEXT REFERENCES "user"(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Shared limiter state for login throttling when several API processes run.
+-- `key` is namespaced, e.g. `ip:203.0.113.7` or `user:alice`.
+CREATE TABLE rate_limit (
+    key TEXT PRIMARY KEY,
+    window_start TIMESTAMP NOT NULL,
+    hits INT NOT NULL DEFAULT 0,
+    failures INT NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMP,
+    locked_until TIMESTAMP
+);
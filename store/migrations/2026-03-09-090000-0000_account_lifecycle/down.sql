@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS password_reset_token;
+
+ALTER TABLE "website_tick" DROP CONSTRAINT "website_tick_website_id_fkey";
+ALTER TABLE "website_tick" ADD CONSTRAINT "website_tick_website_id_fkey" FOREIGN KEY ("website_id") REFERENCES "website"("id") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+ALTER TABLE "website" DROP CONSTRAINT "Website_user_id_fkey";
+ALTER TABLE "website" ADD CONSTRAINT "Website_user_id_fkey" FOREIGN KEY ("user_id") REFERENCES "user"("id") ON DELETE RESTRICT ON UPDATE CASCADE;
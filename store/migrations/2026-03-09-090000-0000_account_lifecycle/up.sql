@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Deleting a user removes the websites they created, and everything hanging off them
+ALTER TABLE "website" DROP CONSTRAINT "Website_user_id_fkey";
+ALTER TABLE "website" ADD CONSTRAINT "Website_user_id_fkey" FOREIGN KEY ("user_id") REFERENCES "user"("id") ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE "website_tick" DROP CONSTRAINT "website_tick_website_id_fkey";
+ALTER TABLE "website_tick" ADD CONSTRAINT "website_tick_website_id_fkey" FOREIGN KEY ("website_id") REFERENCES "website"("id") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- Single-use password reset tokens; only the SHA-256 of each token is stored
+CREATE TABLE password_reset_token (
+    id VARCHAR(255) PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP
+);
+
+CREATE INDEX idx_password_reset_token_user_id ON password_reset_token(user_id);
@@ -0,0 +1,27 @@
+-- Your SQL goes here
+-- No foreign keys: entries must outlive the users, organizations and websites they mention
+CREATE TABLE audit_log (
+    id VARCHAR(255) PRIMARY KEY,
+    organization_id TEXT,
+    actor_id TEXT,
+    action VARCHAR(64) NOT NULL,
+    target_type VARCHAR(32) NOT NULL,
+    target_id TEXT,
+    before JSONB,
+    after JSONB,
+    ip VARCHAR(64),
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX audit_log_organization_created_at_idx ON audit_log (organization_id, created_at DESC, id DESC);
+CREATE INDEX audit_log_actor_idx ON audit_log (actor_id);
+
+CREATE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_no_update_or_delete
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- A website has at most one open incident, so joining it onto websites cannot fan out
+CREATE UNIQUE INDEX idx_incident_open_website ON incident(website_id) WHERE resolved_at IS NULL;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE notification_channel;
+DROP INDEX IF EXISTS idx_website_tags;
+ALTER TABLE website DROP COLUMN tags;
+ALTER TABLE website DROP COLUMN description;
+ALTER TABLE website DROP COLUMN name;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+ALTER TABLE website ADD COLUMN name TEXT;
+ALTER TABLE website ADD COLUMN description TEXT;
+-- Free-form key/value labels, e.g. {"team": "payments"}
+ALTER TABLE website ADD COLUMN tags JSONB NOT NULL DEFAULT '{}';
+CREATE INDEX idx_website_tags ON website USING GIN (tags jsonb_path_ops);
+
+-- Where incident notifications go. A channel only hears about websites carrying
+-- every tag in match_tags; an empty object matches all of the organization's websites.
+CREATE TABLE notification_channel (
+    id VARCHAR(255) PRIMARY KEY,
+    organization_id VARCHAR(255) NOT NULL REFERENCES organization(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    match_tags JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_notification_channel_organization_id ON notification_channel(organization_id);
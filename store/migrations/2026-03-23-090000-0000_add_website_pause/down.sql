@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_website_resume_at;
+ALTER TABLE website DROP COLUMN resume_at;
+ALTER TABLE website DROP COLUMN paused_at;
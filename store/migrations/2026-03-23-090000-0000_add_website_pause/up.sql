@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- A paused website is skipped by the worker; resume_at, when set, is when it picks up again
+ALTER TABLE website ADD COLUMN paused_at TIMESTAMP;
+ALTER TABLE website ADD COLUMN resume_at TIMESTAMP;
+CREATE INDEX idx_website_resume_at ON website(resume_at) WHERE paused_at IS NOT NULL;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_website_organization_external_key;
+ALTER TABLE website DROP COLUMN assertions;
+ALTER TABLE website DROP COLUMN check_interval_seconds;
+ALTER TABLE website DROP COLUMN external_key;
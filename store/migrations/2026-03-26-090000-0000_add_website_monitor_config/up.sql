@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- external_key names a website in a monitors-as-code document, so re-applying the
+-- document updates it instead of creating a duplicate
+ALTER TABLE website ADD COLUMN external_key TEXT;
+ALTER TABLE website ADD COLUMN check_interval_seconds INTEGER NOT NULL DEFAULT 60;
+ALTER TABLE website ADD COLUMN assertions JSONB NOT NULL DEFAULT '{}';
+CREATE UNIQUE INDEX idx_website_organization_external_key ON website(organization_id, external_key);
@@ -0,0 +1,33 @@
+use diesel::{prelude::*, sql_types::Text};
+
+use crate::store::Store;
+
+/// Postgres channel that check results and status transitions are published on
+pub const WEBSITE_EVENTS_CHANNEL: &str = "website_events";
+
+impl Store {
+    /// Publish a payload to every connection listening on the events channel
+    pub fn publish_event(&mut self, payload: &str) -> Result<(), diesel::result::Error> {
+        diesel::sql_query("SELECT pg_notify($1, $2)")
+        .bind::<Text, _>(WEBSITE_EVENTS_CHANNEL)
+        .bind::<Text, _>(payload)
+        .execute(&mut self.conn)?;
+        Ok(())
+    }
+
+    /// Subscribe this connection to the events channel
+    pub fn listen_events(&mut self) -> Result<(), diesel::result::Error> {
+        diesel::sql_query(format!("LISTEN {}", WEBSITE_EVENTS_CHANNEL))
+        .execute(&mut self.conn)?;
+        Ok(())
+    }
+
+    /// Drain the payloads received since the last call, without blocking
+    pub fn poll_events(&mut self) -> Result<Vec<String>, diesel::result::Error> {
+        self.conn
+        .notifications_iter()
+        .filter(|n| !matches!(n, Ok(n) if n.channel != WEBSITE_EVENTS_CHANNEL))
+        .map(|n| n.map(|n| n.payload))
+        .collect()
+    }
+}
@@ -3,4 +3,6 @@ pub mod schema;
 pub mod config;
 pub mod store;
 pub mod models;
-pub mod password;
\ No newline at end of file
+pub mod password;
+pub mod events;
+pub mod migrations;
\ No newline at end of file
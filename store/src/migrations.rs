@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use diesel::migration::{Migration, MigrationSource};
+use diesel::pg::Pg;
+use diesel::sql_types::BigInt;
+use diesel::{PgConnection, RunQueryDsl};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+
+use crate::store::Store;
+
+/// Every migration in `store/migrations`, built into the binary
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Key of the advisory lock held while migrating, so servers starting together and
+/// `migrate` commands take turns
+const MIGRATION_LOCK_KEY: i64 = 0x6275_6d69_6772;
+
+/// diesel_migrations reports failures as boxed errors
+pub type MigrationError = Box<dyn std::error::Error + Send + Sync>;
+
+/// How the database's schema compares with the migrations this build knows about
+pub struct SchemaStatus {
+    /// Known migrations, oldest first, and whether each has been applied
+    pub migrations: Vec<(String, bool)>,
+    /// Versions applied to the database that this build doesn't know, i.e. from a newer release
+    pub unknown: Vec<String>,
+}
+
+impl SchemaStatus {
+    pub fn pending(&self) -> Vec<&str> {
+        self.migrations.iter()
+            .filter(|(_, applied)| !applied)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Whether the schema is ahead of this build, which might not work with it
+    pub fn is_newer(&self) -> bool {
+        !self.unknown.is_empty()
+    }
+}
+
+impl Store {
+    pub fn schema_status(&mut self) -> Result<SchemaStatus, MigrationError> {
+        let applied: HashSet<String> = self.conn.applied_migrations()?
+            .into_iter()
+            .map(|version| version.to_string())
+            .collect();
+        let known = MigrationSource::<Pg>::migrations(&MIGRATIONS)?;
+        let known_versions: HashSet<String> = known.iter().map(|m| m.name().version().to_string()).collect();
+
+        let migrations = known.iter()
+            .map(|m| (m.name().to_string(), applied.contains(&m.name().version().to_string())))
+            .collect();
+        let mut unknown: Vec<String> = applied.into_iter().filter(|v| !known_versions.contains(v)).collect();
+        unknown.sort();
+        Ok(SchemaStatus { migrations, unknown })
+    }
+
+    /// Make sure the schema is one this build can run on: refuse one that is newer, and
+    /// one with pending migrations unless `run_migrations` says to apply them. Returns
+    /// the versions applied.
+    pub fn prepare_schema(&mut self, run_migrations: bool) -> Result<Vec<String>, MigrationError> {
+        let status = self.schema_status()?;
+        if status.is_newer() {
+            return Err(format!(
+                "the database schema is newer than this build (unknown migrations {}); deploy a newer release or revert them with one",
+                status.unknown.join(", "),
+            ).into());
+        }
+        let pending = status.pending();
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+        if !run_migrations {
+            return Err(format!(
+                "{} pending migration(s), starting with {}; run `api migrate up` or start with --run-migrations",
+                pending.len(),
+                pending[0],
+            ).into());
+        }
+        self.run_pending_migrations()
+    }
+
+    /// Apply every pending migration, oldest first, returning the versions applied
+    pub fn run_pending_migrations(&mut self) -> Result<Vec<String>, MigrationError> {
+        self.with_migration_lock(|conn| {
+            conn.run_pending_migrations(MIGRATIONS)
+                .map(|versions| versions.into_iter().map(|version| version.to_string()).collect())
+        })
+    }
+
+    /// Undo the most recently applied migration, returning its version
+    pub fn revert_last_migration(&mut self) -> Result<String, MigrationError> {
+        self.with_migration_lock(|conn| Ok(conn.revert_last_migration(MIGRATIONS)?.to_string()))
+    }
+
+    /// Run `f` holding the advisory lock, so migrations applied or reverted by other
+    /// processes don't interleave with it
+    fn with_migration_lock<T>(
+        &mut self,
+        f: impl FnOnce(&mut PgConnection) -> Result<T, MigrationError>,
+    ) -> Result<T, MigrationError> {
+        diesel::sql_query("SELECT pg_advisory_lock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+        .execute(&mut self.conn)?;
+        let result = f(&mut self.conn);
+        diesel::sql_query("SELECT pg_advisory_unlock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+        .execute(&mut self.conn)?;
+        result
+    }
+}
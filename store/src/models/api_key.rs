@@ -0,0 +1,104 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::password::hash_token;
+use crate::store::Store;
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::api_key)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiKey {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub prefix: String,
+    key_hash: String,
+    pub scope: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub last_used_at: Option<chrono::NaiveDateTime>,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+}
+
+impl Store {
+    /// Store a new key; only its hash and a short display prefix are kept
+    pub fn create_api_key(
+        &mut self,
+        user_id: String,
+        name: String,
+        key: &str,
+        scope: String,
+        expires_at: Option<chrono::NaiveDateTime>
+    ) -> Result<ApiKey, diesel::result::Error> {
+        let api_key = ApiKey {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            name,
+            prefix: key.chars().take(12).collect(),
+            key_hash: hash_token(key),
+            scope,
+            created_at: chrono::Utc::now().naive_utc(),
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+        };
+
+        diesel::insert_into(crate::schema::api_key::table)
+        .values(&api_key)
+        .execute(&mut self.conn)?;
+
+    Ok(api_key)
+    }
+
+    /// Resolve a presented key to its record if it is neither revoked nor expired,
+    /// bumping `last_used_at` at most once a minute
+    pub fn authenticate_api_key(&mut self, key: &str) -> Result<Option<ApiKey>, diesel::result::Error> {
+        use crate::schema::api_key::dsl::*;
+        let now = chrono::Utc::now().naive_utc();
+
+        let found = api_key
+        .filter(key_hash.eq(hash_token(key)))
+        .filter(revoked_at.is_null())
+        .filter(expires_at.is_null().or(expires_at.gt(now)))
+        .select(ApiKey::as_select())
+        .first(&mut self.conn)
+        .optional()?;
+
+        if let Some(found) = &found {
+            diesel::update(api_key.filter(id.eq(found.id.clone())))
+            .filter(last_used_at.is_null().or(last_used_at.lt(now - chrono::Duration::minutes(1))))
+            .set(last_used_at.eq(Some(now)))
+            .execute(&mut self.conn)?;
+        }
+    Ok(found)
+    }
+
+    pub fn list_api_keys(&mut self, input_user_id: String) -> Result<Vec<ApiKey>, diesel::result::Error> {
+        use crate::schema::api_key::dsl::*;
+        let keys = api_key
+        .filter(user_id.eq(input_user_id))
+        .filter(revoked_at.is_null())
+        .order(created_at.desc())
+        .select(ApiKey::as_select())
+        .load(&mut self.conn)?;
+    Ok(keys)
+    }
+
+    pub fn revoke_api_key(
+        &mut self,
+        key_id: String,
+        input_user_id: String
+    ) -> Result<usize, diesel::result::Error> {
+        use crate::schema::api_key::dsl::*;
+        let revoked = diesel::update(api_key)
+        .filter(id.eq(key_id))
+        .filter(user_id.eq(input_user_id))
+        .filter(revoked_at.is_null())
+        .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+        .execute(&mut self.conn)?;
+    if revoked == 0 {
+        return Err(diesel::result::Error::NotFound);
+    }
+    Ok(revoked)
+    }
+}
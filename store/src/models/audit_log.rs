@@ -0,0 +1,111 @@
+use crate::store::Store;
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::audit_log)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditLogEntry {
+    pub id: String,
+    pub organization_id: Option<String>,
+    pub actor_id: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub ip: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// What happened, as recorded by the caller; id and timestamp are filled in on insert
+pub struct NewAuditEntry {
+    pub organization_id: Option<String>,
+    pub actor_id: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub ip: Option<String>,
+}
+
+/// Optional narrowing for `list_audit_log`; every set field must match
+#[derive(Default)]
+pub struct AuditFilter {
+    pub actor_id: Option<String>,
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub from: Option<chrono::NaiveDateTime>,
+    pub to: Option<chrono::NaiveDateTime>,
+}
+
+impl Store {
+    /// Append an entry. The table rejects updates and deletes, so this is the only write.
+    pub fn append_audit_log(&mut self, entry: NewAuditEntry) -> Result<AuditLogEntry, diesel::result::Error> {
+        let row = AuditLogEntry {
+            id: Uuid::new_v4().to_string(),
+            organization_id: entry.organization_id,
+            actor_id: entry.actor_id,
+            action: entry.action,
+            target_type: entry.target_type,
+            target_id: entry.target_id,
+            before: entry.before,
+            after: entry.after,
+            ip: entry.ip,
+            created_at: Utc::now().naive_utc(),
+        };
+
+        diesel::insert_into(crate::schema::audit_log::table)
+        .values(&row)
+        .execute(&mut self.conn)?;
+    Ok(row)
+    }
+
+    /// An organization's entries, newest first. `older_than` is the `(created_at, id)` of
+    /// the last entry on the previous page.
+    pub fn list_audit_log(
+        &mut self,
+        org_id: String,
+        filter: &AuditFilter,
+        older_than: Option<(chrono::NaiveDateTime, String)>,
+        limit: i64
+    ) -> Result<Vec<AuditLogEntry>, diesel::result::Error> {
+        use crate::schema::audit_log::dsl::*;
+
+        let mut query = audit_log
+        .filter(organization_id.eq(org_id))
+        .into_boxed();
+
+        if let Some(value) = &filter.actor_id {
+            query = query.filter(actor_id.eq(value.clone()));
+        }
+        if let Some(value) = &filter.action {
+            query = query.filter(action.eq(value.clone()));
+        }
+        if let Some(value) = &filter.target_type {
+            query = query.filter(target_type.eq(value.clone()));
+        }
+        if let Some(value) = &filter.target_id {
+            query = query.filter(target_id.eq(value.clone()));
+        }
+        if let Some(from) = filter.from {
+            query = query.filter(created_at.ge(from));
+        }
+        if let Some(to) = filter.to {
+            query = query.filter(created_at.lt(to));
+        }
+        if let Some((ts, key)) = older_than {
+            query = query.filter(created_at.lt(ts).or(created_at.eq(ts).and(id.lt(key))));
+        }
+
+        let results = query
+        .order((created_at.desc(), id.desc()))
+        .limit(limit)
+        .select(AuditLogEntry::as_select())
+        .load(&mut self.conn)?;
+    Ok(results)
+    }
+}
@@ -14,6 +14,23 @@ pub struct CheckHistory {
     pub error_message: Option<String>,
 }
 
+/// Optional filters applied to a website's check history.
+/// `from` is inclusive and `to` is exclusive.
+#[derive(Default)]
+pub struct HistoryFilter {
+    pub from: Option<chrono::NaiveDateTime>,
+    pub to: Option<chrono::NaiveDateTime>,
+    pub is_up: Option<bool>,
+}
+
+/// Keyset position in check history, ordered by `(checked_at, id)`.
+pub enum HistoryCursor {
+    /// Rows strictly older than this key (the next page)
+    Older { checked_at: chrono::NaiveDateTime, id: String },
+    /// Rows strictly newer than this key (the previous page)
+    Newer { checked_at: chrono::NaiveDateTime, id: String },
+}
+
 use crate::store::Store;
 
 impl Store {
@@ -69,18 +86,50 @@ impl Store {
     pub fn get_website_history(
         &mut self,
         website_id_value: String,
-        limit: i64,
-        offset: i64
+        filter: &HistoryFilter,
+        cursor: Option<&HistoryCursor>,
+        limit: i64
     ) -> Result<Vec<CheckHistory>, diesel::result::Error> {
         use crate::schema::check_history::dsl::*;
 
-        let results = check_history
+        let mut query = check_history
         .filter(website_id.eq(website_id_value))
-        .order(checked_at.desc())
-        .limit(limit)
-        .offset(offset)
-        .select(CheckHistory::as_select())
-        .load(&mut self.conn)?;
+        .into_boxed();
+
+        if let Some(from) = filter.from {
+            query = query.filter(checked_at.ge(from));
+        }
+        if let Some(to) = filter.to {
+            query = query.filter(checked_at.lt(to));
+        }
+        if let Some(is_up_value) = filter.is_up {
+            query = query.filter(is_up.eq(is_up_value));
+        }
+
+        let results = match cursor {
+            None => query
+                .order((checked_at.desc(), id.desc()))
+                .limit(limit)
+                .select(CheckHistory::as_select())
+                .load(&mut self.conn)?,
+            Some(HistoryCursor::Older { checked_at: ts, id: key }) => query
+                .filter(checked_at.lt(*ts).or(checked_at.eq(*ts).and(id.lt(key.clone()))))
+                .order((checked_at.desc(), id.desc()))
+                .limit(limit)
+                .select(CheckHistory::as_select())
+                .load(&mut self.conn)?,
+            Some(HistoryCursor::Newer { checked_at: ts, id: key }) => {
+                // Walk forward in ascending order, then flip so callers always get newest first
+                let mut rows = query
+                    .filter(checked_at.gt(*ts).or(checked_at.eq(*ts).and(id.gt(key.clone()))))
+                    .order((checked_at.asc(), id.asc()))
+                    .limit(limit)
+                    .select(CheckHistory::as_select())
+                    .load(&mut self.conn)?;
+                rows.reverse();
+                rows
+            }
+        };
     Ok(results)
     }
 }
\ No newline at end of file
@@ -0,0 +1,158 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::models::organization::insert_organization;
+use crate::models::user::User;
+use crate::password::hash_token;
+use crate::store::Store;
+
+/// Stored as the password of users created through SSO. It is not a valid argon2
+/// hash, so password sign-in always fails for them.
+pub const NO_PASSWORD: &str = "!";
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::user_identity)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UserIdentity {
+    pub id: String,
+    pub user_id: String,
+    pub issuer: String,
+    pub subject: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::oidc_login_state)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OidcLoginState {
+    pub id: String,
+    state_hash: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    pub link_user_id: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+fn insert_identity(
+    conn: &mut PgConnection,
+    owner_id: String,
+    identity_issuer: String,
+    identity_subject: String
+) -> Result<UserIdentity, diesel::result::Error> {
+    let identity = UserIdentity {
+        id: Uuid::new_v4().to_string(),
+        user_id: owner_id,
+        issuer: identity_issuer,
+        subject: identity_subject,
+        created_at: chrono::Utc::now().naive_utc(),
+    };
+    diesel::insert_into(crate::schema::user_identity::table)
+    .values(&identity)
+    .execute(conn)?;
+    Ok(identity)
+}
+
+impl Store {
+    /// Remember an authorization request until the provider redirects back
+    pub fn create_oidc_state(
+        &mut self,
+        state: &str,
+        verifier: String,
+        login_nonce: String,
+        link_user: Option<String>,
+        expires: chrono::NaiveDateTime
+    ) -> Result<OidcLoginState, diesel::result::Error> {
+        let row = OidcLoginState {
+            id: Uuid::new_v4().to_string(),
+            state_hash: hash_token(state),
+            code_verifier: verifier,
+            nonce: login_nonce,
+            link_user_id: link_user,
+            created_at: chrono::Utc::now().naive_utc(),
+            expires_at: expires,
+        };
+
+        diesel::insert_into(crate::schema::oidc_login_state::table)
+        .values(&row)
+        .execute(&mut self.conn)?;
+
+    Ok(row)
+    }
+
+    /// Consume the request matching `state`; None if unknown, expired or already used
+    pub fn take_oidc_state(&mut self, state: &str) -> Result<Option<OidcLoginState>, diesel::result::Error> {
+        use crate::schema::oidc_login_state::dsl::*;
+        let now = chrono::Utc::now().naive_utc();
+
+        diesel::delete(oidc_login_state.filter(expires_at.le(now)))
+        .execute(&mut self.conn)?;
+
+        let found = diesel::delete(oidc_login_state.filter(state_hash.eq(hash_token(state))))
+        .returning(OidcLoginState::as_returning())
+        .get_result(&mut self.conn)
+        .optional()?;
+    Ok(found)
+    }
+
+    /// The user an external identity belongs to, if it has been seen before
+    pub fn find_identity_user(
+        &mut self,
+        identity_issuer: String,
+        identity_subject: String
+    ) -> Result<Option<String>, diesel::result::Error> {
+        use crate::schema::user_identity::dsl::*;
+        let found = user_identity
+        .filter(issuer.eq(identity_issuer))
+        .filter(subject.eq(identity_subject))
+        .select(user_id)
+        .first(&mut self.conn)
+        .optional()?;
+    Ok(found)
+    }
+
+    /// Attach an external identity to an existing user. Fails with a unique violation
+    /// if the identity already belongs to someone.
+    pub fn link_identity(
+        &mut self,
+        owner_id: String,
+        identity_issuer: String,
+        identity_subject: String
+    ) -> Result<UserIdentity, diesel::result::Error> {
+        insert_identity(&mut self.conn, owner_id, identity_issuer, identity_subject)
+    }
+
+    /// Create a user for a first-time SSO login, with a personal organization like
+    /// `sign_up` gives. Takes `preferred_username`, adding a numeric suffix if it is taken.
+    pub fn provision_sso_user(
+        &mut self,
+        preferred_username: String,
+        identity_issuer: String,
+        identity_subject: String
+    ) -> Result<String, diesel::result::Error> {
+        use crate::schema::user::dsl::*;
+        let new_id = Uuid::new_v4().to_string();
+
+        self.conn.transaction(|conn| {
+            let mut candidate = preferred_username.clone();
+            let mut suffix = 1;
+            while diesel::select(diesel::dsl::exists(user.filter(username.eq(candidate.clone()))))
+                .get_result::<bool>(conn)?
+            {
+                suffix += 1;
+                candidate = format!("{}-{}", preferred_username, suffix);
+            }
+
+            diesel::insert_into(user)
+            .values(&User {
+                id: new_id.clone(),
+                username: candidate.clone(),
+                password: NO_PASSWORD.to_string(),
+            })
+            .execute(conn)?;
+            insert_organization(conn, candidate, new_id.clone(), true)?;
+            insert_identity(conn, new_id.clone(), identity_issuer, identity_subject)?;
+            Ok(new_id)
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::store::Store;
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::incident)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Incident {
+    pub id: String,
+    pub website_id: String,
+    pub started_at: chrono::NaiveDateTime,
+    pub resolved_at: Option<chrono::NaiveDateTime>,
+    pub cause: Option<String>,
+}
+
+/// What a check did to a website's incidents, for whoever needs telling
+pub enum IncidentChange {
+    Opened(Incident),
+    Resolved(Incident),
+}
+
+impl Store {
+    /// Open an incident when a website goes down and resolve it once it comes back up
+    pub fn track_incident(
+        &mut self,
+        website_id_value: String,
+        is_up_value: bool,
+        cause_value: Option<String>
+    ) -> Result<Option<IncidentChange>, diesel::result::Error> {
+        use crate::schema::incident::dsl::*;
+
+        let open = incident
+        .filter(website_id.eq(website_id_value.clone()))
+        .filter(resolved_at.is_null())
+        .select(Incident::as_select())
+        .first(&mut self.conn)
+        .optional()?;
+
+        let now = chrono::Utc::now().naive_utc();
+        match (open, is_up_value) {
+            (None, false) => {
+                let new_incident = Incident {
+                    id: Uuid::new_v4().to_string(),
+                    website_id: website_id_value,
+                    started_at: now,
+                    resolved_at: None,
+                    cause: cause_value,
+                };
+                diesel::insert_into(incident)
+                .values(&new_incident)
+                .execute(&mut self.conn)?;
+                Ok(Some(IncidentChange::Opened(new_incident)))
+            }
+            (Some(open), true) => {
+                let resolved = diesel::update(incident.filter(id.eq(open.id)))
+                .set(resolved_at.eq(Some(now)))
+                .returning(Incident::as_returning())
+                .get_result(&mut self.conn)?;
+                Ok(Some(IncidentChange::Resolved(resolved)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Incidents that started in `[from, to)`, oldest first, resuming after the `(started_at, id)` key
+    pub fn get_incidents(
+        &mut self,
+        website_id_value: String,
+        from: Option<chrono::NaiveDateTime>,
+        to: Option<chrono::NaiveDateTime>,
+        after: Option<(chrono::NaiveDateTime, String)>,
+        limit: i64
+    ) -> Result<Vec<Incident>, diesel::result::Error> {
+        use crate::schema::incident::dsl::*;
+
+        let mut query = incident
+        .filter(website_id.eq(website_id_value))
+        .into_boxed();
+
+        if let Some(from) = from {
+            query = query.filter(started_at.ge(from));
+        }
+        if let Some(to) = to {
+            query = query.filter(started_at.lt(to));
+        }
+        if let Some((ts, key)) = after {
+            query = query.filter(started_at.gt(ts).or(started_at.eq(ts).and(id.gt(key))));
+        }
+
+        let results = query
+        .order((started_at.asc(), id.asc()))
+        .limit(limit)
+        .select(Incident::as_select())
+        .load(&mut self.conn)?;
+    Ok(results)
+    }
+}
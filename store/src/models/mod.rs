@@ -1,3 +1,13 @@
 pub mod user;
 pub mod website;
-pub mod check_history; 
\ No newline at end of file
+pub mod check_history;
+pub mod incident;
+pub mod session;
+pub mod api_key;
+pub mod organization;
+pub mod two_factor;
+pub mod identity;
+pub mod rate_limit;
+pub mod password_reset;
+pub mod audit_log;
+pub mod notification;
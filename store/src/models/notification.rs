@@ -0,0 +1,82 @@
+use crate::store::Store;
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::notification_channel)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NotificationChannel {
+    pub id: String,
+    pub organization_id: String,
+    pub name: String,
+    /// Webhook that incident events are POSTed to
+    pub url: String,
+    /// Tags a website must all carry for this channel to hear about it
+    pub match_tags: serde_json::Value,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl Store {
+    pub fn create_notification_channel(
+        &mut self,
+        org_id: String,
+        channel_name: String,
+        channel_url: String,
+        tags: serde_json::Value
+    ) -> Result<NotificationChannel, diesel::result::Error> {
+        let channel = NotificationChannel {
+            id: Uuid::new_v4().to_string(),
+            organization_id: org_id,
+            name: channel_name,
+            url: channel_url,
+            match_tags: tags,
+            created_at: Utc::now().naive_utc(),
+        };
+
+        diesel::insert_into(crate::schema::notification_channel::table)
+        .values(&channel)
+        .execute(&mut self.conn)?;
+    Ok(channel)
+    }
+
+    pub fn list_notification_channels(&mut self, org_id: String) -> Result<Vec<NotificationChannel>, diesel::result::Error> {
+        use crate::schema::notification_channel::dsl::*;
+        let channels = notification_channel
+        .filter(organization_id.eq(org_id))
+        .order(created_at.asc())
+        .select(NotificationChannel::as_select())
+        .load(&mut self.conn)?;
+    Ok(channels)
+    }
+
+    /// Remove a channel, returning it; `NotFound` if it isn't in the organization
+    pub fn delete_notification_channel(
+        &mut self,
+        org_id: String,
+        channel_id: String
+    ) -> Result<NotificationChannel, diesel::result::Error> {
+        use crate::schema::notification_channel::dsl::*;
+        let deleted = diesel::delete(notification_channel)
+        .filter(id.eq(channel_id))
+        .filter(organization_id.eq(org_id))
+        .returning(NotificationChannel::as_returning())
+        .get_result(&mut self.conn)?;
+    Ok(deleted)
+    }
+
+    /// Channels in the organization whose `match_tags` are all among `website_tags`
+    pub fn notification_channels_for(
+        &mut self,
+        org_id: String,
+        website_tags: serde_json::Value
+    ) -> Result<Vec<NotificationChannel>, diesel::result::Error> {
+        use crate::schema::notification_channel::dsl::*;
+        let channels = notification_channel
+        .filter(organization_id.eq(org_id))
+        .filter(match_tags.is_contained_by(website_tags))
+        .select(NotificationChannel::as_select())
+        .load(&mut self.conn)?;
+    Ok(channels)
+    }
+}
@@ -0,0 +1,363 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::store::Store;
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::organization)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    pub personal: bool,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::organization_member)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OrganizationMember {
+    pub organization_id: String,
+    pub user_id: String,
+    pub role: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::organization_invitation)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OrganizationInvitation {
+    pub id: String,
+    pub organization_id: String,
+    pub invitee_user_id: String,
+    pub invited_by: Option<String>,
+    pub role: String,
+    pub status: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+/// Create an organization with `owner_user_id` as its first owner, on an existing connection
+/// so sign-up can do it in the same transaction as creating the user
+pub(crate) fn insert_organization(
+    conn: &mut PgConnection,
+    name: String,
+    owner_user_id: String,
+    personal: bool
+) -> Result<Organization, diesel::result::Error> {
+    let now = chrono::Utc::now().naive_utc();
+    let org = Organization {
+        id: Uuid::new_v4().to_string(),
+        name,
+        personal,
+        created_at: now,
+    };
+
+    diesel::insert_into(crate::schema::organization::table)
+    .values(&org)
+    .execute(conn)?;
+
+    diesel::insert_into(crate::schema::organization_member::table)
+    .values(&OrganizationMember {
+        organization_id: org.id.clone(),
+        user_id: owner_user_id,
+        role: "owner".to_string(),
+        created_at: now,
+    })
+    .execute(conn)?;
+
+    Ok(org)
+}
+
+impl Store {
+    pub fn create_organization(
+        &mut self,
+        name: String,
+        owner_user_id: String
+    ) -> Result<Organization, diesel::result::Error> {
+        self.conn.transaction(|conn| insert_organization(conn, name, owner_user_id, false))
+    }
+
+    /// Every organization the user belongs to, with their role in it
+    pub fn list_user_organizations(
+        &mut self,
+        input_user_id: String
+    ) -> Result<Vec<(Organization, String)>, diesel::result::Error> {
+        use crate::schema::{organization, organization_member};
+        let orgs = organization::table
+        .inner_join(organization_member::table)
+        .filter(organization_member::user_id.eq(input_user_id))
+        .order(organization::created_at.asc())
+        .select((Organization::as_select(), organization_member::role))
+        .load(&mut self.conn)?;
+    Ok(orgs)
+    }
+
+    pub fn personal_organization(&mut self, input_user_id: String) -> Result<Organization, diesel::result::Error> {
+        use crate::schema::{organization, organization_member};
+        let org = organization::table
+        .inner_join(organization_member::table)
+        .filter(organization_member::user_id.eq(input_user_id))
+        .filter(organization::personal.eq(true))
+        .filter(organization_member::role.eq("owner"))
+        .order(organization::created_at.asc())
+        .select(Organization::as_select())
+        .first(&mut self.conn)?;
+    Ok(org)
+    }
+
+    /// The user's role in the organization, or None if they are not a member
+    pub fn organization_role(
+        &mut self,
+        org_id: String,
+        input_user_id: String
+    ) -> Result<Option<String>, diesel::result::Error> {
+        use crate::schema::organization_member::dsl::*;
+        let found = organization_member
+        .filter(organization_id.eq(org_id))
+        .filter(user_id.eq(input_user_id))
+        .select(role)
+        .first(&mut self.conn)
+        .optional()?;
+    Ok(found)
+    }
+
+    /// Members with their usernames, oldest first
+    pub fn list_members(
+        &mut self,
+        org_id: String
+    ) -> Result<Vec<(OrganizationMember, String)>, diesel::result::Error> {
+        use crate::schema::{organization_member, user};
+        let members = organization_member::table
+        .inner_join(user::table)
+        .filter(organization_member::organization_id.eq(org_id))
+        .order(organization_member::created_at.asc())
+        .select((OrganizationMember::as_select(), user::username))
+        .load(&mut self.conn)?;
+    Ok(members)
+    }
+
+    pub fn count_owners(&mut self, org_id: String) -> Result<i64, diesel::result::Error> {
+        use crate::schema::organization_member::dsl::*;
+        let owners = organization_member
+        .filter(organization_id.eq(org_id))
+        .filter(role.eq("owner"))
+        .count()
+        .get_result(&mut self.conn)?;
+    Ok(owners)
+    }
+
+    pub fn set_member_role(
+        &mut self,
+        org_id: String,
+        member_user_id: String,
+        new_role: String
+    ) -> Result<usize, diesel::result::Error> {
+        use crate::schema::organization_member::dsl::*;
+        let updated = diesel::update(organization_member)
+        .filter(organization_id.eq(org_id))
+        .filter(user_id.eq(member_user_id))
+        .set(role.eq(new_role))
+        .execute(&mut self.conn)?;
+    if updated == 0 {
+        return Err(diesel::result::Error::NotFound);
+    }
+    Ok(updated)
+    }
+
+    pub fn remove_member(
+        &mut self,
+        org_id: String,
+        member_user_id: String
+    ) -> Result<usize, diesel::result::Error> {
+        use crate::schema::organization_member::dsl::*;
+        let removed = diesel::delete(organization_member)
+        .filter(organization_id.eq(org_id))
+        .filter(user_id.eq(member_user_id))
+        .execute(&mut self.conn)?;
+    if removed == 0 {
+        return Err(diesel::result::Error::NotFound);
+    }
+    Ok(removed)
+    }
+
+    /// Invite a user, first revoking any pending invitation of theirs to the organization
+    /// that has already expired, which would otherwise hold the pending slot forever
+    pub fn create_invitation(
+        &mut self,
+        org_id: String,
+        invitee: String,
+        inviter: String,
+        invited_role: String,
+        expires_at_value: chrono::NaiveDateTime
+    ) -> Result<OrganizationInvitation, diesel::result::Error> {
+        use crate::schema::organization_invitation;
+        let now = chrono::Utc::now().naive_utc();
+        let invitation = OrganizationInvitation {
+            id: Uuid::new_v4().to_string(),
+            organization_id: org_id,
+            invitee_user_id: invitee,
+            invited_by: Some(inviter),
+            role: invited_role,
+            status: "pending".to_string(),
+            created_at: now,
+            expires_at: expires_at_value,
+        };
+
+        self.conn.transaction(|conn| {
+            diesel::update(organization_invitation::table)
+            .filter(organization_invitation::organization_id.eq(&invitation.organization_id))
+            .filter(organization_invitation::invitee_user_id.eq(&invitation.invitee_user_id))
+            .filter(organization_invitation::status.eq("pending"))
+            .filter(organization_invitation::expires_at.le(now))
+            .set(organization_invitation::status.eq("revoked"))
+            .execute(conn)?;
+
+            diesel::insert_into(organization_invitation::table)
+            .values(&invitation)
+            .execute(conn)
+        })?;
+
+    Ok(invitation)
+    }
+
+    /// Pending, unexpired invitations for an organization with the invitee's username
+    pub fn list_organization_invitations(
+        &mut self,
+        org_id: String
+    ) -> Result<Vec<(OrganizationInvitation, String)>, diesel::result::Error> {
+        use crate::schema::{organization_invitation, user};
+        let invitations = organization_invitation::table
+        .inner_join(user::table.on(user::id.eq(organization_invitation::invitee_user_id)))
+        .filter(organization_invitation::organization_id.eq(org_id))
+        .filter(organization_invitation::status.eq("pending"))
+        .filter(organization_invitation::expires_at.gt(chrono::Utc::now().naive_utc()))
+        .order(organization_invitation::created_at.desc())
+        .select((OrganizationInvitation::as_select(), user::username))
+        .load(&mut self.conn)?;
+    Ok(invitations)
+    }
+
+    /// Pending, unexpired invitations addressed to a user, with the organization's name
+    pub fn list_user_invitations(
+        &mut self,
+        input_user_id: String
+    ) -> Result<Vec<(OrganizationInvitation, String)>, diesel::result::Error> {
+        use crate::schema::{organization, organization_invitation};
+        let invitations = organization_invitation::table
+        .inner_join(organization::table)
+        .filter(organization_invitation::invitee_user_id.eq(input_user_id))
+        .filter(organization_invitation::status.eq("pending"))
+        .filter(organization_invitation::expires_at.gt(chrono::Utc::now().naive_utc()))
+        .order(organization_invitation::created_at.desc())
+        .select((OrganizationInvitation::as_select(), organization::name))
+        .load(&mut self.conn)?;
+    Ok(invitations)
+    }
+
+    /// Accept a pending invitation addressed to `input_user_id`, joining (or re-roling) them
+    pub fn accept_invitation(
+        &mut self,
+        invitation_id: String,
+        input_user_id: String
+    ) -> Result<OrganizationInvitation, diesel::result::Error> {
+        use crate::schema::{organization_invitation, organization_member};
+        let now = chrono::Utc::now().naive_utc();
+
+        self.conn.transaction(|conn| {
+            let invitation = diesel::update(organization_invitation::table)
+            .filter(organization_invitation::id.eq(invitation_id))
+            .filter(organization_invitation::invitee_user_id.eq(input_user_id.clone()))
+            .filter(organization_invitation::status.eq("pending"))
+            .filter(organization_invitation::expires_at.gt(now))
+            .set(organization_invitation::status.eq("accepted"))
+            .returning(OrganizationInvitation::as_returning())
+            .get_result(conn)?;
+
+            diesel::insert_into(organization_member::table)
+            .values(&OrganizationMember {
+                organization_id: invitation.organization_id.clone(),
+                user_id: input_user_id,
+                role: invitation.role.clone(),
+                created_at: now,
+            })
+            .on_conflict((organization_member::organization_id, organization_member::user_id))
+            .do_update()
+            .set(organization_member::role.eq(invitation.role.clone()))
+            .execute(conn)?;
+
+            Ok(invitation)
+        })
+    }
+
+    /// Move a pending invitation to `declined` (by the invitee) or `revoked` (by an admin)
+    pub fn close_invitation(
+        &mut self,
+        invitation_id: String,
+        new_status: &str
+    ) -> Result<OrganizationInvitation, diesel::result::Error> {
+        use crate::schema::organization_invitation::dsl::*;
+        let invitation = diesel::update(organization_invitation)
+        .filter(id.eq(invitation_id))
+        .filter(status.eq("pending"))
+        .set(status.eq(new_status))
+        .returning(OrganizationInvitation::as_returning())
+        .get_result(&mut self.conn)?;
+    Ok(invitation)
+    }
+
+    pub fn get_invitation(&mut self, invitation_id: String) -> Result<OrganizationInvitation, diesel::result::Error> {
+        use crate::schema::organization_invitation::dsl::*;
+        let invitation = organization_invitation
+        .filter(id.eq(invitation_id))
+        .select(OrganizationInvitation::as_select())
+        .first(&mut self.conn)?;
+    Ok(invitation)
+    }
+
+    /// Ids of every organization the user belongs to
+    pub fn user_organization_ids(&mut self, input_user_id: String) -> Result<Vec<String>, diesel::result::Error> {
+        use crate::schema::organization_member::dsl::*;
+        let ids = organization_member
+        .filter(user_id.eq(input_user_id))
+        .select(organization_id)
+        .load(&mut self.conn)?;
+    Ok(ids)
+    }
+
+    pub fn find_user_id_by_username(&mut self, input_username: String) -> Result<String, diesel::result::Error> {
+        use crate::schema::user::dsl::*;
+        let found = user
+        .filter(username.eq(input_username))
+        .select(id)
+        .first(&mut self.conn)?;
+    Ok(found)
+    }
+
+    /// Shared organizations that would be left without an owner if the user went away
+    pub fn sole_owner_organizations(&mut self, input_user_id: String) -> Result<Vec<Organization>, diesel::result::Error> {
+        use crate::schema::{organization, organization_member};
+        let owned: Vec<Organization> = organization::table
+        .inner_join(organization_member::table)
+        .filter(organization_member::user_id.eq(input_user_id.clone()))
+        .filter(organization_member::role.eq("owner"))
+        .filter(organization::personal.eq(false))
+        .select(Organization::as_select())
+        .load(&mut self.conn)?;
+
+        let mut blocking = Vec::new();
+        for org in owned {
+            let (owners, members): (i64, i64) = organization_member::table
+            .filter(organization_member::organization_id.eq(org.id.clone()))
+            .select((
+                diesel::dsl::sql::<diesel::sql_types::BigInt>("COUNT(*) FILTER (WHERE role = 'owner')"),
+                diesel::dsl::count_star(),
+            ))
+            .first(&mut self.conn)?;
+            if owners == 1 && members > 1 {
+                blocking.push(org);
+            }
+        }
+    Ok(blocking)
+    }
+}
@@ -0,0 +1,83 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::password::hash_token;
+use crate::store::Store;
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::password_reset_token)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PasswordResetToken {
+    pub id: String,
+    pub user_id: String,
+    token_hash: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+    pub used_at: Option<chrono::NaiveDateTime>,
+}
+
+impl Store {
+    /// Issue a reset token, retiring any earlier one the user hasn't used
+    pub fn create_password_reset_token(
+        &mut self,
+        input_user_id: String,
+        token: &str,
+        expires: chrono::NaiveDateTime
+    ) -> Result<PasswordResetToken, diesel::result::Error> {
+        use crate::schema::password_reset_token::dsl::*;
+        let now = chrono::Utc::now().naive_utc();
+        let reset = PasswordResetToken {
+            id: Uuid::new_v4().to_string(),
+            user_id: input_user_id.clone(),
+            token_hash: hash_token(token),
+            created_at: now,
+            expires_at: expires,
+            used_at: None,
+        };
+
+        self.conn.transaction(|conn| {
+            diesel::update(password_reset_token)
+            .filter(user_id.eq(input_user_id))
+            .filter(used_at.is_null())
+            .set(used_at.eq(Some(now)))
+            .execute(conn)?;
+
+            diesel::insert_into(password_reset_token)
+            .values(&reset)
+            .execute(conn)
+        })?;
+
+    Ok(reset)
+    }
+
+    /// Spend a reset token on a new password hash and sign the user out everywhere.
+    /// Returns the user id, or None if the token is unknown, used or expired.
+    pub fn reset_password(&mut self, token: &str, new_hash: String) -> Result<Option<String>, diesel::result::Error> {
+        use crate::schema::{auth_session, password_reset_token, user};
+        let now = chrono::Utc::now().naive_utc();
+
+        self.conn.transaction(|conn| {
+            let owner: Option<String> = diesel::update(password_reset_token::table)
+            .filter(password_reset_token::token_hash.eq(hash_token(token)))
+            .filter(password_reset_token::used_at.is_null())
+            .filter(password_reset_token::expires_at.gt(now))
+            .set(password_reset_token::used_at.eq(Some(now)))
+            .returning(password_reset_token::user_id)
+            .get_result(conn)
+            .optional()?;
+            let Some(owner) = owner else {
+                return Ok(None);
+            };
+
+            diesel::update(user::table.filter(user::id.eq(owner.clone())))
+            .set(user::password.eq(new_hash))
+            .execute(conn)?;
+            diesel::update(auth_session::table)
+            .filter(auth_session::user_id.eq(owner.clone()))
+            .filter(auth_session::revoked_at.is_null())
+            .set(auth_session::revoked_at.eq(Some(now)))
+            .execute(conn)?;
+            Ok(Some(owner))
+        })
+    }
+}
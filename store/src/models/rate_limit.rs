@@ -0,0 +1,103 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Int4, Int8, Text, Timestamp};
+
+use crate::store::Store;
+
+#[derive(QueryableByName)]
+struct Hits {
+    #[diesel(sql_type = Int4)]
+    hits: i32,
+    #[diesel(sql_type = Timestamp)]
+    window_start: chrono::NaiveDateTime,
+}
+
+#[derive(QueryableByName)]
+struct Failures {
+    #[diesel(sql_type = Int4)]
+    failures: i32,
+}
+
+// The counters are read-modify-write, so they are single upserts rather than DSL
+// queries to stay correct with several API processes hitting the same key.
+
+impl Store {
+    /// Count a request against `limit_key` in a fixed window of `window_seconds`,
+    /// returning the hits so far in the window and when the window started
+    pub fn rate_limit_hit(
+        &mut self,
+        limit_key: &str,
+        window_seconds: i64
+    ) -> Result<(i32, chrono::NaiveDateTime), diesel::result::Error> {
+        let row: Hits = diesel::sql_query(
+            "INSERT INTO rate_limit (key, window_start, hits) VALUES ($1, $2, 1)
+             ON CONFLICT (key) DO UPDATE SET
+                 hits = CASE WHEN rate_limit.window_start <= $2 - make_interval(secs => $3)
+                             THEN 1 ELSE rate_limit.hits + 1 END,
+                 window_start = CASE WHEN rate_limit.window_start <= $2 - make_interval(secs => $3)
+                                     THEN $2 ELSE rate_limit.window_start END
+             RETURNING hits, window_start"
+        )
+        .bind::<Text, _>(limit_key)
+        .bind::<Timestamp, _>(chrono::Utc::now().naive_utc())
+        .bind::<Int8, _>(window_seconds)
+        .get_result(&mut self.conn)?;
+    Ok((row.hits, row.window_start))
+    }
+
+    pub fn rate_limit_locked_until(&mut self, limit_key: &str) -> Result<Option<chrono::NaiveDateTime>, diesel::result::Error> {
+        use crate::schema::rate_limit::dsl::*;
+        let until = rate_limit
+        .filter(key.eq(limit_key))
+        .filter(locked_until.gt(chrono::Utc::now().naive_utc()))
+        .select(locked_until)
+        .first::<Option<chrono::NaiveDateTime>>(&mut self.conn)
+        .optional()?;
+    Ok(until.flatten())
+    }
+
+    /// Add a failed attempt and return the consecutive failure count. Failures older
+    /// than `decay_seconds` are forgotten first.
+    pub fn rate_limit_failure(&mut self, limit_key: &str, decay_seconds: i64) -> Result<i32, diesel::result::Error> {
+        let row: Failures = diesel::sql_query(
+            "INSERT INTO rate_limit (key, window_start, failures, last_failure_at) VALUES ($1, $2, 1, $2)
+             ON CONFLICT (key) DO UPDATE SET
+                 failures = CASE WHEN rate_limit.last_failure_at IS NULL
+                                   OR rate_limit.last_failure_at <= $2 - make_interval(secs => $3)
+                                 THEN 1 ELSE rate_limit.failures + 1 END,
+                 last_failure_at = $2
+             RETURNING failures"
+        )
+        .bind::<Text, _>(limit_key)
+        .bind::<Timestamp, _>(chrono::Utc::now().naive_utc())
+        .bind::<Int8, _>(decay_seconds)
+        .get_result(&mut self.conn)?;
+    Ok(row.failures)
+    }
+
+    pub fn rate_limit_lock(&mut self, limit_key: &str, until: chrono::NaiveDateTime) -> Result<(), diesel::result::Error> {
+        use crate::schema::rate_limit::dsl::*;
+        diesel::update(rate_limit.filter(key.eq(limit_key)))
+        .set(locked_until.eq(Some(until)))
+        .execute(&mut self.conn)?;
+    Ok(())
+    }
+
+    pub fn rate_limit_reset_failures(&mut self, limit_key: &str) -> Result<(), diesel::result::Error> {
+        use crate::schema::rate_limit::dsl::*;
+        diesel::update(rate_limit.filter(key.eq(limit_key)))
+        .set((failures.eq(0), last_failure_at.eq(None::<chrono::NaiveDateTime>), locked_until.eq(None::<chrono::NaiveDateTime>)))
+        .execute(&mut self.conn)?;
+    Ok(())
+    }
+
+    /// Drop rows that no longer hold any live window, failure streak or lock
+    pub fn prune_rate_limits(&mut self, before: chrono::NaiveDateTime) -> Result<usize, diesel::result::Error> {
+        use crate::schema::rate_limit::dsl::*;
+        let pruned = diesel::delete(rate_limit)
+        .filter(window_start.lt(before))
+        .filter(last_failure_at.is_null().or(last_failure_at.lt(before)))
+        .filter(locked_until.is_null().or(locked_until.lt(chrono::Utc::now().naive_utc())))
+        .execute(&mut self.conn)?;
+    Ok(pruned)
+    }
+}
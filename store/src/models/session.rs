@@ -0,0 +1,190 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::password::hash_token;
+use crate::store::Store;
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::auth_session)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuthSession {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::refresh_token)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RefreshToken {
+    pub id: String,
+    pub session_id: String,
+    pub token_hash: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+    pub used_at: Option<chrono::NaiveDateTime>,
+}
+
+pub enum RefreshOutcome {
+    /// The presented token was valid and has been replaced
+    Rotated { user_id: String, session_id: String },
+    /// An already-used token was presented again; the whole session has been revoked
+    Reused,
+    /// Unknown, expired, or belonging to a revoked session
+    Invalid,
+}
+
+impl Store {
+    /// Start a new session for a sign-in, with its first refresh token
+    pub fn create_session(
+        &mut self,
+        user_id_value: String,
+        token: &str,
+        expires_at_value: chrono::NaiveDateTime
+    ) -> Result<AuthSession, diesel::result::Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let new_session = AuthSession {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id_value,
+            created_at: now,
+            revoked_at: None,
+        };
+
+        self.conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(crate::schema::auth_session::table)
+            .values(&new_session)
+            .execute(conn)?;
+
+            diesel::insert_into(crate::schema::refresh_token::table)
+            .values(&RefreshToken {
+                id: Uuid::new_v4().to_string(),
+                session_id: new_session.id.clone(),
+                token_hash: hash_token(token),
+                created_at: now,
+                expires_at: expires_at_value,
+                used_at: None,
+            })
+            .execute(conn)?;
+
+            Ok(())
+        })?;
+
+        Ok(new_session)
+    }
+
+    /// Exchange a refresh token for `replacement`. Presenting a token twice is treated
+    /// as theft and revokes every token in the session.
+    pub fn rotate_refresh_token(
+        &mut self,
+        presented: &str,
+        replacement: &str,
+        expires_at_value: chrono::NaiveDateTime
+    ) -> Result<RefreshOutcome, diesel::result::Error> {
+        use crate::schema::auth_session;
+        use crate::schema::refresh_token;
+
+        let presented_hash = hash_token(presented);
+        let now = chrono::Utc::now().naive_utc();
+
+        self.conn.transaction(|conn| {
+            let Some(token) = refresh_token::table
+            .filter(refresh_token::token_hash.eq(presented_hash))
+            .for_update()
+            .select(RefreshToken::as_select())
+            .first(conn)
+            .optional()? else {
+                return Ok(RefreshOutcome::Invalid);
+            };
+
+            let session = auth_session::table
+            .find(token.session_id.clone())
+            .for_update()
+            .select(AuthSession::as_select())
+            .first(conn)?;
+
+            if session.revoked_at.is_some() {
+                return Ok(RefreshOutcome::Invalid);
+            }
+
+            if token.used_at.is_some() {
+                diesel::update(auth_session::table.find(session.id))
+                .set(auth_session::revoked_at.eq(Some(now)))
+                .execute(conn)?;
+                return Ok(RefreshOutcome::Reused);
+            }
+
+            if token.expires_at <= now {
+                return Ok(RefreshOutcome::Invalid);
+            }
+
+            diesel::update(refresh_token::table.find(token.id))
+            .set(refresh_token::used_at.eq(Some(now)))
+            .execute(conn)?;
+
+            diesel::insert_into(refresh_token::table)
+            .values(&RefreshToken {
+                id: Uuid::new_v4().to_string(),
+                session_id: session.id.clone(),
+                token_hash: hash_token(replacement),
+                created_at: now,
+                expires_at: expires_at_value,
+                used_at: None,
+            })
+            .execute(conn)?;
+
+            Ok(RefreshOutcome::Rotated { user_id: session.user_id, session_id: session.id })
+        })
+    }
+
+    pub fn is_session_active(&mut self, session_id_value: String) -> Result<bool, diesel::result::Error> {
+        use crate::schema::auth_session::dsl::*;
+        let active = auth_session
+        .filter(id.eq(session_id_value))
+        .filter(revoked_at.is_null())
+        .count()
+        .get_result::<i64>(&mut self.conn)?;
+    Ok(active > 0)
+    }
+
+    pub fn revoke_session(
+        &mut self,
+        session_id_value: String,
+        input_user_id: String
+    ) -> Result<usize, diesel::result::Error> {
+        use crate::schema::auth_session::dsl::*;
+        let revoked = diesel::update(auth_session)
+        .filter(id.eq(session_id_value))
+        .filter(user_id.eq(input_user_id))
+        .filter(revoked_at.is_null())
+        .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+        .execute(&mut self.conn)?;
+    Ok(revoked)
+    }
+
+    pub fn revoke_all_sessions(&mut self, input_user_id: String) -> Result<usize, diesel::result::Error> {
+        use crate::schema::auth_session::dsl::*;
+        let revoked = diesel::update(auth_session)
+        .filter(user_id.eq(input_user_id))
+        .filter(revoked_at.is_null())
+        .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+        .execute(&mut self.conn)?;
+    Ok(revoked)
+    }
+
+    /// Revoke every session but `keep_session_id`, e.g. after a password change
+    pub fn revoke_other_sessions(
+        &mut self,
+        input_user_id: String,
+        keep_session_id: String
+    ) -> Result<usize, diesel::result::Error> {
+        use crate::schema::auth_session::dsl::*;
+        let revoked = diesel::update(auth_session)
+        .filter(user_id.eq(input_user_id))
+        .filter(id.ne(keep_session_id))
+        .filter(revoked_at.is_null())
+        .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+        .execute(&mut self.conn)?;
+    Ok(revoked)
+    }
+}
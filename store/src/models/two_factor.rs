@@ -0,0 +1,225 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::password::hash_token;
+use crate::store::Store;
+
+/// Wrong codes allowed against one sign-in challenge before it stops working
+pub const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::user_totp)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UserTotp {
+    pub user_id: String,
+    pub secret: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub enabled_at: Option<chrono::NaiveDateTime>,
+    pub last_used_step: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::recovery_code)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+struct RecoveryCode {
+    id: String,
+    user_id: String,
+    code_hash: String,
+    created_at: chrono::NaiveDateTime,
+    used_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::two_factor_challenge)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TwoFactorChallenge {
+    pub id: String,
+    pub user_id: String,
+    token_hash: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+    pub attempts: i32,
+}
+
+fn insert_recovery_codes(
+    conn: &mut PgConnection,
+    owner_id: &str,
+    codes: &[String]
+) -> Result<(), diesel::result::Error> {
+    use crate::schema::recovery_code::dsl::*;
+    let now = chrono::Utc::now().naive_utc();
+
+    diesel::delete(recovery_code.filter(user_id.eq(owner_id))).execute(conn)?;
+
+    let rows: Vec<RecoveryCode> = codes.iter().map(|code| RecoveryCode {
+        id: Uuid::new_v4().to_string(),
+        user_id: owner_id.to_string(),
+        code_hash: hash_token(code),
+        created_at: now,
+        used_at: None,
+    }).collect();
+    diesel::insert_into(recovery_code).values(&rows).execute(conn)?;
+    Ok(())
+}
+
+impl Store {
+    pub fn get_totp(&mut self, input_user_id: String) -> Result<Option<UserTotp>, diesel::result::Error> {
+        use crate::schema::user_totp::dsl::*;
+        let found = user_totp
+        .filter(user_id.eq(input_user_id))
+        .select(UserTotp::as_select())
+        .first(&mut self.conn)
+        .optional()?;
+    Ok(found)
+    }
+
+    /// Store a fresh, unconfirmed secret, replacing any earlier unconfirmed one
+    pub fn start_totp_enrollment(
+        &mut self,
+        input_user_id: String,
+        new_secret: String
+    ) -> Result<UserTotp, diesel::result::Error> {
+        use crate::schema::user_totp::dsl::*;
+        let row = UserTotp {
+            user_id: input_user_id,
+            secret: new_secret,
+            created_at: chrono::Utc::now().naive_utc(),
+            enabled_at: None,
+            last_used_step: None,
+        };
+
+        diesel::insert_into(user_totp)
+        .values(&row)
+        .on_conflict(user_id)
+        .do_update()
+        .set((
+            secret.eq(row.secret.clone()),
+            created_at.eq(row.created_at),
+            last_used_step.eq(None::<i64>),
+        ))
+        .execute(&mut self.conn)?;
+
+    Ok(row)
+    }
+
+    /// Confirm an enrollment with the time step of a verified code and store the
+    /// user's recovery codes. Fails with NotFound if there is no pending enrollment.
+    pub fn enable_totp(
+        &mut self,
+        input_user_id: String,
+        step: i64,
+        codes: &[String]
+    ) -> Result<(), diesel::result::Error> {
+        use crate::schema::user_totp::dsl::*;
+        let now = chrono::Utc::now().naive_utc();
+
+        self.conn.transaction(|conn| {
+            let updated = diesel::update(user_totp)
+            .filter(user_id.eq(input_user_id.clone()))
+            .filter(enabled_at.is_null())
+            .set((enabled_at.eq(Some(now)), last_used_step.eq(Some(step))))
+            .execute(conn)?;
+            if updated == 0 {
+                return Err(diesel::result::Error::NotFound);
+            }
+            insert_recovery_codes(conn, &input_user_id, codes)
+        })
+    }
+
+    /// Record that a code for `step` was used. Returns false if that step (or a later
+    /// one) was already used, which means the code is being replayed.
+    pub fn accept_totp_step(&mut self, input_user_id: String, step: i64) -> Result<bool, diesel::result::Error> {
+        use crate::schema::user_totp::dsl::*;
+        let updated = diesel::update(user_totp)
+        .filter(user_id.eq(input_user_id))
+        .filter(last_used_step.is_null().or(last_used_step.lt(step)))
+        .set(last_used_step.eq(Some(step)))
+        .execute(&mut self.conn)?;
+    Ok(updated == 1)
+    }
+
+    /// Turn 2FA off, dropping the secret, recovery codes and outstanding challenges
+    pub fn disable_totp(&mut self, input_user_id: String) -> Result<(), diesel::result::Error> {
+        use crate::schema::{recovery_code, two_factor_challenge, user_totp};
+        self.conn.transaction(|conn| {
+            diesel::delete(user_totp::table.filter(user_totp::user_id.eq(input_user_id.clone())))
+            .execute(conn)?;
+            diesel::delete(recovery_code::table.filter(recovery_code::user_id.eq(input_user_id.clone())))
+            .execute(conn)?;
+            diesel::delete(two_factor_challenge::table.filter(two_factor_challenge::user_id.eq(input_user_id)))
+            .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    /// Replace every recovery code the user has with a new set
+    pub fn replace_recovery_codes(&mut self, input_user_id: String, codes: &[String]) -> Result<(), diesel::result::Error> {
+        self.conn.transaction(|conn| insert_recovery_codes(conn, &input_user_id, codes))
+    }
+
+    /// Spend a recovery code; returns false if it is unknown or already used
+    pub fn use_recovery_code(&mut self, input_user_id: String, code: &str) -> Result<bool, diesel::result::Error> {
+        use crate::schema::recovery_code::dsl::*;
+        let updated = diesel::update(recovery_code)
+        .filter(user_id.eq(input_user_id))
+        .filter(code_hash.eq(hash_token(code)))
+        .filter(used_at.is_null())
+        .set(used_at.eq(Some(chrono::Utc::now().naive_utc())))
+        .execute(&mut self.conn)?;
+    Ok(updated == 1)
+    }
+
+    pub fn count_unused_recovery_codes(&mut self, input_user_id: String) -> Result<i64, diesel::result::Error> {
+        use crate::schema::recovery_code::dsl::*;
+        let remaining = recovery_code
+        .filter(user_id.eq(input_user_id))
+        .filter(used_at.is_null())
+        .count()
+        .get_result(&mut self.conn)?;
+    Ok(remaining)
+    }
+
+    pub fn create_two_factor_challenge(
+        &mut self,
+        input_user_id: String,
+        token: &str,
+        expires: chrono::NaiveDateTime
+    ) -> Result<TwoFactorChallenge, diesel::result::Error> {
+        let challenge = TwoFactorChallenge {
+            id: Uuid::new_v4().to_string(),
+            user_id: input_user_id,
+            token_hash: hash_token(token),
+            created_at: chrono::Utc::now().naive_utc(),
+            expires_at: expires,
+            attempts: 0,
+        };
+
+        diesel::insert_into(crate::schema::two_factor_challenge::table)
+        .values(&challenge)
+        .execute(&mut self.conn)?;
+
+    Ok(challenge)
+    }
+
+    /// Look up a live challenge and count an attempt against it in one statement, so
+    /// concurrent guesses can't exceed `MAX_CHALLENGE_ATTEMPTS`
+    pub fn attempt_two_factor_challenge(&mut self, token: &str) -> Result<Option<TwoFactorChallenge>, diesel::result::Error> {
+        use crate::schema::two_factor_challenge::dsl::*;
+        let found = diesel::update(two_factor_challenge)
+        .filter(token_hash.eq(hash_token(token)))
+        .filter(expires_at.gt(chrono::Utc::now().naive_utc()))
+        .filter(attempts.lt(MAX_CHALLENGE_ATTEMPTS))
+        .set(attempts.eq(attempts + 1))
+        .returning(TwoFactorChallenge::as_returning())
+        .get_result(&mut self.conn)
+        .optional()?;
+    Ok(found)
+    }
+
+    pub fn delete_two_factor_challenge(&mut self, challenge_id: String) -> Result<(), diesel::result::Error> {
+        use crate::schema::two_factor_challenge::dsl::*;
+        diesel::delete(two_factor_challenge.filter(id.eq(challenge_id)))
+        .execute(&mut self.conn)?;
+    Ok(())
+    }
+}
@@ -2,28 +2,35 @@ use crate::store::Store;
 use diesel::prelude::*;
 use uuid::Uuid;
 
+use crate::models::organization::insert_organization;
+
 #[derive(Queryable, Selectable, Insertable)]
 #[diesel(table_name = crate::schema::user)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct User {
     pub id: String,
-    username: String,
-    password: String,
+    pub(crate) username: String,
+    pub(crate) password: String,
 }
 
 impl Store {
     pub fn sign_up(&mut self, username:String, password:String) -> Result<String, diesel::result::Error>{
         let id = Uuid::new_v4();
         let u = User {
-            username,
+            username: username.clone(),
             password,
             id: id.to_string()
         };
         
-        diesel::insert_into(crate::schema::user::table)
-            .values(&u)
-            .returning(User::as_returning())
-            .get_result(&mut self.conn)?;
+        // Every user gets a personal organization to hold the websites they create
+        self.conn.transaction(|conn| {
+            diesel::insert_into(crate::schema::user::table)
+                .values(&u)
+                .returning(User::as_returning())
+                .get_result(conn)?;
+
+            insert_organization(conn, username, id.to_string(), true)
+        })?;
 
         Ok(id.to_string())
     }
@@ -40,4 +47,95 @@ impl Store {
                 Err(_) => Err(diesel::result::Error::NotFound),  // Simplified - treat verification errors as not found
             }
     }
-}
\ No newline at end of file
+
+    pub fn get_username(&mut self, input_user_id: String) -> Result<String, diesel::result::Error> {
+        use crate::schema::user::dsl::*;
+        let found = user
+        .filter(id.eq(input_user_id))
+        .select(username)
+        .first(&mut self.conn)?;
+    Ok(found)
+    }
+
+    /// Check `input_password` against the user's stored hash; false for SSO-only users
+    pub fn verify_user_password(&mut self, input_user_id: String, input_password: &str) -> Result<bool, diesel::result::Error> {
+        use crate::schema::user::dsl::*;
+        let hash: String = user
+        .filter(id.eq(input_user_id))
+        .select(password)
+        .first(&mut self.conn)?;
+    Ok(crate::password::verify_password(input_password, &hash).unwrap_or(false))
+    }
+
+    /// Whether the user can sign in with a password at all (SSO-provisioned users can't)
+    pub fn has_password(&mut self, input_user_id: String) -> Result<bool, diesel::result::Error> {
+        use crate::schema::user::dsl::*;
+        let hash: String = user
+        .filter(id.eq(input_user_id))
+        .select(password)
+        .first(&mut self.conn)?;
+    Ok(hash != crate::models::identity::NO_PASSWORD)
+    }
+
+    pub fn set_password(&mut self, input_user_id: String, new_hash: String) -> Result<(), diesel::result::Error> {
+        use crate::schema::user::dsl::*;
+        let updated = diesel::update(user.filter(id.eq(input_user_id)))
+        .set(password.eq(new_hash))
+        .execute(&mut self.conn)?;
+    if updated == 0 {
+        return Err(diesel::result::Error::NotFound);
+    }
+    Ok(())
+    }
+
+    /// Delete a user and everything they own. Their personal organization and any
+    /// organization they are the only member of go with them; websites they created in
+    /// organizations that live on are handed to one of that organization's owners so
+    /// the `website.user_id` cascade doesn't take shared monitors down. Returns the ids
+    /// of those organizations that live on.
+    pub fn delete_user(&mut self, input_user_id: String) -> Result<Vec<String>, diesel::result::Error> {
+        use crate::schema::{organization, organization_member, user};
+        use diesel::dsl::{exists, not};
+
+        self.conn.transaction(|conn| {
+            let other_members = organization_member::table
+                .filter(organization_member::organization_id.eq(organization::id))
+                .filter(organization_member::user_id.ne(input_user_id.clone()));
+            let memberships: Vec<String> = organization_member::table
+                .filter(organization_member::user_id.eq(input_user_id.clone()))
+                .select(organization_member::organization_id)
+                .load(conn)?;
+            let doomed: Vec<String> = organization::table
+                .filter(organization::id.eq_any(&memberships))
+                .filter(organization::personal.eq(true).or(not(exists(other_members))))
+                .select(organization::id)
+                .load(conn)?;
+            diesel::delete(organization::table.filter(organization::id.eq_any(&doomed)))
+                .execute(conn)?;
+
+            diesel::sql_query(
+                "UPDATE website SET user_id = (
+                     SELECT m.user_id FROM organization_member m
+                     WHERE m.organization_id = website.organization_id
+                       AND m.user_id <> $1
+                     ORDER BY (m.role = 'owner') DESC, m.created_at ASC
+                     LIMIT 1
+                 )
+                 WHERE website.user_id = $1
+                   AND EXISTS (
+                     SELECT 1 FROM organization_member m
+                     WHERE m.organization_id = website.organization_id AND m.user_id <> $1
+                 )"
+            )
+            .bind::<diesel::sql_types::Text, _>(input_user_id.clone())
+            .execute(conn)?;
+
+            let deleted = diesel::delete(user::table.filter(user::id.eq(input_user_id)))
+                .execute(conn)?;
+            if deleted == 0 {
+                return Err(diesel::result::Error::NotFound);
+            }
+            Ok(memberships.into_iter().filter(|id| !doomed.contains(id)).collect())
+        })
+    }
+}
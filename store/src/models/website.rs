@@ -1,5 +1,9 @@
+use crate::models::incident::Incident;
 use crate::store::Store;
 use chrono::Utc;
+use diesel::dsl::sql;
+use diesel::pg::Pg;
+use diesel::sql_types::{Double, Nullable, Text};
 use diesel::{Insertable, prelude::*};
 use uuid::Uuid;
 #[derive(Queryable, Selectable, Insertable)]
@@ -15,23 +19,205 @@ pub struct Website {
     pub last_checked: Option<chrono::NaiveDateTime>,
     pub last_down_time: Option<chrono::NaiveDateTime>,
     pub response_time_ms: Option<i32>,
+    pub organization_id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// A JSON object of string tags, e.g. `{"team": "payments"}`
+    pub tags: serde_json::Value,
+    /// Set while checks are paused
+    pub paused_at: Option<chrono::NaiveDateTime>,
+    /// When a paused website resumes by itself, if ever
+    pub resume_at: Option<chrono::NaiveDateTime>,
+    /// Stable name from a monitors-as-code document, unique within the organization
+    pub external_key: Option<String>,
+    pub check_interval_seconds: i32,
+    /// A JSON object of conditions a response must meet to count as up
+    pub assertions: serde_json::Value,
 }
 
+/// What a user can say about a website besides its URL
+pub struct WebsiteDetails {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub tags: serde_json::Value,
+    pub external_key: Option<String>,
+    pub check_interval_seconds: i32,
+    pub assertions: serde_json::Value,
+}
+
+/// Fields to change on a website; `None` leaves a field as it is
+#[derive(AsChangeset)]
+#[diesel(table_name = crate::schema::website)]
+pub struct WebsiteChanges {
+    pub url: Option<String>,
+    pub name: Option<Option<String>>,
+    pub description: Option<Option<String>>,
+    pub tags: Option<serde_json::Value>,
+    pub external_key: Option<Option<String>>,
+    pub check_interval_seconds: Option<i32>,
+    pub assertions: Option<serde_json::Value>,
+}
+
+/// Everything it takes to bring an organization's websites in line with a document
+#[derive(Default)]
+pub struct WebsiteSync {
+    pub create: Vec<(String, WebsiteDetails)>,
+    /// `(website id, changes)`
+    pub update: Vec<(String, WebsiteChanges)>,
+    /// Ids of websites to delete
+    pub delete: Vec<String>,
+}
+
+/// The websites a `WebsiteSync` touched, as they are after it
+#[derive(Default)]
+pub struct SyncedWebsites {
+    pub created: Vec<Website>,
+    pub updated: Vec<Website>,
+    pub deleted: Vec<Website>,
+}
+
+/// Where a website stands as of its last check
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum WebsiteState {
+    /// Not checked yet
+    Pending,
+    Up,
+    Down,
+    /// Not being checked until resumed
+    Paused,
+}
+
+impl Website {
+    pub fn state(&self) -> WebsiteState {
+        if self.paused_at.is_some() {
+            return WebsiteState::Paused;
+        }
+        match (self.last_checked, self.is_up) {
+            (None, _) => WebsiteState::Pending,
+            (Some(_), Some(false)) => WebsiteState::Down,
+            (Some(_), _) => WebsiteState::Up,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub enum WebsiteSort {
+    #[default]
+    TimeAdded,
+    Url,
+    /// Down before up, then never-checked and paused websites
+    Status,
+    ResponseTime,
+    LastChecked,
+    Uptime,
+}
+
+/// Narrowing, ordering and paging for `list_website_summaries`
+pub struct WebsiteListQuery {
+    pub organization_id: Option<String>,
+    pub state: Option<WebsiteState>,
+    /// Case-insensitive substring of the URL, name or description
+    pub search: Option<String>,
+    /// `(key, value)` tags the website must all carry
+    pub tags: Vec<(String, String)>,
+    pub sort: WebsiteSort,
+    pub descending: bool,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// A website with what a dashboard shows next to it
+pub struct WebsiteSummary {
+    pub website: Website,
+    pub open_incident: Option<Incident>,
+    /// Percentage of the last 24 hours' checks that were up; None without checks
+    pub uptime_24h: Option<f64>,
+}
 
+/// Uptime is the share of checks that were up. Paused websites aren't checked, so time
+/// spent paused counts neither for nor against them.
+const UPTIME_24H: &str = "CAST((
+    SELECT 100.0 * avg(CASE WHEN check_history.is_up THEN 1 ELSE 0 END)
+    FROM check_history
+    WHERE check_history.website_id = website.id
+    AND check_history.checked_at >= (now() AT TIME ZONE 'UTC') - INTERVAL '24 hours'
+) AS DOUBLE PRECISION) AS uptime_24h";
+
+/// Websites the user can see that pass the query's filters
+fn matching_websites(input_user_id: String, query: &WebsiteListQuery) -> crate::schema::website::BoxedQuery<'static, Pg> {
+    use crate::schema::{organization_member, website};
+    let member_of = organization_member::table
+    .filter(organization_member::user_id.eq(input_user_id))
+    .select(organization_member::organization_id);
+    let mut websites = website::table
+    .filter(website::organization_id.eq_any(member_of))
+    .into_boxed();
+    if let Some(org_id) = &query.organization_id {
+        websites = websites.filter(website::organization_id.eq(org_id.clone()));
+    }
+    websites = match query.state {
+        Some(WebsiteState::Paused) => websites.filter(website::paused_at.is_not_null()),
+        Some(WebsiteState::Pending) => websites
+        .filter(website::paused_at.is_null())
+        .filter(website::last_checked.is_null()),
+        Some(WebsiteState::Up) => websites
+        .filter(website::paused_at.is_null())
+        .filter(website::last_checked.is_not_null())
+        .filter(website::is_up.is_distinct_from(false)),
+        Some(WebsiteState::Down) => websites
+        .filter(website::paused_at.is_null())
+        .filter(website::last_checked.is_not_null())
+        .filter(website::is_up.eq(false)),
+        None => websites,
+    };
+    if let Some(search) = &query.search {
+        let escaped = search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+        websites = websites.filter(
+            website::url.ilike(pattern.clone())
+            .or(website::name.ilike(pattern.clone()))
+            .or(website::description.ilike(pattern))
+        );
+    }
+    for (key, value) in &query.tags {
+        let mut tag = serde_json::Map::new();
+        tag.insert(key.clone(), serde_json::Value::String(value.clone()));
+        websites = websites.filter(website::tags.contains(serde_json::Value::Object(tag)));
+    }
+    websites
+}
+
+fn new_website(user_id: String, organization_id: String, url: String, details: WebsiteDetails) -> Website {
+    Website{
+        user_id,
+        organization_id,
+        url,
+        name: details.name,
+        description: details.description,
+        tags: details.tags,
+        external_key: details.external_key,
+        check_interval_seconds: details.check_interval_seconds,
+        assertions: details.assertions,
+        id: Uuid::new_v4().to_string(),
+        time_added: Utc::now().naive_utc(),
+        is_up: Some(true),
+        last_checked: None,
+        last_down_time: None,
+        response_time_ms: None,
+        paused_at: None,
+        resume_at: None,
+    }
+}
 
 impl Store {
-    pub fn create_website(&mut self, user_id: String, url: String) -> Result<Website, diesel::result::Error> {
-        let id = Uuid::new_v4();
-        let website = Website{
-            user_id,
-            url,
-            id:id.to_string(),
-            time_added: Utc::now().naive_utc(),
-            is_up: Some(true),
-            last_checked: None,
-            last_down_time: None,
-            response_time_ms: None
-        };
+    pub fn create_website(
+        &mut self,
+        user_id: String,
+        organization_id: String,
+        url: String,
+        details: WebsiteDetails
+    ) -> Result<Website, diesel::result::Error> {
+        let website = new_website(user_id, organization_id, url, details);
 
         diesel::insert_into(crate::schema::website::table)
         .values(&website)
@@ -49,28 +235,118 @@ impl Store {
     Ok(website_result)
     }
 
-    pub fn list_websites(&mut self, input_user_id: String) -> Result<Vec<Website>, diesel::result::Error>{
+    /// A page of the websites in every organization the user belongs to, with their
+    /// current state, plus how many websites match the query in total
+    pub fn list_website_summaries(
+        &mut self,
+        input_user_id: String,
+        query: &WebsiteListQuery
+    ) -> Result<(Vec<WebsiteSummary>, i64), diesel::result::Error> {
+        let total = matching_websites(input_user_id.clone(), query)
+        .count()
+        .get_result(&mut self.conn)?;
+        let ids = matching_websites(input_user_id, query).select(crate::schema::website::id);
+        let summaries = self.load_summaries(ids, query.sort, query.descending, query.limit, query.offset)?;
+    Ok((summaries, total))
+    }
+
+    pub fn get_website_summary(&mut self, input_id: String) -> Result<WebsiteSummary, diesel::result::Error> {
         use crate::schema::website::dsl::*;
-        let websites = website.filter
-        (user_id.eq(input_user_id.clone()))
-        .order(time_added.desc())
-        .select(Website::as_select())
-        .load(&mut self.conn)?;
-    Ok(websites)  
+        let ids = website.filter(id.eq(input_id)).select(id).into_boxed();
+        self.load_summaries(ids, WebsiteSort::TimeAdded, true, 1, 0)?
+        .pop()
+        .ok_or(diesel::result::Error::NotFound)
+    }
+
+    /// One joined query for the websites in `ids`: the open incident comes from a left
+    /// join (there is at most one per website) and 24h uptime from a correlated subquery
+    /// on the `(website_id, checked_at)` index.
+    fn load_summaries(
+        &mut self,
+        ids: crate::schema::website::BoxedQuery<'static, Pg, Text>,
+        sort: WebsiteSort,
+        descending: bool,
+        limit: i64,
+        offset: i64
+    ) -> Result<Vec<WebsiteSummary>, diesel::result::Error> {
+        use crate::schema::{incident, website};
+
+        let mut query = website::table
+        .left_join(incident::table.on(
+            incident::website_id.eq(website::id)
+            .and(incident::resolved_at.is_null())
+        ))
+        .filter(website::id.eq_any(ids))
+        .select((
+            Website::as_select(),
+            Option::<Incident>::as_select(),
+            sql::<Nullable<Double>>(UPTIME_24H),
+        ))
+        .into_boxed();
+
+        let uptime = sql::<Nullable<Double>>("uptime_24h");
+        query = match (sort, descending) {
+            (WebsiteSort::TimeAdded, false) => query.order_by(website::time_added.asc()),
+            (WebsiteSort::TimeAdded, true) => query.order_by(website::time_added.desc()),
+            (WebsiteSort::Url, false) => query.order_by(website::url.asc()),
+            (WebsiteSort::Url, true) => query.order_by(website::url.desc()),
+            (WebsiteSort::Status, false) => query.order_by((
+                website::paused_at.is_not_null().asc(),
+                website::last_checked.is_null().asc(),
+                website::is_up.asc(),
+            )),
+            (WebsiteSort::Status, true) => query.order_by((
+                website::paused_at.is_not_null().asc(),
+                website::last_checked.is_null().asc(),
+                website::is_up.desc(),
+            )),
+            (WebsiteSort::ResponseTime, false) => query.order_by(website::response_time_ms.asc().nulls_last()),
+            (WebsiteSort::ResponseTime, true) => query.order_by(website::response_time_ms.desc().nulls_last()),
+            (WebsiteSort::LastChecked, false) => query.order_by(website::last_checked.asc().nulls_last()),
+            (WebsiteSort::LastChecked, true) => query.order_by(website::last_checked.desc().nulls_last()),
+            (WebsiteSort::Uptime, false) => query.order_by(uptime.asc().nulls_last()),
+            (WebsiteSort::Uptime, true) => query.order_by(uptime.desc().nulls_last()),
+        };
+
+        let rows = query
+        .then_order_by(website::id.asc())
+        .limit(limit)
+        .offset(offset)
+        .load::<(Website, Option<Incident>, Option<f64>)>(&mut self.conn)?;
+    Ok(rows.into_iter()
+        .map(|(website, open_incident, uptime_24h)| WebsiteSummary { website, open_incident, uptime_24h })
+        .collect())
+    }
+
+    /// A website together with the user's role in the organization that owns it
+    /// (None if they are not a member)
+    pub fn get_website_access(
+        &mut self,
+        input_id: String,
+        input_user_id: String
+    ) -> Result<(Website, Option<String>), diesel::result::Error> {
+        use crate::schema::{organization_member, website};
+        let found = website::table
+        .left_join(organization_member::table.on(
+            organization_member::organization_id.eq(website::organization_id)
+            .and(organization_member::user_id.eq(input_user_id))
+        ))
+        .filter(website::id.eq(input_id))
+        .select((Website::as_select(), organization_member::role.nullable()))
+        .first(&mut self.conn)?;
+    Ok(found)
     }
 
     pub fn update_website(
         &mut self,
         website_id: String,
-        input_user_id: String,
-        new_url: String
+        changes: WebsiteChanges
     ) -> Result<Website, diesel::result::Error> {
         use crate::schema::website::dsl::*;
         let updated = diesel::update(
             website
         ).filter(id.eq(website_id.clone()))
-        .filter(user_id.eq(input_user_id.clone()))
-        .set(url.eq(new_url))
+        .set(&changes)
         .returning(Website::as_returning())
         .get_result(&mut self.conn)?;
     Ok(updated)
@@ -78,13 +354,11 @@ impl Store {
 
     pub fn delete_website (
         &mut self,
-        website_id: String,
-        input_user_id :String 
+        website_id: String
     ) -> Result< usize, diesel::result::Error> {
         use crate::schema::website::dsl::*;
         let deleted = diesel::delete(website)
         .filter(id.eq(website_id.clone()))
-        .filter(user_id.eq(input_user_id.clone()))
         .execute(&mut self.conn)?;
     if deleted == 0 {
         return Err(diesel::result::Error::NotFound);
@@ -92,12 +366,116 @@ impl Store {
     Ok(deleted)
 }
 
-pub fn get_all_websites(&mut self) -> Result<Vec<Website>, diesel::result::Error> {
+/// Every website that isn't paused, i.e. those the worker should check
+pub fn get_active_websites(&mut self) -> Result<Vec<Website>, diesel::result::Error> {
     use crate::schema::website::dsl::*;
 
-    let all_websites = website.select(Website::as_select())
+    let active_websites = website
+    .filter(paused_at.is_null())
+    .select(Website::as_select())
     .load(&mut self.conn)?;
 
-    Ok(all_websites)
+    Ok(active_websites)
+}
+
+    /// Stop checking a website, until `until` if given. Any open incident is resolved now so
+    /// the pause isn't counted as downtime; pausing again only moves the resume time.
+    pub fn pause_website(
+        &mut self,
+        website_id: String,
+        until: Option<chrono::NaiveDateTime>
+    ) -> Result<Website, diesel::result::Error> {
+        use crate::schema::{incident, website};
+        let now = Utc::now().naive_utc();
+        self.conn.transaction(|conn| {
+            let current = website::table
+            .filter(website::id.eq(website_id.clone()))
+            .select(Website::as_select())
+            .first(conn)?;
+            let paused = diesel::update(website::table.filter(website::id.eq(website_id.clone())))
+            .set((
+                website::paused_at.eq(current.paused_at.or(Some(now))),
+                website::resume_at.eq(until),
+            ))
+            .returning(Website::as_returning())
+            .get_result(conn)?;
+
+            diesel::update(incident::table
+                .filter(incident::website_id.eq(website_id))
+                .filter(incident::resolved_at.is_null()))
+            .set(incident::resolved_at.eq(Some(now)))
+            .execute(conn)?;
+            Ok(paused)
+        })
+    }
+
+    pub fn resume_website(&mut self, website_id: String) -> Result<Website, diesel::result::Error> {
+        use crate::schema::website::dsl::*;
+        let resumed = diesel::update(website.filter(id.eq(website_id)))
+        .set((paused_at.eq(None::<chrono::NaiveDateTime>), resume_at.eq(None::<chrono::NaiveDateTime>)))
+        .returning(Website::as_returning())
+        .get_result(&mut self.conn)?;
+    Ok(resumed)
+    }
+
+    /// Resume every website whose pause has run out, returning them
+    pub fn resume_due_websites(&mut self, now: chrono::NaiveDateTime) -> Result<Vec<Website>, diesel::result::Error> {
+        use crate::schema::website::dsl::*;
+        let resumed = diesel::update(website
+            .filter(paused_at.is_not_null())
+            .filter(resume_at.le(now)))
+        .set((paused_at.eq(None::<chrono::NaiveDateTime>), resume_at.eq(None::<chrono::NaiveDateTime>)))
+        .returning(Website::as_returning())
+        .get_results(&mut self.conn)?;
+    Ok(resumed)
+    }
+
+    /// Every website an organization owns, oldest first
+    pub fn list_organization_websites(&mut self, org_id: String) -> Result<Vec<Website>, diesel::result::Error> {
+        use crate::schema::website::dsl::*;
+        let websites = website
+        .filter(organization_id.eq(org_id))
+        .order((time_added.asc(), id.asc()))
+        .select(Website::as_select())
+        .load(&mut self.conn)?;
+    Ok(websites)
+    }
+
+    /// Apply a whole sync at once, so a document is either applied completely or not at all
+    pub fn sync_websites(
+        &mut self,
+        user_id: String,
+        org_id: String,
+        sync: WebsiteSync
+    ) -> Result<SyncedWebsites, diesel::result::Error> {
+        use crate::schema::website;
+        self.conn.transaction(|conn| {
+            let mut synced = SyncedWebsites::default();
+            for (url, details) in sync.create {
+                let created = diesel::insert_into(website::table)
+                .values(&new_website(user_id.clone(), org_id.clone(), url, details))
+                .returning(Website::as_returning())
+                .get_result(conn)?;
+                synced.created.push(created);
+            }
+            for (website_id, changes) in sync.update {
+                let updated = diesel::update(website::table
+                    .filter(website::id.eq(website_id))
+                    .filter(website::organization_id.eq(org_id.clone())))
+                .set(&changes)
+                .returning(Website::as_returning())
+                .get_result(conn)?;
+                synced.updated.push(updated);
+            }
+            for website_id in sync.delete {
+                let deleted = diesel::delete(website::table
+                    .filter(website::id.eq(website_id))
+                    .filter(website::organization_id.eq(org_id.clone())))
+                .returning(Website::as_returning())
+                .get_result(conn)?;
+                synced.deleted.push(deleted);
+            }
+            Ok(synced)
+        })
+    }
 }
-}
\ No newline at end of file
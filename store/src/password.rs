@@ -1,4 +1,5 @@
 use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use sha2::{Digest, Sha256};
 
 pub fn verify_password(password: &str, hash: &str) -> Result<bool, argon2::password_hash::Error> {
     let parsed_hash = PasswordHash::new(hash)?;
@@ -9,4 +10,13 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, argon2::passw
         Err(argon2::password_hash::Error::Password) => Ok(false),
         Err(e) => Err(e),
     }
-}
\ No newline at end of file
+}
+
+/// Hash a high-entropy token (refresh token, API key). These are random rather than
+/// user-chosen, so a plain SHA-256 is enough to keep them out of the database.
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -6,6 +6,54 @@ pub mod sql_types {
     pub struct WebsiteStatus;
 }
 
+diesel::table! {
+    api_key (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        user_id -> Text,
+        name -> Text,
+        #[max_length = 16]
+        prefix -> Varchar,
+        #[max_length = 64]
+        key_hash -> Varchar,
+        #[max_length = 16]
+        scope -> Varchar,
+        created_at -> Timestamp,
+        expires_at -> Nullable<Timestamp>,
+        last_used_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    audit_log (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        organization_id -> Nullable<Text>,
+        actor_id -> Nullable<Text>,
+        #[max_length = 64]
+        action -> Varchar,
+        #[max_length = 32]
+        target_type -> Varchar,
+        target_id -> Nullable<Text>,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        #[max_length = 64]
+        ip -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    auth_session (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        user_id -> Text,
+        created_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     check_history (id) {
         #[max_length = 255]
@@ -20,6 +68,133 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    incident (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        #[max_length = 255]
+        website_id -> Varchar,
+        started_at -> Timestamp,
+        resolved_at -> Nullable<Timestamp>,
+        cause -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    notification_channel (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        #[max_length = 255]
+        organization_id -> Varchar,
+        name -> Text,
+        url -> Text,
+        match_tags -> Jsonb,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    oidc_login_state (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        #[max_length = 64]
+        state_hash -> Varchar,
+        code_verifier -> Text,
+        nonce -> Text,
+        link_user_id -> Nullable<Text>,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    organization (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        name -> Text,
+        personal -> Bool,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    organization_invitation (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        #[max_length = 255]
+        organization_id -> Varchar,
+        invitee_user_id -> Text,
+        invited_by -> Nullable<Text>,
+        #[max_length = 16]
+        role -> Varchar,
+        #[max_length = 16]
+        status -> Varchar,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    organization_member (organization_id, user_id) {
+        #[max_length = 255]
+        organization_id -> Varchar,
+        user_id -> Text,
+        #[max_length = 16]
+        role -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    password_reset_token (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        user_id -> Text,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    rate_limit (key) {
+        key -> Text,
+        window_start -> Timestamp,
+        hits -> Int4,
+        failures -> Int4,
+        last_failure_at -> Nullable<Timestamp>,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    recovery_code (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        user_id -> Text,
+        #[max_length = 64]
+        code_hash -> Varchar,
+        created_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    refresh_token (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        #[max_length = 255]
+        session_id -> Varchar,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     region (id) {
         id -> Text,
@@ -27,6 +202,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    two_factor_challenge (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        user_id -> Text,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        attempts -> Int4,
+    }
+}
+
 diesel::table! {
     user (id) {
         id -> Text,
@@ -35,6 +223,27 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    user_identity (id) {
+        #[max_length = 255]
+        id -> Varchar,
+        user_id -> Text,
+        issuer -> Text,
+        subject -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_totp (user_id) {
+        user_id -> Text,
+        secret -> Text,
+        created_at -> Timestamp,
+        enabled_at -> Nullable<Timestamp>,
+        last_used_step -> Nullable<Int8>,
+    }
+}
+
 diesel::table! {
     website (id) {
         id -> Text,
@@ -45,6 +254,16 @@ diesel::table! {
         last_checked -> Nullable<Timestamp>,
         last_down_time -> Nullable<Timestamp>,
         response_time_ms -> Nullable<Int4>,
+        #[max_length = 255]
+        organization_id -> Varchar,
+        name -> Nullable<Text>,
+        description -> Nullable<Text>,
+        tags -> Jsonb,
+        paused_at -> Nullable<Timestamp>,
+        resume_at -> Nullable<Timestamp>,
+        external_key -> Nullable<Text>,
+        check_interval_seconds -> Int4,
+        assertions -> Jsonb,
     }
 }
 
@@ -62,9 +281,46 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(api_key -> user (user_id));
+diesel::joinable!(auth_session -> user (user_id));
 diesel::joinable!(check_history -> website (website_id));
+diesel::joinable!(incident -> website (website_id));
+diesel::joinable!(notification_channel -> organization (organization_id));
+diesel::joinable!(oidc_login_state -> user (link_user_id));
+diesel::joinable!(organization_invitation -> organization (organization_id));
+diesel::joinable!(organization_member -> organization (organization_id));
+diesel::joinable!(organization_member -> user (user_id));
+diesel::joinable!(password_reset_token -> user (user_id));
+diesel::joinable!(recovery_code -> user (user_id));
+diesel::joinable!(refresh_token -> auth_session (session_id));
+diesel::joinable!(two_factor_challenge -> user (user_id));
+diesel::joinable!(user_identity -> user (user_id));
+diesel::joinable!(user_totp -> user (user_id));
+diesel::joinable!(website -> organization (organization_id));
 diesel::joinable!(website -> user (user_id));
 diesel::joinable!(website_tick -> region (region_id));
 diesel::joinable!(website_tick -> website (website_id));
 
-diesel::allow_tables_to_appear_in_same_query!(check_history, region, user, website, website_tick,);
+diesel::allow_tables_to_appear_in_same_query!(
+    api_key,
+    audit_log,
+    auth_session,
+    check_history,
+    incident,
+    notification_channel,
+    oidc_login_state,
+    organization,
+    organization_invitation,
+    organization_member,
+    password_reset_token,
+    rate_limit,
+    recovery_code,
+    refresh_token,
+    region,
+    two_factor_challenge,
+    user,
+    user_identity,
+    user_totp,
+    website,
+    website_tick,
+);
@@ -7,8 +7,11 @@ pub struct Store {
 impl Store {
     pub fn new() -> Result<Self, ConnectionError> {
         let config = Config::default();
-        let connection = PgConnection::establish(&config.db_url)?;
+        Self::connect(&config.db_url)
+    }
+
+    pub fn connect(database_url: &str) -> Result<Self, ConnectionError> {
+        let connection = PgConnection::establish(database_url)?;
         Ok(Self { conn: connection })
-        
     }
 }
\ No newline at end of file